@@ -0,0 +1,204 @@
+#![allow(deprecated, unexpected_cfgs)]
+//! Example governance-style program that drives bounty-split purely through CPI.
+//!
+//! Each `Dao` owns two PDAs that never sign a transaction themselves:
+//! - `treasury` (`[b"treasury", dao]`) is the escrow requester and holds the bounty tokens.
+//! - `council` (`[b"council", dao]`) is the escrow arbiter.
+//!
+//! `Dao::authority` stands in for a governance proposal executor; in Realms or
+//! Squads this would be the governance or vault PDA itself.
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use bounty_split::cpi::accounts::{FundEscrow, InitializeEscrow, Release, ResolveDispute};
+use bounty_split::instructions::InitializeParams;
+use bounty_split::program::BountySplit;
+
+declare_id!("5zFgT1iExU2X8eYGRrPZmMsipkXCuWA6UbUhxi5Rid6F");
+
+#[program]
+pub mod bounty_split_caller {
+    use super::*;
+
+    pub fn init_dao(ctx: Context<InitDao>) -> Result<()> {
+        ctx.accounts.dao.set_inner(Dao {
+            authority: ctx.accounts.authority.key(),
+            bump: ctx.bumps.dao,
+            treasury_bump: ctx.bumps.treasury,
+            council_bump: ctx.bumps.council,
+        });
+        Ok(())
+    }
+
+    /// Creates an escrow requested by the treasury PDA and arbitrated by the council PDA.
+    /// `remaining_accounts` are forwarded to bounty-split unchanged.
+    pub fn create_bounty<'info>(ctx: Context<'_, '_, '_, 'info, CreateBounty<'info>>, params: InitializeParams) -> Result<()> {
+        let params = InitializeParams { arbiter: ctx.accounts.council.key(), ..params };
+        let dao_key = ctx.accounts.dao.key();
+        let seeds: &[&[u8]] = &[b"treasury", dao_key.as_ref(), &[ctx.accounts.dao.treasury_bump]];
+        let signer_seeds = &[seeds];
+        let cpi_accounts = InitializeEscrow {
+            escrow: ctx.accounts.escrow.to_account_info(),
+            vault: ctx.accounts.vault.to_account_info(),
+            payer: ctx.accounts.authority.to_account_info(),
+            requester: ctx.accounts.treasury.to_account_info(),
+            guardian_config: ctx.accounts.guardian_config.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.bounty_split_program.to_account_info(), cpi_accounts, signer_seeds)
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        bounty_split::cpi::initialize_escrow(cpi_ctx, params)
+    }
+
+    /// Funds an escrow from the treasury's token account.
+    pub fn fund_bounty(ctx: Context<FundBounty>, amount: u64) -> Result<()> {
+        let dao_key = ctx.accounts.dao.key();
+        let seeds: &[&[u8]] = &[b"treasury", dao_key.as_ref(), &[ctx.accounts.dao.treasury_bump]];
+        let signer_seeds = &[seeds];
+        let cpi_accounts = FundEscrow {
+            escrow: ctx.accounts.escrow.to_account_info(),
+            payer: ctx.accounts.treasury.to_account_info(),
+            payer_token_account: ctx.accounts.treasury_token_account.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            vault: ctx.accounts.vault.to_account_info(),
+            guardian_config: ctx.accounts.guardian_config.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.bounty_split_program.to_account_info(), cpi_accounts, signer_seeds);
+        bounty_split::cpi::fund_escrow(cpi_ctx, amount)
+    }
+
+    /// Releases an escrow as its requester, skipping recipient confirmations.
+    pub fn release_bounty(ctx: Context<ReleaseBounty>) -> Result<()> {
+        let dao_key = ctx.accounts.dao.key();
+        let seeds: &[&[u8]] = &[b"treasury", dao_key.as_ref(), &[ctx.accounts.dao.treasury_bump]];
+        let signer_seeds = &[seeds];
+        let cpi_accounts = Release {
+            escrow: ctx.accounts.escrow.to_account_info(),
+            requester: ctx.accounts.treasury.to_account_info(),
+            guardian_config: ctx.accounts.guardian_config.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.bounty_split_program.to_account_info(), cpi_accounts, signer_seeds);
+        bounty_split::cpi::release(cpi_ctx)
+    }
+
+    /// Resolves a disputed escrow as its arbiter.
+    pub fn resolve_bounty(ctx: Context<ResolveBounty>) -> Result<()> {
+        let dao_key = ctx.accounts.dao.key();
+        let seeds: &[&[u8]] = &[b"council", dao_key.as_ref(), &[ctx.accounts.dao.council_bump]];
+        let signer_seeds = &[seeds];
+        let cpi_accounts = ResolveDispute {
+            escrow: ctx.accounts.escrow.to_account_info(),
+            arbiter: ctx.accounts.council.to_account_info(),
+            guardian_config: ctx.accounts.guardian_config.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.bounty_split_program.to_account_info(), cpi_accounts, signer_seeds);
+        bounty_split::cpi::resolve_dispute(cpi_ctx)
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Dao {
+    pub authority: Pubkey,
+    pub bump: u8,
+    pub treasury_bump: u8,
+    pub council_bump: u8,
+}
+
+#[derive(Accounts)]
+pub struct InitDao<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(init, payer = authority, space = 8 + Dao::INIT_SPACE, seeds = [b"dao", authority.key().as_ref()], bump)]
+    pub dao: Account<'info, Dao>,
+    /// CHECK: signing-only PDA, holds no data
+    #[account(seeds = [b"treasury", dao.key().as_ref()], bump)]
+    pub treasury: UncheckedAccount<'info>,
+    /// CHECK: signing-only PDA, holds no data
+    #[account(seeds = [b"council", dao.key().as_ref()], bump)]
+    pub council: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateBounty<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(has_one = authority, seeds = [b"dao", authority.key().as_ref()], bump = dao.bump)]
+    pub dao: Account<'info, Dao>,
+    /// CHECK: signing-only PDA, holds no data
+    #[account(seeds = [b"treasury", dao.key().as_ref()], bump = dao.treasury_bump)]
+    pub treasury: UncheckedAccount<'info>,
+    /// CHECK: signing-only PDA, holds no data
+    #[account(seeds = [b"council", dao.key().as_ref()], bump = dao.council_bump)]
+    pub council: UncheckedAccount<'info>,
+    /// CHECK: created and validated by bounty-split
+    #[account(mut)]
+    pub escrow: UncheckedAccount<'info>,
+    /// CHECK: validated by bounty-split
+    #[account(mut)]
+    pub vault: UncheckedAccount<'info>,
+    /// CHECK: bounty-split `[b"guardian"]` config, validated by bounty-split
+    pub guardian_config: UncheckedAccount<'info>,
+    pub bounty_split_program: Program<'info, BountySplit>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct FundBounty<'info> {
+    pub authority: Signer<'info>,
+    #[account(has_one = authority, seeds = [b"dao", authority.key().as_ref()], bump = dao.bump)]
+    pub dao: Account<'info, Dao>,
+    /// CHECK: signing-only PDA, holds no data
+    #[account(mut, seeds = [b"treasury", dao.key().as_ref()], bump = dao.treasury_bump)]
+    pub treasury: UncheckedAccount<'info>,
+    #[account(mut, token::mint = mint, token::authority = treasury)]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    pub mint: Account<'info, Mint>,
+    /// CHECK: validated by bounty-split
+    #[account(mut)]
+    pub escrow: UncheckedAccount<'info>,
+    /// CHECK: validated by bounty-split
+    #[account(mut)]
+    pub vault: UncheckedAccount<'info>,
+    /// CHECK: bounty-split `[b"guardian"]` config, validated by bounty-split
+    pub guardian_config: UncheckedAccount<'info>,
+    pub bounty_split_program: Program<'info, BountySplit>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseBounty<'info> {
+    pub authority: Signer<'info>,
+    #[account(has_one = authority, seeds = [b"dao", authority.key().as_ref()], bump = dao.bump)]
+    pub dao: Account<'info, Dao>,
+    /// CHECK: signing-only PDA, holds no data
+    #[account(seeds = [b"treasury", dao.key().as_ref()], bump = dao.treasury_bump)]
+    pub treasury: UncheckedAccount<'info>,
+    /// CHECK: validated by bounty-split
+    #[account(mut)]
+    pub escrow: UncheckedAccount<'info>,
+    /// CHECK: bounty-split `[b"guardian"]` config, validated by bounty-split
+    pub guardian_config: UncheckedAccount<'info>,
+    pub bounty_split_program: Program<'info, BountySplit>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveBounty<'info> {
+    pub authority: Signer<'info>,
+    #[account(has_one = authority, seeds = [b"dao", authority.key().as_ref()], bump = dao.bump)]
+    pub dao: Account<'info, Dao>,
+    /// CHECK: signing-only PDA, holds no data
+    #[account(seeds = [b"council", dao.key().as_ref()], bump = dao.council_bump)]
+    pub council: UncheckedAccount<'info>,
+    /// CHECK: validated by bounty-split
+    #[account(mut)]
+    pub escrow: UncheckedAccount<'info>,
+    /// CHECK: bounty-split `[b"guardian"]` config, validated by bounty-split
+    pub guardian_config: UncheckedAccount<'info>,
+    pub bounty_split_program: Program<'info, BountySplit>,
+}
@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::sysvar::instructions::load_instruction_at_checked;
+use crate::errors::EscrowError;
+
+/// Domain separator so approvals can't be replayed as signatures for another protocol
+pub const APPROVAL_DOMAIN: &[u8] = b"bounty-split/approval";
+
+/// `APPROVAL_DOMAIN | escrow (32) | created_at i64 le | nonce u64 le | action u8`
+///
+/// `created_at` ties the approval to one escrow instance, so signatures for a closed
+/// escrow don't apply to one re-created at the same address.
+pub const APPROVAL_MESSAGE_LEN: usize = APPROVAL_DOMAIN.len() + 32 + 8 + 8 + 1;
+
+// Approval actions
+pub const APPROVAL_ACTION_CONFIRM_RELEASE: u8 = 0;
+
+// Ed25519SignatureOffsets: seven u16 fields after the 2-byte header
+const SIGNATURE_OFFSETS_START: usize = 2;
+const SIGNATURE_OFFSETS_SIZE: usize = 14;
+const PUBKEY_SIZE: usize = 32;
+
+/// Message a recipient signs off-chain to approve `action` on the escrow instance
+/// `(escrow, created_at)` at `nonce`
+pub fn approval_message(escrow: &Pubkey, created_at: i64, nonce: u64, action: u8) -> [u8; APPROVAL_MESSAGE_LEN] {
+    let mut msg = [0u8; APPROVAL_MESSAGE_LEN];
+    let (domain, rest) = msg.split_at_mut(APPROVAL_DOMAIN.len());
+    domain.copy_from_slice(APPROVAL_DOMAIN);
+    rest[..32].copy_from_slice(escrow.as_ref());
+    rest[32..40].copy_from_slice(&created_at.to_le_bytes());
+    rest[40..48].copy_from_slice(&nonce.to_le_bytes());
+    rest[48] = action;
+    msg
+}
+
+/// Signed approval found in the transaction
+pub struct Approval {
+    pub signer: Pubkey,
+    pub nonce: u64,
+    pub action: u8,
+}
+
+/// Collect the approvals for the escrow instance `(escrow, created_at)` verified by
+/// Ed25519 program instructions anywhere in the transaction.
+///
+/// The runtime verifies every Ed25519 instruction before any program runs, so
+/// a signature that is present here is valid. Signatures over other messages,
+/// or that read their data from other instructions, are ignored, so one
+/// transaction can carry approvals for several escrows and other Ed25519 users.
+pub fn load_approvals(instructions_sysvar: &AccountInfo, escrow: &Pubkey, created_at: i64) -> Result<Vec<Approval>> {
+    let mut prefix = [0u8; APPROVAL_DOMAIN.len() + 32 + 8];
+    let (domain, rest) = prefix.split_at_mut(APPROVAL_DOMAIN.len());
+    domain.copy_from_slice(APPROVAL_DOMAIN);
+    rest[..32].copy_from_slice(escrow.as_ref());
+    rest[32..].copy_from_slice(&created_at.to_le_bytes());
+
+    let mut approvals = Vec::new();
+    let mut index = 0;
+    while let Ok(ix) = load_instruction_at_checked(index, instructions_sysvar) {
+        index += 1;
+        if ix.program_id != ed25519_program::ID {
+            continue;
+        }
+        let data = &ix.data;
+        require!(data.len() >= SIGNATURE_OFFSETS_START, EscrowError::InvalidApproval);
+        for i in 0..data[0] as usize {
+            let start = SIGNATURE_OFFSETS_START + i * SIGNATURE_OFFSETS_SIZE;
+            let offsets = data.get(start..start + SIGNATURE_OFFSETS_SIZE).ok_or(error!(EscrowError::InvalidApproval))?;
+            let field = |n: usize| u16::from_le_bytes([offsets[2 * n], offsets[2 * n + 1]]);
+            let (signature_ix, pubkey_offset, pubkey_ix) = (field(1), field(2) as usize, field(3));
+            let (message_offset, message_size, message_ix) = (field(4) as usize, field(5) as usize, field(6));
+
+            // Offsets may point into other instructions; approvals carry all their data inline,
+            // so anything else is another program's verification
+            if signature_ix != u16::MAX || pubkey_ix != u16::MAX || message_ix != u16::MAX {
+                continue;
+            }
+            let message = data.get(message_offset..message_offset + message_size).ok_or(error!(EscrowError::InvalidApproval))?;
+            if message.len() != APPROVAL_MESSAGE_LEN || !message.starts_with(&prefix) {
+                continue;
+            }
+            let pubkey = data.get(pubkey_offset..pubkey_offset + PUBKEY_SIZE).ok_or(error!(EscrowError::InvalidApproval))?;
+            approvals.push(Approval {
+                signer: Pubkey::try_from(pubkey).map_err(|_| error!(EscrowError::InvalidApproval))?,
+                nonce: u64::from_le_bytes(message[prefix.len()..prefix.len() + 8].try_into().unwrap()),
+                action: message[APPROVAL_MESSAGE_LEN - 1],
+            });
+        }
+    }
+    Ok(approvals)
+}
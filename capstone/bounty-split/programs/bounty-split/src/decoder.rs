@@ -0,0 +1,335 @@
+//! Off-chain decoding of bounty-split events from transaction logs.
+//!
+//! Anchor's `emit!` writes each event as a `Program data: <base64>` log line,
+//! the base64 being the 8-byte event discriminator followed by the borsh body.
+//! `decode_logs` turns the logs of one transaction into `EscrowEvent`s, and
+//! `EscrowHistory` folds the events of many transactions into the timeline of
+//! a single escrow.
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use crate::events::*;
+use crate::state::STATUS_CLOSED;
+
+const PROGRAM_DATA: &str = "Program data: ";
+
+macro_rules! escrow_events {
+    (escrow: [$($variant:ident),* $(,)?], protocol: [$($protocol:ident),* $(,)?] $(,)?) => {
+        /// Any event emitted by bounty-split
+        pub enum EscrowEvent {
+            $($variant($variant),)*
+            $($protocol($protocol),)*
+        }
+
+        impl EscrowEvent {
+            /// Decode discriminator + borsh body; `None` for foreign or malformed data
+            pub fn decode(data: &[u8]) -> Option<Self> {
+                $(
+                    if let Some(mut body) = data.strip_prefix($variant::DISCRIMINATOR) {
+                        return $variant::deserialize(&mut body).ok().map(Self::$variant);
+                    }
+                )*
+                $(
+                    if let Some(mut body) = data.strip_prefix($protocol::DISCRIMINATOR) {
+                        return $protocol::deserialize(&mut body).ok().map(Self::$protocol);
+                    }
+                )*
+                None
+            }
+
+            /// Escrow the event belongs to; `None` for protocol-wide events
+            pub fn escrow(&self) -> Option<Pubkey> {
+                match self {
+                    $(Self::$variant(e) => Some(e.escrow),)*
+                    $(Self::$protocol(_) => None,)*
+                }
+            }
+
+            pub fn slot(&self) -> u64 {
+                match self {
+                    $(Self::$variant(e) => e.slot,)*
+                    $(Self::$protocol(e) => e.slot,)*
+                }
+            }
+
+            pub fn timestamp(&self) -> i64 {
+                match self {
+                    $(Self::$variant(e) => e.timestamp,)*
+                    $(Self::$protocol(e) => e.timestamp,)*
+                }
+            }
+
+            /// `(status_before, status_after)` of the escrow; `None` for protocol-wide events
+            pub fn status_change(&self) -> Option<(u8, u8)> {
+                match self {
+                    $(Self::$variant(e) => Some((e.status_before, e.status_after)),)*
+                    $(Self::$protocol(_) => None,)*
+                }
+            }
+        }
+    };
+}
+
+escrow_events!(
+    escrow: [
+        EscrowCreated,
+        EscrowFunded,
+        ReleaseProposed,
+        ReleaseConfirmed,
+        EscrowReleased,
+        RecipientClaimed,
+        EscrowRefunded,
+        DisputeRaised,
+        DisputeResolved,
+        EscrowClosed,
+        EscrowMigrated,
+        UsdTargetSet,
+        UsdTargetSettled,
+    ],
+    // Not tied to one escrow's lifecycle
+    protocol: [PauseUpdated, PayoutDestinationSet],
+);
+
+/// Decode the bounty-split events in one transaction's log messages, in emission order.
+///
+/// Only `Program data:` lines written while bounty-split is the executing program
+/// are considered, so events of callers or other programs that share a
+/// discriminator are never misread. Logs truncated by the runtime simply yield
+/// the events seen so far. Pass logs of successful transactions only: a failed
+/// transaction logs events whose state changes were rolled back.
+pub fn decode_logs<S: AsRef<str>>(logs: &[S]) -> Vec<EscrowEvent> {
+    let program_id = crate::ID.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+    for line in logs {
+        let line = line.as_ref();
+        if let Some(data) = line.strip_prefix(PROGRAM_DATA) {
+            if stack.last() == Some(&program_id.as_str()) {
+                if let Some(event) = STANDARD.decode(data).ok().and_then(|bytes| EscrowEvent::decode(&bytes)) {
+                    events.push(event);
+                }
+            }
+        } else if let Some(rest) = line.strip_prefix("Program ") {
+            let mut words = rest.split(' ');
+            let (id, verb) = (words.next().unwrap_or_default(), words.next().unwrap_or_default());
+            if verb == "invoke" {
+                stack.push(id);
+            } else if (verb == "success" || verb == "failed:") && stack.last() == Some(&id) {
+                stack.pop();
+            }
+        }
+    }
+    events
+}
+
+/// Ordered event timeline of one escrow
+#[derive(Default)]
+pub struct EscrowHistory {
+    pub escrow: Pubkey,
+    pub events: Vec<EscrowEvent>,
+}
+
+impl EscrowHistory {
+    /// Build the history of `escrow` from the logs of any number of transactions.
+    ///
+    /// Transactions may be passed in any order (e.g. newest-first as returned by
+    /// `getSignaturesForAddress`); events are sorted by slot, keeping log order
+    /// within a slot, so pass transactions of the same slot in execution order.
+    pub fn from_logs<'a, I, S>(escrow: Pubkey, transactions: I) -> Self
+    where
+        I: IntoIterator<Item = &'a [S]>,
+        S: AsRef<str> + 'a,
+    {
+        let mut events: Vec<EscrowEvent> = transactions
+            .into_iter()
+            .flat_map(decode_logs)
+            .filter(|e| e.escrow() == Some(escrow))
+            .collect();
+        events.sort_by_key(EscrowEvent::slot);
+        Self { escrow, events }
+    }
+
+    /// Status after the latest event (STATUS_* constants), `None` if nothing was seen
+    pub fn status(&self) -> Option<u8> {
+        self.events.last().and_then(EscrowEvent::status_change).map(|(_, after)| after)
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.status() == Some(STATUS_CLOSED)
+    }
+
+    /// Total deposited through `fund_escrow`
+    pub fn total_funded(&self) -> u64 {
+        self.events
+            .iter()
+            .filter_map(|e| match e { EscrowEvent::EscrowFunded(f) => Some(f.amount), _ => None })
+            .sum()
+    }
+
+    /// `(recipient index, amount)` of every claim so far
+    pub fn claims(&self) -> Vec<(u8, u64)> {
+        self.events
+            .iter()
+            .filter_map(|e| match e { EscrowEvent::RecipientClaimed(c) => Some((c.index, c.amount)), _ => None })
+            .collect()
+    }
+
+    /// First event whose status transition does not start where the previous one ended,
+    /// a sign of missing transactions
+    pub fn first_gap(&self) -> Option<usize> {
+        let mut last = None;
+        for (i, event) in self.events.iter().enumerate() {
+            if let Some((before, after)) = event.status_change() {
+                if last.is_some_and(|l| l != before) {
+                    return Some(i);
+                }
+                last = Some(after);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Event;
+    use crate::state::{STATUS_FUNDED, STATUS_INITIALIZED, STATUS_PENDING, STATUS_RELEASED};
+
+    fn data_line<E: Event>(event: &E) -> String {
+        format!("{PROGRAM_DATA}{}", STANDARD.encode(event.data()))
+    }
+
+    fn invoke(program: &Pubkey, depth: u8) -> String {
+        format!("Program {program} invoke [{depth}]")
+    }
+
+    fn success(program: &Pubkey) -> String {
+        format!("Program {program} success")
+    }
+
+    fn funded(escrow: Pubkey, slot: u64, amount: u64) -> EscrowFunded {
+        EscrowFunded {
+            escrow,
+            payer: Pubkey::new_unique(),
+            amount,
+            total_amount: amount,
+            slot,
+            timestamp: slot as i64,
+            status_before: STATUS_INITIALIZED,
+            status_after: STATUS_FUNDED,
+        }
+    }
+
+    fn released(escrow: Pubkey, slot: u64, status_before: u8) -> EscrowReleased {
+        EscrowReleased {
+            escrow,
+            by: Pubkey::new_unique(),
+            total_amount: 100,
+            slot,
+            timestamp: slot as i64,
+            status_before,
+            status_after: STATUS_RELEASED,
+        }
+    }
+
+    #[test]
+    fn decodes_top_level_events() {
+        let escrow = Pubkey::new_unique();
+        let logs = vec![
+            invoke(&crate::ID, 1),
+            "Program log: Instruction: FundEscrow".to_string(),
+            data_line(&funded(escrow, 10, 100)),
+            format!("Program {} consumed 5000 of 200000 compute units", crate::ID),
+            success(&crate::ID),
+        ];
+        let events = decode_logs(&logs);
+        assert_eq!(events.len(), 1);
+        assert!(matches!(&events[0], EscrowEvent::EscrowFunded(f) if f.escrow == escrow && f.amount == 100));
+    }
+
+    #[test]
+    fn only_decodes_data_logged_by_bounty_split_in_nested_cpi() {
+        let escrow = Pubkey::new_unique();
+        let caller = Pubkey::new_unique();
+        let logs = vec![
+            invoke(&caller, 1),
+            // The caller logs bytes that happen to carry a bounty-split discriminator
+            data_line(&funded(escrow, 1, 1)),
+            invoke(&crate::ID, 2),
+            invoke(&anchor_spl::token::ID, 3),
+            data_line(&funded(escrow, 2, 2)),
+            success(&anchor_spl::token::ID),
+            data_line(&funded(escrow, 3, 3)),
+            success(&crate::ID),
+            data_line(&funded(escrow, 4, 4)),
+            success(&caller),
+        ];
+        let events = decode_logs(&logs);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].slot(), 3);
+    }
+
+    #[test]
+    fn ignores_other_programs_and_malformed_data() {
+        let escrow = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let logs = vec![
+            invoke(&other, 1),
+            data_line(&funded(escrow, 1, 1)),
+            success(&other),
+            invoke(&crate::ID, 1),
+            format!("{PROGRAM_DATA}not base64!"),
+            format!("{PROGRAM_DATA}{}", STANDARD.encode([0u8; 16])),
+            data_line(&funded(escrow, 2, 2)),
+            success(&crate::ID),
+        ];
+        let events = decode_logs(&logs);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].slot(), 2);
+    }
+
+    #[test]
+    fn truncated_logs_yield_the_events_seen_so_far() {
+        let escrow = Pubkey::new_unique();
+        let logs = vec![
+            invoke(&crate::ID, 1),
+            data_line(&funded(escrow, 1, 1)),
+            "Log truncated".to_string(),
+        ];
+        assert_eq!(decode_logs(&logs).len(), 1);
+
+        // A failed inner program is popped like a successful one
+        let failed = vec![
+            invoke(&crate::ID, 1),
+            invoke(&anchor_spl::token::ID, 2),
+            format!("Program {} failed: custom program error: 0x1", anchor_spl::token::ID),
+            data_line(&funded(escrow, 2, 2)),
+        ];
+        assert_eq!(decode_logs(&failed).len(), 1);
+    }
+
+    #[test]
+    fn history_sorts_filters_and_finds_gaps() {
+        let escrow = Pubkey::new_unique();
+        let tx = |event: String| vec![invoke(&crate::ID, 1), event, success(&crate::ID)];
+        // Newest first, with another escrow's transaction mixed in
+        let transactions = [
+            tx(data_line(&released(escrow, 30, STATUS_PENDING))),
+            tx(data_line(&funded(Pubkey::new_unique(), 20, 7))),
+            tx(data_line(&funded(escrow, 10, 100))),
+        ];
+        let history = EscrowHistory::from_logs(escrow, transactions.iter().map(Vec::as_slice));
+        assert_eq!(history.events.len(), 2);
+        assert_eq!(history.events[0].slot(), 10);
+        assert_eq!(history.total_funded(), 100);
+        assert_eq!(history.status(), Some(STATUS_RELEASED));
+        assert!(!history.is_closed());
+        // The ReleaseProposed event that moved FUNDED -> PENDING is missing
+        assert_eq!(history.first_gap(), Some(1));
+
+        let complete = [tx(data_line(&funded(escrow, 10, 100))), tx(data_line(&released(escrow, 30, STATUS_FUNDED)))];
+        let history = EscrowHistory::from_logs(escrow, complete.iter().map(Vec::as_slice));
+        assert_eq!(history.first_gap(), None);
+    }
+}
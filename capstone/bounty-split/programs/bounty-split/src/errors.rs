@@ -46,4 +46,40 @@ pub enum EscrowError {
     MathError,
     #[msg("Invalid vault account")]
     InvalidVault,
+    #[msg("Escrow is not fully settled")]
+    NotSettled,
+    #[msg("Invalid index page")]
+    InvalidIndexPage,
+    #[msg("Index page is full")]
+    IndexPageFull,
+    #[msg("Wrong number of index pages")]
+    IndexAccountsMismatch,
+    #[msg("Escrow not found in index page")]
+    NotIndexed,
+    #[msg("Protocol is paused")]
+    ProtocolPaused,
+    #[msg("Unknown pause flags")]
+    InvalidPauseFlags,
+    #[msg("USD target must be settled before claiming")]
+    UsdNotSettled,
+    #[msg("Invalid price feed account")]
+    InvalidPriceFeed,
+    #[msg("Price is stale")]
+    StalePrice,
+    #[msg("Invalid price")]
+    InvalidPrice,
+    #[msg("Escrow already uses the current layout")]
+    AlreadyMigrated,
+    #[msg("Unknown escrow layout version")]
+    UnsupportedVersion,
+    #[msg("Malformed or stale signed approval")]
+    InvalidApproval,
+    #[msg("No signed approvals for this escrow")]
+    NoApprovals,
+    #[msg("Destination does not match the registered payout destination")]
+    InvalidDestination,
+    #[msg("Escrow must be migrated to the current layout first")]
+    MigrationRequired,
+    #[msg("USD terms account is required for this escrow")]
+    MissingUsdTerms,
 }
@@ -1,35 +1,82 @@
 use anchor_lang::prelude::*;
 
+// Every escrow event carries the slot and unix timestamp it was emitted at and
+// the escrow status (STATUS_* constants) before and after the instruction.
+// `decoder` rebuilds an escrow's history from these.
+
 #[event]
 pub struct EscrowCreated {
     pub escrow: Pubkey,
     pub requester: Pubkey,
     pub bounty_id: [u8;32],
+    pub token_mint: Pubkey,
+    pub arbiter: Pubkey,
+    pub recipient_count: u8,
+    pub required_confirmations: u8,
+    pub slot: u64,
+    pub timestamp: i64,
+    pub status_before: u8,
+    pub status_after: u8,
 }
 
 #[event]
 pub struct EscrowFunded {
     pub escrow: Pubkey,
+    pub payer: Pubkey,
     pub amount: u64,
+    pub total_amount: u64,                 // escrowed balance after this deposit
+    pub slot: u64,
+    pub timestamp: i64,
+    pub status_before: u8,
+    pub status_after: u8,
 }
 
 #[event]
 pub struct ReleaseProposed {
     pub escrow: Pubkey,
     pub by: Pubkey,
+    pub slot: u64,
+    pub timestamp: i64,
+    pub status_before: u8,
+    pub status_after: u8,
 }
 
 #[event]
 pub struct ReleaseConfirmed {
     pub escrow: Pubkey,
     pub by: Pubkey,
-    pub confirmations: u8,
+    pub index: u8,                         // recipient index of `by`
+    pub confirmations: u8,                 // bitmask after this confirmation
+    pub slot: u64,
+    pub timestamp: i64,
+    pub status_before: u8,
+    pub status_after: u8,
 }
 
+/// Escrow became claimable, by `release`, by the final `confirm_release` or by `resolve_dispute`
 #[event]
 pub struct EscrowReleased {
     pub escrow: Pubkey,
-    pub total_distributed: u64,
+    pub by: Pubkey,
+    pub total_amount: u64,                 // amount split between recipients
+    pub slot: u64,
+    pub timestamp: i64,
+    pub status_before: u8,
+    pub status_after: u8,
+}
+
+#[event]
+pub struct RecipientClaimed {
+    pub escrow: Pubkey,
+    pub recipient: Pubkey,
+    pub index: u8,
+    pub amount: u64,
+    pub destination: Pubkey,               // owner of the token account that was paid
+    pub claimed: u8,                       // bitmask after this claim
+    pub slot: u64,
+    pub timestamp: i64,
+    pub status_before: u8,
+    pub status_after: u8,
 }
 
 #[event]
@@ -37,6 +84,10 @@ pub struct EscrowRefunded {
     pub escrow: Pubkey,
     pub refunded_to: Pubkey,
     pub amount: u64,
+    pub slot: u64,
+    pub timestamp: i64,
+    pub status_before: u8,
+    pub status_after: u8,
 }
 
 #[event]
@@ -44,11 +95,84 @@ pub struct DisputeRaised {
     pub escrow: Pubkey,
     pub by: Pubkey,
     pub reason_hash: [u8;32],
+    pub slot: u64,
+    pub timestamp: i64,
+    pub status_before: u8,
+    pub status_after: u8,
 }
 
 #[event]
 pub struct DisputeResolved {
     pub escrow: Pubkey,
     pub arbiter: Pubkey,
-    pub total_distributed: u64,
+    pub ruling: u8,                        // see RULING_* constants
+    pub total_amount: u64,
+    pub slot: u64,
+    pub timestamp: i64,
+    pub status_before: u8,
+    pub status_after: u8,
+}
+
+#[event]
+pub struct EscrowClosed {
+    pub escrow: Pubkey,
+    pub requester: Pubkey,
+    pub swept: u64,
+    pub slot: u64,
+    pub timestamp: i64,
+    pub status_before: u8,
+    pub status_after: u8,                  // always STATUS_CLOSED
+}
+
+#[event]
+pub struct EscrowMigrated {
+    pub escrow: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+    pub slot: u64,
+    pub timestamp: i64,
+    pub status_before: u8,
+    pub status_after: u8,
+}
+
+#[event]
+pub struct PauseUpdated {
+    pub guardian: Pubkey,
+    pub paused: bool,
+    pub pause_flags: u8,
+    pub slot: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PayoutDestinationSet {
+    pub recipient: Pubkey,
+    pub escrow: Pubkey,                    // Pubkey::default() for the global entry
+    pub destination: Pubkey,               // Pubkey::default() when cleared
+    pub slot: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct UsdTargetSet {
+    pub escrow: Pubkey,
+    pub usd_target: u64,
+    pub price_feed_id: [u8;32],
+    pub slot: u64,
+    pub timestamp: i64,
+    pub status_before: u8,
+    pub status_after: u8,
+}
+
+#[event]
+pub struct UsdTargetSettled {
+    pub escrow: Pubkey,
+    pub price: i64,
+    pub expo: i32,
+    pub amount: u64,
+    pub returned: u64,
+    pub slot: u64,
+    pub timestamp: i64,
+    pub status_before: u8,
+    pub status_after: u8,
 }
@@ -1,5 +1,5 @@
 use anchor_lang::prelude::*;
-use crate::state::BASIS_POINTS_DENOM;
+use crate::state::{GuardianConfig, BASIS_POINTS_DENOM};
 
 /// Count set bits in u8
 pub fn count_bits(x: u8) -> u8 {
@@ -16,13 +16,31 @@ pub fn is_bit_set(mask: u8, idx: usize) -> bool {
     (mask & (1u8 << idx)) != 0
 }
 
+/// Fail if the guardian paused the action identified by `flag` (a `PAUSE_*` bit).
+/// `guardian_config` is the `[b"guardian"]` PDA; before it is initialized nothing is paused.
+pub fn require_not_paused(guardian_config: &AccountInfo, flag: u8) -> Result<()> {
+    if guardian_config.data_is_empty() {
+        return Ok(());
+    }
+    require_keys_eq!(*guardian_config.owner, crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+    let config = GuardianConfig::try_deserialize(&mut &guardian_config.try_borrow_data()?[..])?;
+    require!(!config.paused && (config.pause_flags & flag) == 0, crate::errors::EscrowError::ProtocolPaused);
+    Ok(())
+}
+
+/// Slot and unix timestamp stamped on every event
+pub fn event_clock() -> Result<(u64, i64)> {
+    let clock = Clock::get()?;
+    Ok((clock.slot, clock.unix_timestamp))
+}
+
 /// Compute distribution amounts (returns Vec<u64>)
 pub fn calc_distributions(total: u64, splits: &[u16], count: usize) -> Result<Vec<u64>> {
     let mut out = Vec::with_capacity(count);
     let mut accumulated: u128 = 0;
-    for i in 0..count {
+    for split in splits.iter().take(count) {
         let amt = (total as u128)
-            .checked_mul(splits[i] as u128)
+            .checked_mul(*split as u128)
             .ok_or(error!(crate::errors::EscrowError::Overflow))?
             / (BASIS_POINTS_DENOM as u128);
         out.push(amt as u64);
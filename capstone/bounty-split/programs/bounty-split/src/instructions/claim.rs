@@ -1,49 +1,50 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{Mint, Token, TokenAccount};
 use crate::state::*;
 use crate::errors::*;
-use crate::events::*;
-use crate::helpers::calc_distributions;
+use crate::helpers::require_not_paused;
+use crate::instructions::common::{pay_recipient, resolve_payout_destination};
 
 #[derive(Accounts)]
 pub struct Claim<'info> {
-    #[account(mut)] pub escrow: Account<'info, Escrow>,
+    #[account(mut, has_one = token_mint @ EscrowError::InvalidMint)] pub escrow: Account<'info, Escrow>,
     #[account(mut, 
         constraint = vault.owner == escrow.key(), 
         constraint = vault.mint == escrow.token_mint
     )] 
     pub vault: Account<'info, TokenAccount>,
-    #[account(
-        mut, 
-        constraint = claimant_ata.owner == claimant.key(), 
-        constraint = claimant_ata.mint == escrow.token_mint
-    )] 
-    pub claimant_ata: Account<'info, TokenAccount>,
+    /// Pays for `destination_ata` if it doesn't exist yet
+    #[account(mut)]
     pub claimant: Signer<'info>,
+    /// CHECK: `[b"payout", claimant, escrow]` PayoutDestination, may not exist
+    #[account(seeds = [b"payout", claimant.key().as_ref(), escrow.key().as_ref()], bump)]
+    pub escrow_payout: UncheckedAccount<'info>,
+    /// CHECK: `[b"payout", claimant]` PayoutDestination, may not exist
+    #[account(seeds = [b"payout", claimant.key().as_ref()], bump)]
+    pub global_payout: UncheckedAccount<'info>,
+    /// CHECK: compared with the registered payout destination in the handler
+    pub destination: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = claimant,
+        associated_token::mint = token_mint,
+        associated_token::authority = destination
+    )]
+    pub destination_ata: Account<'info, TokenAccount>,
+    pub token_mint: Account<'info, Mint>,
+    /// CHECK: the `[b"guardian"]` PDA. Until `initialize_guardian` creates it nothing is paused.
+    #[account(seeds = [b"guardian"], bump)]
+    pub guardian_config: UncheckedAccount<'info>,
     pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
 }
 
 pub fn claim(ctx: Context<Claim>) -> Result<()> {
-    let e = &mut ctx.accounts.escrow;
-    require!(e.status == STATUS_RELEASED, EscrowError::InvalidStatus);
-    let rc = e.recipient_count as usize;
-    // find claimant index
-    let mut idx_opt = None; for i in 0..rc { if e.recipients[i] == ctx.accounts.claimant.key() { idx_opt = Some(i); break; } }
-    let idx = idx_opt.ok_or(error!(EscrowError::RecipientNotFound))?;
-    require!((e.claimed & (1u8 << idx)) == 0, EscrowError::AlreadyClaimed);
-
-    let distributions = calc_distributions(e.total_amount, &e.splits, rc)?;
-    let amount = distributions[idx];
-    require!(amount > 0, EscrowError::InvalidSplits);
-
-    e.claimed |= 1u8 << idx;
-
-    let seeds: &[&[u8]] = &[b"escrow", e.requester.as_ref(), &e.bounty_id[..], &[e.bump]]; let signer_seeds = &[seeds];
-    let cpi_accounts = token::Transfer { from: ctx.accounts.vault.to_account_info(), to: ctx.accounts.claimant_ata.to_account_info(), authority: e.to_account_info() };
-    let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer_seeds);
-    token::transfer(cpi_ctx, amount)?;
-
-    if e.claimed.count_ones() as usize >= rc { e.total_amount = 0; }
-    emit!(EscrowReleased { escrow: e.key(), total_distributed: amount });
-    Ok(())
+    require_not_paused(&ctx.accounts.guardian_config, PAUSE_CLAIM)?;
+    let claimant = ctx.accounts.claimant.key();
+    let destination = resolve_payout_destination(claimant, &ctx.accounts.escrow_payout, &ctx.accounts.global_payout)?;
+    require_keys_eq!(ctx.accounts.destination.key(), destination, EscrowError::InvalidDestination);
+    pay_recipient(&mut ctx.accounts.escrow, &ctx.accounts.vault, &ctx.accounts.destination_ata, &ctx.accounts.token_program, claimant)
 }
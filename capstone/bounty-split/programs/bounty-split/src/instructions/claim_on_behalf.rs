@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use crate::state::*;
+use crate::errors::*;
+use crate::helpers::require_not_paused;
+use crate::instructions::common::{pay_recipient, resolve_payout_destination};
+
+#[derive(Accounts)]
+pub struct ClaimOnBehalf<'info> {
+    #[account(mut, has_one = token_mint @ EscrowError::InvalidMint)]
+    pub escrow: Account<'info, Escrow>,
+    #[account(mut, constraint = vault.owner == escrow.key(), constraint = vault.mint == escrow.token_mint)]
+    pub vault: Account<'info, TokenAccount>,
+    /// CHECK: any recipient of `escrow`; looked up in the handler
+    pub recipient: UncheckedAccount<'info>,
+    /// CHECK: `[b"payout", recipient, escrow]` PayoutDestination, may not exist
+    #[account(seeds = [b"payout", recipient.key().as_ref(), escrow.key().as_ref()], bump)]
+    pub escrow_payout: UncheckedAccount<'info>,
+    /// CHECK: `[b"payout", recipient]` PayoutDestination, may not exist
+    #[account(seeds = [b"payout", recipient.key().as_ref()], bump)]
+    pub global_payout: UncheckedAccount<'info>,
+    /// CHECK: compared with the registered payout destination in the handler
+    pub destination: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = token_mint,
+        associated_token::authority = destination
+    )]
+    pub destination_ata: Account<'info, TokenAccount>,
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: the `[b"guardian"]` PDA. Until `initialize_guardian` creates it nothing is paused.
+    #[account(seeds = [b"guardian"], bump)]
+    pub guardian_config: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Same payout as `claim`, triggered by anyone. Funds can only reach the
+/// recipient's registered destination (or the recipient), so no signature is needed.
+pub fn claim_on_behalf(ctx: Context<ClaimOnBehalf>) -> Result<()> {
+    require_not_paused(&ctx.accounts.guardian_config, PAUSE_CLAIM)?;
+    let recipient = ctx.accounts.recipient.key();
+    let destination = resolve_payout_destination(recipient, &ctx.accounts.escrow_payout, &ctx.accounts.global_payout)?;
+    require_keys_eq!(ctx.accounts.destination.key(), destination, EscrowError::InvalidDestination);
+    pay_recipient(&mut ctx.accounts.escrow, &ctx.accounts.vault, &ctx.accounts.destination_ata, &ctx.accounts.token_program, recipient)
+}
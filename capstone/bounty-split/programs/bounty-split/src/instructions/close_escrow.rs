@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount};
+use crate::state::*;
+use crate::errors::*;
+use crate::events::*;
+use crate::helpers::event_clock;
+use crate::instructions::common::{close_escrow_payouts, index_owners, remove_from_index};
+
+#[derive(Accounts)]
+pub struct CloseEscrow<'info> {
+    #[account(mut, close = requester, has_one = requester @ EscrowError::Unauthorized, has_one = vault @ EscrowError::InvalidVault)]
+    pub escrow: Account<'info, Escrow>,
+    #[account(mut, constraint = vault.owner == escrow.key(), constraint = vault.mint == escrow.token_mint)]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(mut, constraint = requester_ata.owner == escrow.requester, constraint = requester_ata.mint == escrow.token_mint)]
+    pub requester_ata: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub requester: Signer<'info>,
+    /// Required when the escrow has a USD target; its rent goes to the requester
+    #[account(mut, close = requester, seeds = [b"usd_terms", escrow.key().as_ref()], bump = usd_terms.bump)]
+    pub usd_terms: Option<Account<'info, UsdTerms>>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Closes a refunded or fully claimed escrow, its vault and its `UsdTerms` (if any),
+/// returning rent and any rounding dust to the requester.
+///
+/// `remaining_accounts`: the escrow's `EscrowIndex` pages in `add_to_index` order
+/// (omit for escrows created before indexing existed), then a
+/// `(payout_destination, recipient)` pair for every per-escrow payout registration.
+pub fn close_escrow<'info>(ctx: Context<'_, '_, 'info, 'info, CloseEscrow<'info>>) -> Result<()> {
+    let e = &ctx.accounts.escrow;
+    let rc = e.recipient_count as usize;
+    let all_claimed = e.claimed.count_ones() as usize >= rc;
+    require!(e.status == STATUS_REFUNDED || (e.status == STATUS_RELEASED && all_claimed), EscrowError::NotSettled);
+    require!(e.usd_state == USD_NONE || ctx.accounts.usd_terms.is_some(), EscrowError::MissingUsdTerms);
+
+    let page_count = if e.indexed != 0 { index_owners(e).len() } else { 0 };
+    require!(ctx.remaining_accounts.len() >= page_count, EscrowError::IndexAccountsMismatch);
+    let (pages, payouts) = ctx.remaining_accounts.split_at(page_count);
+    if e.indexed != 0 {
+        remove_from_index(e, e.key(), pages)?;
+    }
+    close_escrow_payouts(e, e.key(), payouts)?;
+
+    let seeds: &[&[u8]] = &[b"escrow", e.requester.as_ref(), &e.bounty_id[..], &[e.bump]];
+    let signer_seeds = &[seeds];
+
+    // Sweep rounding dust left by per-recipient claims
+    let swept = ctx.accounts.vault.amount;
+    if swept > 0 {
+        let cpi_accounts = token::Transfer { from: ctx.accounts.vault.to_account_info(), to: ctx.accounts.requester_ata.to_account_info(), authority: e.to_account_info() };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        token::transfer(cpi_ctx, swept)?;
+    }
+
+    let cpi_accounts = token::CloseAccount { account: ctx.accounts.vault.to_account_info(), destination: ctx.accounts.requester.to_account_info(), authority: e.to_account_info() };
+    let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer_seeds);
+    token::close_account(cpi_ctx)?;
+
+    let (slot, timestamp) = event_clock()?;
+    emit!(EscrowClosed { escrow: e.key(), requester: e.requester, swept, slot, timestamp, status_before: e.status, status_after: STATUS_CLOSED });
+    Ok(())
+}
@@ -4,7 +4,8 @@ use anchor_lang::solana_program::program_pack::Pack;
 use anchor_spl::token::{self, Token, TokenAccount};
 use crate::state::*;
 use crate::errors::*;
-use crate::helpers::calc_distributions;
+use crate::events::*;
+use crate::helpers::{calc_distributions, event_clock};
 
 pub fn distribute_spl<'info>(
     escrow: &mut Account<'info, Escrow>,
@@ -14,7 +15,7 @@ pub fn distribute_spl<'info>(
 ) -> Result<u64> {
     let rc = escrow.recipient_count as usize;
     require!(rc > 0, EscrowError::InvalidRecipientCount);
-    require!(remaining_accounts.len() >= rc + 1, EscrowError::RecipientNotFound);
+    require!(remaining_accounts.len() > rc, EscrowError::RecipientNotFound);
 
     let distributions = calc_distributions(escrow.total_amount, &escrow.splits, rc)?;
 
@@ -57,3 +58,123 @@ pub fn distribute_spl<'info>(
     escrow.total_amount = 0;
     Ok(distributed_total_u64)
 }
+
+/// Payout owner for `recipient`: the per-escrow registration, else the global one,
+/// else the recipient itself. Both registration PDAs are passed whether or not
+/// they exist, so a caller can't skip the one that applies.
+pub fn resolve_payout_destination<'info>(recipient: Pubkey, escrow_payout: &AccountInfo<'info>, global_payout: &AccountInfo<'info>) -> Result<Pubkey> {
+    for info in [escrow_payout, global_payout] {
+        if info.owner == &crate::ID && !info.data_is_empty() {
+            return Ok(PayoutDestination::try_deserialize(&mut &info.try_borrow_data()?[..])?.destination);
+        }
+    }
+    Ok(recipient)
+}
+
+/// Closes the `[b"payout", recipient, escrow]` registrations passed as
+/// `(payout_destination, recipient)` pairs, returning their rent to the recipients.
+// `usize::is_multiple_of` needs a newer rustc than the SBF toolchain may ship
+#[allow(clippy::manual_is_multiple_of)]
+pub fn close_escrow_payouts<'info>(escrow: &Escrow, escrow_key: Pubkey, accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+    require!(accounts.len() % 2 == 0, EscrowError::InvalidDestination);
+    let (slot, timestamp) = event_clock()?;
+    for pair in accounts.chunks(2) {
+        let (payout_info, recipient_info) = (&pair[0], &pair[1]);
+        let payout = Account::<PayoutDestination>::try_from(payout_info)?;
+        require_keys_eq!(payout.escrow, escrow_key, EscrowError::InvalidDestination);
+        require_keys_eq!(payout.recipient, recipient_info.key(), EscrowError::InvalidDestination);
+        require!(escrow.recipients[..escrow.recipient_count as usize].contains(&payout.recipient), EscrowError::RecipientNotFound);
+        payout.close(recipient_info.clone())?;
+        emit!(PayoutDestinationSet { recipient: recipient_info.key(), escrow: escrow_key, destination: Pubkey::default(), slot, timestamp });
+    }
+    Ok(())
+}
+
+/// Pays `recipient` its split of a released escrow into `destination_ata`
+pub fn pay_recipient<'info>(
+    escrow: &mut Account<'info, Escrow>,
+    vault: &Account<'info, TokenAccount>,
+    destination_ata: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    recipient: Pubkey,
+) -> Result<()> {
+    require!(escrow.status == STATUS_RELEASED, EscrowError::InvalidStatus);
+    require!(escrow.usd_state != USD_PENDING, EscrowError::UsdNotSettled);
+    let rc = escrow.recipient_count as usize;
+    let idx = escrow.recipients[..rc].iter().position(|r| *r == recipient).ok_or(error!(EscrowError::RecipientNotFound))?;
+    require!((escrow.claimed & (1u8 << idx)) == 0, EscrowError::AlreadyClaimed);
+
+    let distributions = calc_distributions(escrow.total_amount, &escrow.splits, rc)?;
+    let amount = distributions[idx];
+    require!(amount > 0, EscrowError::InvalidSplits);
+
+    escrow.claimed |= 1u8 << idx;
+
+    let seeds: &[&[u8]] = &[b"escrow", escrow.requester.as_ref(), &escrow.bounty_id[..], &[escrow.bump]]; let signer_seeds = &[seeds];
+    let cpi_accounts = token::Transfer { from: vault.to_account_info(), to: destination_ata.to_account_info(), authority: escrow.to_account_info() };
+    let cpi_ctx = CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer_seeds);
+    token::transfer(cpi_ctx, amount)?;
+
+    if escrow.claimed.count_ones() as usize >= rc { escrow.total_amount = 0; }
+    let (slot, timestamp) = event_clock()?;
+    emit!(RecipientClaimed {
+        escrow: escrow.key(),
+        recipient,
+        index: idx as u8,
+        amount,
+        destination: destination_ata.owner,
+        claimed: escrow.claimed,
+        slot,
+        timestamp,
+        status_before: escrow.status,
+        status_after: escrow.status,
+    });
+    Ok(())
+}
+
+/// (kind, owner) of every index page an escrow is listed in.
+/// Order: requester, mint, then one entry per recipient.
+pub fn index_owners(escrow: &Escrow) -> Vec<(u8, Pubkey)> {
+    let rc = escrow.recipient_count as usize;
+    let mut out = Vec::with_capacity(rc + 2);
+    out.push((INDEX_KIND_REQUESTER, escrow.requester));
+    out.push((INDEX_KIND_MINT, escrow.token_mint));
+    for recipient in escrow.recipients.iter().take(rc) {
+        out.push((INDEX_KIND_RECIPIENT, *recipient));
+    }
+    out
+}
+
+/// Appends `escrow_key` to the index pages passed in `pages` (see `index_owners` for order).
+pub fn add_to_index<'info>(escrow: &Escrow, escrow_key: Pubkey, pages: &'info [AccountInfo<'info>]) -> Result<()> {
+    let owners = index_owners(escrow);
+    require!(pages.len() == owners.len(), EscrowError::IndexAccountsMismatch);
+    for ((kind, owner), info) in owners.iter().zip(pages.iter()) {
+        let mut page = Account::<EscrowIndex>::try_from(info)?;
+        require!(page.kind == *kind && page.owner == *owner, EscrowError::InvalidIndexPage);
+        let n = page.count as usize;
+        require!(n < INDEX_PAGE_CAPACITY, EscrowError::IndexPageFull);
+        page.escrows[n] = escrow_key;
+        page.count += 1;
+        page.exit(&crate::ID)?;
+    }
+    Ok(())
+}
+
+/// Removes `escrow_key` from the index pages passed in `pages` (swap-remove, order not kept).
+/// The page is not refilled from later pages, so any page may be partly empty afterwards.
+pub fn remove_from_index<'info>(escrow: &Escrow, escrow_key: Pubkey, pages: &'info [AccountInfo<'info>]) -> Result<()> {
+    let owners = index_owners(escrow);
+    require!(pages.len() == owners.len(), EscrowError::IndexAccountsMismatch);
+    for ((kind, owner), info) in owners.iter().zip(pages.iter()) {
+        let mut page = Account::<EscrowIndex>::try_from(info)?;
+        require!(page.kind == *kind && page.owner == *owner, EscrowError::InvalidIndexPage);
+        let n = page.count as usize;
+        let pos = page.escrows[..n].iter().position(|k| *k == escrow_key).ok_or(error!(EscrowError::NotIndexed))?;
+        page.escrows[pos] = page.escrows[n - 1];
+        page.escrows[n - 1] = Pubkey::default();
+        page.count -= 1;
+        page.exit(&crate::ID)?;
+    }
+    Ok(())
+}
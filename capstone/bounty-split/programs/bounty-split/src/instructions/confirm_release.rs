@@ -3,16 +3,20 @@ use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::errors::*;
 use crate::events::*;
-use crate::helpers::count_bits;
+use crate::helpers::{count_bits, event_clock, require_not_paused};
 
 #[derive(Accounts)]
 pub struct ConfirmRelease<'info> {
     #[account(mut)]
     pub escrow: Account<'info, Escrow>,
     pub recipient: Signer<'info>,
+    /// CHECK: the `[b"guardian"]` PDA. Until `initialize_guardian` creates it nothing is paused.
+    #[account(seeds = [b"guardian"], bump)]
+    pub guardian_config: UncheckedAccount<'info>,
 }
 
 pub fn confirm(ctx: Context<ConfirmRelease>) -> Result<()> {
+    require_not_paused(&ctx.accounts.guardian_config, PAUSE_RELEASE)?;
     let e = &mut ctx.accounts.escrow;
     require!(e.status == STATUS_PENDING, EscrowError::InvalidStatus);
     // find recipient index
@@ -20,7 +24,25 @@ pub fn confirm(ctx: Context<ConfirmRelease>) -> Result<()> {
     let idx = idx.ok_or(error!(EscrowError::RecipientNotFound))?;
     require!((e.confirmations & (1u8 << idx)) == 0, EscrowError::AlreadyConfirmed);
     e.confirmations |= 1u8 << idx;
-    emit!(ReleaseConfirmed { escrow: e.key(), by: ctx.accounts.recipient.key(), confirmations: e.confirmations });
+    let status_before = e.status;
     if count_bits(e.confirmations) as usize >= e.required_confirmations as usize { e.status = STATUS_RELEASED; }
+
+    let (slot, timestamp) = event_clock()?;
+    let by = ctx.accounts.recipient.key();
+    emit!(ReleaseConfirmed {
+        escrow: e.key(),
+        by,
+        index: idx as u8,
+        confirmations: e.confirmations,
+        slot,
+        timestamp,
+        status_before,
+        status_after: status_before,
+    });
+    // The final confirmation's status change is reported by EscrowReleased
+    if e.status == STATUS_RELEASED {
+        e.released_at = timestamp;
+        emit!(EscrowReleased { escrow: e.key(), by, total_amount: e.total_amount, slot, timestamp, status_before, status_after: e.status });
+    }
     Ok(())
 }
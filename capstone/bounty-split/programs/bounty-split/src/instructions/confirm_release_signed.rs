@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions as instructions_sysvar;
+use crate::state::*;
+use crate::errors::*;
+use crate::events::*;
+use crate::approvals::{load_approvals, APPROVAL_ACTION_CONFIRM_RELEASE};
+use crate::helpers::{count_bits, event_clock, require_not_paused};
+
+#[derive(Accounts)]
+pub struct ConfirmReleaseSigned<'info> {
+    #[account(mut)]
+    pub escrow: Account<'info, Escrow>,
+    /// CHECK: the `[b"guardian"]` PDA. Until `initialize_guardian` creates it nothing is paused.
+    #[account(seeds = [b"guardian"], bump)]
+    pub guardian_config: UncheckedAccount<'info>,
+    /// CHECK: address checked; read through `sysvar::instructions`
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+/// Applies every approval for this escrow carried by the transaction.
+///
+/// A signer already counted in `confirmations` is rejected, so a relayer may submit
+/// approvals in several batches without invalidating the ones it hasn't sent yet.
+/// `approval_nonce` is bumped once the escrow is released, closing the round.
+pub fn confirm_release_signed(ctx: Context<ConfirmReleaseSigned>) -> Result<()> {
+    require_not_paused(&ctx.accounts.guardian_config, PAUSE_RELEASE)?;
+    let e = &mut ctx.accounts.escrow;
+    require!(e.status == STATUS_PENDING, EscrowError::InvalidStatus);
+
+    let approvals = load_approvals(&ctx.accounts.instructions.to_account_info(), &e.key(), e.created_at)?;
+    require!(!approvals.is_empty(), EscrowError::NoApprovals);
+
+    let (slot, timestamp) = event_clock()?;
+    let status_before = e.status;
+    let mut last_signer = Pubkey::default();
+    for approval in approvals {
+        require!(
+            approval.nonce == e.approval_nonce && approval.action == APPROVAL_ACTION_CONFIRM_RELEASE,
+            EscrowError::InvalidApproval
+        );
+        let idx = e.recipients[..e.recipient_count as usize]
+            .iter()
+            .position(|r| *r == approval.signer)
+            .ok_or(error!(EscrowError::RecipientNotFound))?;
+        require!((e.confirmations & (1u8 << idx)) == 0, EscrowError::AlreadyConfirmed);
+        e.confirmations |= 1u8 << idx;
+        last_signer = approval.signer;
+        emit!(ReleaseConfirmed {
+            escrow: e.key(),
+            by: approval.signer,
+            index: idx as u8,
+            confirmations: e.confirmations,
+            slot,
+            timestamp,
+            status_before,
+            status_after: status_before,
+        });
+    }
+    if count_bits(e.confirmations) as usize >= e.required_confirmations as usize {
+        e.approval_nonce = e.approval_nonce.checked_add(1).ok_or(error!(EscrowError::Overflow))?;
+        e.status = STATUS_RELEASED;
+        e.released_at = timestamp;
+        emit!(EscrowReleased {
+            escrow: e.key(),
+            by: last_signer,
+            total_amount: e.total_amount,
+            slot,
+            timestamp,
+            status_before,
+            status_after: e.status,
+        });
+    }
+    Ok(())
+}
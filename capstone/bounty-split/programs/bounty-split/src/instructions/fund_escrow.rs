@@ -12,6 +12,7 @@ use anchor_spl::token::{
 use crate::state::*;
 use crate::errors::*;
 use crate::events::*;
+use crate::helpers::{event_clock, require_not_paused};
 
 #[derive(Accounts)]
 pub struct FundEscrow<'info> {
@@ -37,11 +38,16 @@ pub struct FundEscrow<'info> {
     )]
     pub vault: Account<'info, TokenAccount>,
 
+    /// CHECK: the `[b"guardian"]` PDA. Until `initialize_guardian` creates it nothing is paused.
+    #[account(seeds = [b"guardian"], bump)]
+    pub guardian_config: UncheckedAccount<'info>,
+
     pub token_program: Program<'info, Token>,
 }
 
 pub fn fund_escrow(ctx: Context<FundEscrow>, amount: u64) -> Result<()> {
     require!(amount > 0, EscrowError::InvalidAmount);
+    require_not_paused(&ctx.accounts.guardian_config, PAUSE_FUND)?;
 
     // Transfer tokens from payer ATA -> vault ATA
     let cpi_accounts = TransferChecked {
@@ -59,8 +65,19 @@ pub fn fund_escrow(ctx: Context<FundEscrow>, amount: u64) -> Result<()> {
     escrow.total_amount = escrow.total_amount.checked_add(amount).ok_or(error!(EscrowError::Overflow))?;
 
     // if this was the first fund, mark as Funded
+    let status_before = escrow.status;
     if escrow.status == STATUS_INITIALIZED { escrow.status = STATUS_FUNDED; }
 
-    emit!(EscrowFunded { escrow: ctx.accounts.escrow.key(), amount });
+    let (slot, timestamp) = event_clock()?;
+    emit!(EscrowFunded {
+        escrow: escrow.key(),
+        payer: ctx.accounts.payer.key(),
+        amount,
+        total_amount: escrow.total_amount,
+        slot,
+        timestamp,
+        status_before,
+        status_after: escrow.status,
+    });
     Ok(())
 }
@@ -4,7 +4,9 @@ use anchor_spl::token::Token;
 use anchor_spl::token::TokenAccount;
 use crate::state::*;
 use crate::events::*;
+use crate::helpers::{event_clock, require_not_paused};
 use crate::errors::EscrowError;
+use crate::instructions::common::add_to_index;
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct InitializeParams {
@@ -22,7 +24,7 @@ pub struct InitializeParams {
 pub struct InitializeEscrow<'info> {
     #[account(
         init,
-        payer = requester,
+        payer = payer,
         space = ESCROW_SPACE,
         seeds = [b"escrow", requester.key().as_ref(), params.bounty_id.as_ref()],
         bump
@@ -36,16 +38,29 @@ pub struct InitializeEscrow<'info> {
     constraint = vault.mint == params.token_mint
     )]
     pub vault: Account<'info, TokenAccount>,
-    
+
+    /// Pays rent for the escrow account. Kept separate from `requester` so a
+    /// program-owned PDA (governance treasury, multisig vault) can act as the
+    /// requester through CPI while a regular wallet covers account creation.
     #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Owner of the escrow. May be a PDA signing via `invoke_signed`.
     pub requester: Signer<'info>,
 
+    /// CHECK: the `[b"guardian"]` PDA. Until `initialize_guardian` creates it nothing is paused.
+    #[account(seeds = [b"guardian"], bump)]
+    pub guardian_config: UncheckedAccount<'info>,
+
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
     pub rent: Sysvar<'info, Rent>,
 }
 
-pub fn initialize_escrow(ctx: Context<InitializeEscrow>, params: InitializeParams) -> Result<()> {
+/// `remaining_accounts`: writable `EscrowIndex` pages with room for one more entry,
+/// for the requester, the mint, then each recipient in order.
+pub fn initialize_escrow<'info>(ctx: Context<'_, '_, 'info, 'info, InitializeEscrow<'info>>, params: InitializeParams) -> Result<()> {
+    require_not_paused(&ctx.accounts.guardian_config, PAUSE_INITIALIZE)?;
     let escrow = &mut ctx.accounts.escrow;
 
     let recipients = &params.recipients;
@@ -106,7 +121,24 @@ pub fn initialize_escrow(ctx: Context<InitializeEscrow>, params: InitializeParam
     escrow.timelock_expiry = params.timelock_expiry;
     escrow.bump = ctx.bumps.escrow; // updated bump retrieval
     escrow.claimed = 0; // initialize claimed bitmask
+    escrow.indexed = 1;
+    escrow.version = ESCROW_VERSION;
+
+    add_to_index(escrow, escrow.key(), ctx.remaining_accounts)?;
 
-    emit!(EscrowCreated { escrow: escrow.key(), requester: escrow.requester, bounty_id: escrow.bounty_id });
+    let (slot, timestamp) = event_clock()?;
+    emit!(EscrowCreated {
+        escrow: escrow.key(),
+        requester: escrow.requester,
+        bounty_id: escrow.bounty_id,
+        token_mint: escrow.token_mint,
+        arbiter: escrow.arbiter,
+        recipient_count: escrow.recipient_count,
+        required_confirmations: escrow.required_confirmations,
+        slot,
+        timestamp,
+        status_before: STATUS_INITIALIZED,
+        status_after: escrow.status,
+    });
     Ok(())
 }
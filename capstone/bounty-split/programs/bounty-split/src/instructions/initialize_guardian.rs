@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+use crate::program::BountySplit;
+use crate::state::*;
+use crate::errors::*;
+
+#[derive(Accounts)]
+pub struct InitializeGuardian<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = GUARDIAN_CONFIG_SPACE,
+        seeds = [b"guardian"],
+        bump
+    )]
+    pub guardian_config: Account<'info, GuardianConfig>,
+
+    /// Must be the program's upgrade authority
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ EscrowError::Unauthorized)]
+    pub program: Program<'info, BountySplit>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(authority.key()) @ EscrowError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_guardian(ctx: Context<InitializeGuardian>, guardian: Pubkey) -> Result<()> {
+    let g = &mut ctx.accounts.guardian_config;
+    g.guardian = guardian;
+    g.paused = false;
+    g.pause_flags = 0;
+    g.bump = ctx.bumps.guardian_config;
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+use crate::state::*;
+use crate::errors::*;
+use crate::events::*;
+use crate::helpers::event_clock;
+
+#[derive(Accounts)]
+pub struct MigrateEscrow<'info> {
+    /// CHECK: may be shorter than the current layout; decoded by `Escrow::from_account_data`
+    #[account(mut, owner = crate::ID)]
+    pub escrow: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn migrate_escrow(ctx: Context<MigrateEscrow>) -> Result<()> {
+    let info = ctx.accounts.escrow.to_account_info();
+    let mut escrow = Escrow::from_account_data(&info.try_borrow_data()?)?;
+    let from_version = escrow.version;
+    require!(from_version < ESCROW_VERSION, EscrowError::AlreadyMigrated);
+
+    let (slot, timestamp) = event_clock()?;
+    // Fields added so far start at 0 unless noted:
+    // v0 -> v1: `indexed` and `usd_state` were added inside the old padding
+    // v1 -> v2: `approval_nonce` starts at 0
+    // v2 -> v3: escrows released before the upgrade take the migration time as
+    //           `released_at`, so a pending USD target can still be settled
+    if escrow.status == STATUS_RELEASED && escrow.released_at == 0 {
+        escrow.released_at = timestamp;
+    }
+    escrow.version = ESCROW_VERSION;
+
+    if info.data_len() < ESCROW_SPACE {
+        let top_up = Rent::get()?.minimum_balance(ESCROW_SPACE).saturating_sub(info.lamports());
+        if top_up > 0 {
+            let cpi_accounts = Transfer { from: ctx.accounts.payer.to_account_info(), to: info.clone() };
+            let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+            system_program::transfer(cpi_ctx, top_up)?;
+        }
+        info.resize(ESCROW_SPACE)?;
+    }
+
+    let mut data = info.try_borrow_mut_data()?;
+    escrow.try_serialize(&mut &mut data[..])?;
+    // Everything past the current layout must read as zero for future versions
+    data[8 + Escrow::INIT_SPACE..].fill(0);
+
+    emit!(EscrowMigrated {
+        escrow: info.key(),
+        from_version,
+        to_version: escrow.version,
+        slot,
+        timestamp,
+        status_before: escrow.status,
+        status_after: escrow.status,
+    });
+    Ok(())
+}
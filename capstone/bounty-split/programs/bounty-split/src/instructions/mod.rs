@@ -8,6 +8,16 @@ pub mod raise_dispute;
 pub mod resolve_dispute;
 pub mod refund;
 pub mod claim;
+pub mod open_index_page;
+pub mod close_escrow;
+pub mod initialize_guardian;
+pub mod update_guardian;
+pub mod set_usd_target;
+pub mod settle_usd_target;
+pub mod migrate_escrow;
+pub mod confirm_release_signed;
+pub mod claim_on_behalf;
+pub mod payout_destination;
 pub mod common;
 
 pub use initialize_escrow::*;
@@ -19,4 +29,14 @@ pub use raise_dispute::*;
 pub use resolve_dispute::*;
 pub use refund::*;
 pub use claim::*;
+pub use open_index_page::*;
+pub use close_escrow::*;
+pub use initialize_guardian::*;
+pub use update_guardian::*;
+pub use set_usd_target::*;
+pub use settle_usd_target::*;
+pub use migrate_escrow::*;
+pub use confirm_release_signed::*;
+pub use claim_on_behalf::*;
+pub use payout_destination::*;
 pub use common::*;
\ No newline at end of file
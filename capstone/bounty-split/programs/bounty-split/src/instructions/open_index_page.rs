@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::*;
+
+#[derive(Accounts)]
+#[instruction(kind: u8, owner: Pubkey, page: u32)]
+pub struct OpenIndexPage<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Page `page - 1` for the same owner; required (and must be full) unless `page == 0`
+    pub previous_page: Option<Account<'info, EscrowIndex>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = ESCROW_INDEX_SPACE,
+        seeds = [b"index", kind.to_le_bytes().as_ref(), owner.as_ref(), page.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub index_page: Account<'info, EscrowIndex>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn open_index_page(ctx: Context<OpenIndexPage>, kind: u8, owner: Pubkey, page: u32) -> Result<()> {
+    require!(kind <= INDEX_KIND_MINT, EscrowError::InvalidIndexPage);
+
+    // Pages are opened in order so readers can walk 0, 1, 2... until the first missing one.
+    // Closes can later leave room on any page, so "full" only holds when the next page opens.
+    if page > 0 {
+        let prev = ctx.accounts.previous_page.as_ref().ok_or(error!(EscrowError::InvalidIndexPage))?;
+        require!(prev.kind == kind && prev.owner == owner && prev.page + 1 == page, EscrowError::InvalidIndexPage);
+        require!(prev.count as usize == INDEX_PAGE_CAPACITY, EscrowError::InvalidIndexPage);
+    }
+
+    let p = &mut ctx.accounts.index_page;
+    p.kind = kind;
+    p.owner = owner;
+    p.page = page;
+    p.count = 0;
+    p.bump = ctx.bumps.index_page;
+    Ok(())
+}
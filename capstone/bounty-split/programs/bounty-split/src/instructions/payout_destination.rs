@@ -0,0 +1,77 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::*;
+use crate::events::*;
+use crate::helpers::event_clock;
+
+#[derive(Accounts)]
+pub struct SetPayoutDestination<'info> {
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = PAYOUT_DESTINATION_SPACE,
+        seeds = [b"payout", recipient.key().as_ref()],
+        bump
+    )]
+    pub payout_destination: Account<'info, PayoutDestination>,
+    pub recipient: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetEscrowPayoutDestination<'info> {
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = PAYOUT_DESTINATION_SPACE,
+        seeds = [b"payout", recipient.key().as_ref(), escrow.key().as_ref()],
+        bump
+    )]
+    pub payout_destination: Account<'info, PayoutDestination>,
+    pub recipient: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClearPayoutDestination<'info> {
+    #[account(mut, close = recipient, has_one = recipient @ EscrowError::Unauthorized)]
+    pub payout_destination: Account<'info, PayoutDestination>,
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+}
+
+pub fn set_payout_destination(ctx: Context<SetPayoutDestination>, destination: Pubkey) -> Result<()> {
+    let bump = ctx.bumps.payout_destination;
+    write_destination(&mut ctx.accounts.payout_destination, ctx.accounts.recipient.key(), Pubkey::default(), destination, bump)
+}
+
+pub fn set_escrow_payout_destination(ctx: Context<SetEscrowPayoutDestination>, destination: Pubkey) -> Result<()> {
+    let e = &ctx.accounts.escrow;
+    let recipient = ctx.accounts.recipient.key();
+    require!(e.recipients[..e.recipient_count as usize].contains(&recipient), EscrowError::RecipientNotFound);
+    let bump = ctx.bumps.payout_destination;
+    write_destination(&mut ctx.accounts.payout_destination, recipient, e.key(), destination, bump)
+}
+
+pub fn clear_payout_destination(ctx: Context<ClearPayoutDestination>) -> Result<()> {
+    let p = &ctx.accounts.payout_destination;
+    let (slot, timestamp) = event_clock()?;
+    emit!(PayoutDestinationSet { recipient: p.recipient, escrow: p.escrow, destination: Pubkey::default(), slot, timestamp });
+    Ok(())
+}
+
+fn write_destination(p: &mut PayoutDestination, recipient: Pubkey, escrow: Pubkey, destination: Pubkey, bump: u8) -> Result<()> {
+    require_keys_neq!(destination, Pubkey::default(), EscrowError::InvalidDestination);
+    p.recipient = recipient;
+    p.escrow = escrow;
+    p.destination = destination;
+    p.bump = bump;
+    let (slot, timestamp) = event_clock()?;
+    emit!(PayoutDestinationSet { recipient, escrow, destination, slot, timestamp });
+    Ok(())
+}
@@ -2,14 +2,19 @@ use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::errors::*;
 use crate::events::*;
+use crate::helpers::{event_clock, require_not_paused};
 
 #[derive(Accounts)]
 pub struct ProposeRelease<'info> {
     #[account(mut)] pub escrow: Account<'info, Escrow>,
     pub proposer: Signer<'info>,
+    /// CHECK: the `[b"guardian"]` PDA. Until `initialize_guardian` creates it nothing is paused.
+    #[account(seeds = [b"guardian"], bump)]
+    pub guardian_config: UncheckedAccount<'info>,
 }
 
 pub fn propose_release(ctx: Context<ProposeRelease>) -> Result<()> {
+    require_not_paused(&ctx.accounts.guardian_config, PAUSE_RELEASE)?;
     let e = &mut ctx.accounts.escrow;
     require!(e.status == STATUS_FUNDED, EscrowError::InvalidStatus);
     let k = ctx.accounts.proposer.key();
@@ -18,7 +23,9 @@ pub fn propose_release(ctx: Context<ProposeRelease>) -> Result<()> {
         for i in 0..(e.recipient_count as usize) { if e.recipients[i] == k { authorized = true; break; } }
     }
     require!(authorized, EscrowError::Unauthorized);
+    let status_before = e.status;
     e.status = STATUS_PENDING;
-    emit!(ReleaseProposed { escrow: e.key(), by: k });
+    let (slot, timestamp) = event_clock()?;
+    emit!(ReleaseProposed { escrow: e.key(), by: k, slot, timestamp, status_before, status_after: e.status });
     Ok(())
 }
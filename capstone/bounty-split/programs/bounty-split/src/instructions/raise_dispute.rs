@@ -2,6 +2,7 @@ use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::errors::*;
 use crate::events::*;
+use crate::helpers::event_clock;
 
 #[derive(Accounts)]
 pub struct RaiseDispute<'info> {
@@ -16,7 +17,9 @@ pub fn raise_dispute(ctx: Context<RaiseDispute>, reason_hash: [u8;32]) -> Result
     let mut authorized = k == e.requester;
     if !authorized { for i in 0..(e.recipient_count as usize) { if e.recipients[i] == k { authorized = true; break; } } }
     require!(authorized, EscrowError::Unauthorized);
+    let status_before = e.status;
     e.status = STATUS_DISPUTED;
-    emit!(DisputeRaised { escrow: e.key(), by: k, reason_hash });
+    let (slot, timestamp) = event_clock()?;
+    emit!(DisputeRaised { escrow: e.key(), by: k, reason_hash, slot, timestamp, status_before, status_after: e.status });
     Ok(())
 }
@@ -3,6 +3,7 @@ use anchor_spl::token::{self, TokenAccount, Token};
 use crate::state::*;
 use crate::errors::*;
 use crate::events::*;
+use crate::helpers::event_clock;
 
 #[derive(Accounts)]
 pub struct Refund<'info> {
@@ -15,13 +16,13 @@ pub struct Refund<'info> {
 
 pub fn refund(ctx: Context<Refund>) -> Result<()> {
     // Phase 1: extract data & mutate state minimally
-    let (requester_key, bounty_id, bump, amount) = {
+    let (requester_key, bounty_id, bump, amount, status_before) = {
         let e = &mut ctx.accounts.escrow;
         require!(e.requester == ctx.accounts.requester.key(), EscrowError::Unauthorized);
         require!(e.status != STATUS_RELEASED && e.status != STATUS_REFUNDED, EscrowError::AlreadyFinalized);
         if e.timelock_expiry != 0 { let now = Clock::get()?.unix_timestamp; require!(now >= e.timelock_expiry, EscrowError::TimelockActive); }
         let amt = e.total_amount; require!(amt > 0, EscrowError::InsufficientFunds);
-        (e.requester, e.bounty_id, e.bump, amt)
+        (e.requester, e.bounty_id, e.bump, amt, e.status)
     };
 
     // Phase 2: perform transfer (no mutable escrow borrow during CPI)
@@ -35,6 +36,7 @@ pub fn refund(ctx: Context<Refund>) -> Result<()> {
     let e2 = &mut ctx.accounts.escrow;
     e2.total_amount = 0;
     e2.status = STATUS_REFUNDED;
-    emit!(EscrowRefunded { escrow: e2.key(), refunded_to: requester_key, amount });
+    let (slot, timestamp) = event_clock()?;
+    emit!(EscrowRefunded { escrow: e2.key(), refunded_to: requester_key, amount, slot, timestamp, status_before, status_after: e2.status });
     Ok(())
 }
@@ -3,6 +3,7 @@ use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::errors::*;
 use crate::events::*;
+use crate::helpers::{event_clock, require_not_paused};
 
 
 #[derive(Accounts)]
@@ -10,13 +11,28 @@ pub struct Release<'info> {
     #[account(mut)]
     pub escrow: Account<'info, Escrow>,
     pub requester: Signer<'info>,
+    /// CHECK: the `[b"guardian"]` PDA. Until `initialize_guardian` creates it nothing is paused.
+    #[account(seeds = [b"guardian"], bump)]
+    pub guardian_config: UncheckedAccount<'info>,
 }
 
 pub fn release(ctx: Context<Release>) -> Result<()> {
+    require_not_paused(&ctx.accounts.guardian_config, PAUSE_RELEASE)?;
     let e = &mut ctx.accounts.escrow;
     require!(e.status == STATUS_FUNDED || e.status == STATUS_PENDING, EscrowError::InvalidStatus);
     require_keys_eq!(e.requester, ctx.accounts.requester.key(), EscrowError::Unauthorized);
+    let status_before = e.status;
     e.status = STATUS_RELEASED;
-    emit!(EscrowReleased { escrow: e.key(), total_distributed: 0 });
+    let (slot, timestamp) = event_clock()?;
+    e.released_at = timestamp;
+    emit!(EscrowReleased {
+        escrow: e.key(),
+        by: ctx.accounts.requester.key(),
+        total_amount: e.total_amount,
+        slot,
+        timestamp,
+        status_before,
+        status_after: e.status,
+    });
     Ok(())
 }
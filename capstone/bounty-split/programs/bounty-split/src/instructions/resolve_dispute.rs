@@ -2,19 +2,36 @@ use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::errors::*;
 use crate::events::*;
+use crate::helpers::{event_clock, require_not_paused};
 
 #[derive(Accounts)]
 pub struct ResolveDispute<'info> {
     #[account(mut)]
     pub escrow: Account<'info, Escrow>,
     pub arbiter: Signer<'info>,
+    /// CHECK: the `[b"guardian"]` PDA. Until `initialize_guardian` creates it nothing is paused.
+    #[account(seeds = [b"guardian"], bump)]
+    pub guardian_config: UncheckedAccount<'info>,
 }
 
 pub fn resolve_dispute(ctx: Context<ResolveDispute>) -> Result<()> {
+    require_not_paused(&ctx.accounts.guardian_config, PAUSE_RELEASE)?;
     let e = &mut ctx.accounts.escrow;
     require!(e.status == STATUS_DISPUTED, EscrowError::InvalidStatus);
     require_keys_eq!(e.arbiter, ctx.accounts.arbiter.key(), EscrowError::Unauthorized);
+    let status_before = e.status;
     e.status = STATUS_RELEASED;
-    emit!(EscrowReleased { escrow: e.key(), total_distributed: 0 });
+    let (slot, timestamp) = event_clock()?;
+    e.released_at = timestamp;
+    emit!(DisputeResolved {
+        escrow: e.key(),
+        arbiter: e.arbiter,
+        ruling: RULING_RELEASE,
+        total_amount: e.total_amount,
+        slot,
+        timestamp,
+        status_before,
+        status_after: e.status,
+    });
     Ok(())
 }
@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::*;
+use crate::events::*;
+use crate::helpers::event_clock;
+
+#[derive(Accounts)]
+pub struct SetUsdTarget<'info> {
+    #[account(mut, has_one = requester @ EscrowError::Unauthorized)]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = USD_TERMS_SPACE,
+        seeds = [b"usd_terms", escrow.key().as_ref()],
+        bump
+    )]
+    pub usd_terms: Account<'info, UsdTerms>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub requester: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn set_usd_target(ctx: Context<SetUsdTarget>, usd_target: u64, price_feed_id: [u8; 32], max_price_age: u32) -> Result<()> {
+    let e = &mut ctx.accounts.escrow;
+    // Terms are fixed before any funds arrive so recipients can review them
+    require!(e.status == STATUS_INITIALIZED, EscrowError::InvalidStatus);
+    require!(usd_target > 0, EscrowError::InvalidAmount);
+    require!(max_price_age > 0, EscrowError::StalePrice);
+
+    let t = &mut ctx.accounts.usd_terms;
+    t.escrow = e.key();
+    t.usd_target = usd_target;
+    t.price_feed_id = price_feed_id;
+    t.max_price_age = max_price_age;
+    t.bump = ctx.bumps.usd_terms;
+    e.usd_state = USD_PENDING;
+
+    let (slot, timestamp) = event_clock()?;
+    emit!(UsdTargetSet { escrow: e.key(), usd_target, price_feed_id, slot, timestamp, status_before: e.status, status_after: e.status });
+    Ok(())
+}
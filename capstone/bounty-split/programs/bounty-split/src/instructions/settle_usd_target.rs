@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+use crate::state::*;
+use crate::errors::*;
+use crate::events::*;
+use crate::helpers::event_clock;
+use crate::oracle::{load_price, usd_to_tokens};
+
+#[derive(Accounts)]
+pub struct SettleUsdTarget<'info> {
+    #[account(mut)]
+    pub escrow: Account<'info, Escrow>,
+    #[account(seeds = [b"usd_terms", escrow.key().as_ref()], bump = usd_terms.bump)]
+    pub usd_terms: Account<'info, UsdTerms>,
+    /// CHECK: parsed and validated by `oracle::load_price`
+    pub price_update: UncheckedAccount<'info>,
+    #[account(constraint = mint.key() == escrow.token_mint @ EscrowError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    #[account(mut, constraint = vault.owner == escrow.key(), constraint = vault.mint == escrow.token_mint)]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(mut, constraint = requester_ata.owner == escrow.requester, constraint = requester_ata.mint == escrow.token_mint)]
+    pub requester_ata: Account<'info, TokenAccount>,
+    /// The requester, a recipient or the arbiter
+    pub settler: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Converts the USD target at a price published within `max_price_age` of the
+/// release, so waiting for a better price doesn't pay off however late settlement runs.
+pub fn settle_usd_target(ctx: Context<SettleUsdTarget>) -> Result<()> {
+    let terms = &ctx.accounts.usd_terms;
+    let (requester_key, bounty_id, bump, funded, released_at) = {
+        let e = &ctx.accounts.escrow;
+        require!(e.status == STATUS_RELEASED, EscrowError::InvalidStatus);
+        require!(e.usd_state == USD_PENDING, EscrowError::InvalidStatus);
+        // Released before the v3 upgrade and not migrated yet
+        require!(e.released_at != 0, EscrowError::MigrationRequired);
+        let settler = ctx.accounts.settler.key();
+        let is_party = settler == e.requester
+            || e.recipients[..e.recipient_count as usize].contains(&settler)
+            || (e.arbiter != Pubkey::default() && settler == e.arbiter);
+        require!(is_party, EscrowError::Unauthorized);
+        (e.requester, e.bounty_id, e.bump, e.total_amount, e.released_at)
+    };
+
+    let price = load_price(&ctx.accounts.price_update.to_account_info())?;
+    require!(price.feed_id == terms.price_feed_id, EscrowError::InvalidPriceFeed);
+    let (slot, now) = event_clock()?;
+    require!(price.publish_time.abs_diff(released_at) <= terms.max_price_age as u64, EscrowError::StalePrice);
+
+    // An underfunded escrow pays out everything it holds
+    let owed = usd_to_tokens(terms.usd_target, &price, ctx.accounts.mint.decimals)?;
+    let amount = owed.min(funded);
+    let returned = funded - amount;
+
+    if returned > 0 {
+        let seeds: &[&[u8]] = &[b"escrow", requester_key.as_ref(), &bounty_id[..], &[bump]];
+        let signer_seeds = &[seeds];
+        let cpi_accounts = token::Transfer { from: ctx.accounts.vault.to_account_info(), to: ctx.accounts.requester_ata.to_account_info(), authority: ctx.accounts.escrow.to_account_info() };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        token::transfer(cpi_ctx, returned)?;
+    }
+
+    let e = &mut ctx.accounts.escrow;
+    e.total_amount = amount;
+    e.usd_state = USD_SETTLED;
+    emit!(UsdTargetSettled {
+        escrow: e.key(),
+        price: price.price,
+        expo: price.expo,
+        amount,
+        returned,
+        slot,
+        timestamp: now,
+        status_before: e.status,
+        status_after: e.status,
+    });
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::*;
+use crate::events::*;
+use crate::helpers::event_clock;
+
+#[derive(Accounts)]
+pub struct UpdateGuardian<'info> {
+    #[account(mut, seeds = [b"guardian"], bump = guardian_config.bump, has_one = guardian @ EscrowError::Unauthorized)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+    pub guardian: Signer<'info>,
+}
+
+pub fn set_pause(ctx: Context<UpdateGuardian>, paused: bool, pause_flags: u8) -> Result<()> {
+    require!(pause_flags & !PAUSE_ALL == 0, EscrowError::InvalidPauseFlags);
+    let g = &mut ctx.accounts.guardian_config;
+    g.paused = paused;
+    g.pause_flags = pause_flags;
+    let (slot, timestamp) = event_clock()?;
+    emit!(PauseUpdated { guardian: g.guardian, paused, pause_flags, slot, timestamp });
+    Ok(())
+}
+
+pub fn set_guardian(ctx: Context<UpdateGuardian>, new_guardian: Pubkey) -> Result<()> {
+    ctx.accounts.guardian_config.guardian = new_guardian;
+    Ok(())
+}
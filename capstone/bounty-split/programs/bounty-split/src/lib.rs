@@ -1,10 +1,27 @@
 #![allow(deprecated, unexpected_cfgs)]
+//! Bounty escrow that splits a funded amount between up to `MAX_RECIPIENTS`.
+//!
+//! # Calling through CPI
+//!
+//! Build with the `cpi` feature to get `bounty_split::cpi::*` entry points and
+//! `bounty_split::cpi::accounts::*` structs. Every authority account (requester,
+//! arbiter, proposer, recipient) is a plain `Signer`, so a program-derived
+//! address can fill any of these roles by signing with
+//! `CpiContext::new_with_signer`. Rent for new accounts is always taken from a
+//! separate `payer`, which lets data-carrying PDAs act as requesters.
+//!
+//! See `programs/bounty-split-caller` for a governance-style caller that
+//! creates, funds and settles escrows entirely through CPI.
 use anchor_lang::prelude::*;
 
 pub mod state;
 pub mod errors;
 pub mod events;
 pub mod helpers;
+pub mod oracle;
+pub mod approvals;
+#[cfg(not(target_os = "solana"))]
+pub mod decoder;
 pub mod instructions;
 
 use instructions::*;
@@ -14,39 +31,124 @@ declare_id!("EoCSLgFJUCTaQeEPdev9KqKYgVhmnxqQL7opDHa5tEq7");
 #[program]
 pub mod bounty_split {
     use super::*;
-    pub fn initialize_escrow(ctx: Context<InitializeEscrow>, params: InitializeParams) -> Result<()> {
+    /// Creates the escrow PDA `[b"escrow", requester, bounty_id]` and lists it in the
+    /// requester, mint and recipient index pages passed as `remaining_accounts`.
+    /// `requester` signs (directly or as a PDA), `payer` funds the rent.
+    pub fn initialize_escrow<'info>(ctx: Context<'_, '_, 'info, 'info, InitializeEscrow<'info>>, params: InitializeParams) -> Result<()> {
         instructions::initialize_escrow::initialize_escrow(ctx, params)
     }
 
+    /// Moves `amount` from `payer_token_account` into the vault.
+    /// `payer` must own the source account and may be a PDA.
    pub fn fund_escrow(ctx: Context<FundEscrow>, amount: u64) -> Result<()> {
        instructions::fund_escrow::fund_escrow(ctx, amount)
     }
- 
+
+    /// Moves a funded escrow to pending. `proposer` is the requester or a recipient.
     pub fn propose_release(ctx: Context<ProposeRelease>) -> Result<()> {
         instructions::propose_release::propose_release(ctx)
     }
 
+    /// Records the confirmation of `recipient` on a pending escrow.
     pub fn confirm_release(ctx: Context<ConfirmRelease>) -> Result<()> {
         instructions::confirm_release::confirm(ctx)
     }
 
+    /// Records confirmations that recipients signed off-chain (see `approvals`), verified
+    /// through Ed25519 program instructions in the same transaction. Anyone may relay.
+    pub fn confirm_release_signed(ctx: Context<ConfirmReleaseSigned>) -> Result<()> {
+        instructions::confirm_release_signed::confirm_release_signed(ctx)
+    }
+
+    /// Releases the escrow without waiting for confirmations. `requester` only.
     pub fn release(ctx: Context<Release>) -> Result<()> {
         instructions::release::release(ctx)
     }
 
+    /// Freezes the escrow for arbitration. `initiator` is the requester or a recipient.
     pub fn raise_dispute(ctx: Context<RaiseDispute>, reason_hash: [u8;32]) -> Result<()> {
         instructions::raise_dispute::raise_dispute(ctx, reason_hash)
     }
 
+    /// Releases a disputed escrow. `arbiter` must match `Escrow::arbiter`.
     pub fn resolve_dispute(ctx: Context<ResolveDispute>) -> Result<()> {
         instructions::resolve_dispute::resolve_dispute(ctx)
     }
 
+    /// Returns the escrowed amount to `requester_ata` once any timelock has passed.
     pub fn refund(ctx: Context<Refund>) -> Result<()> {
         instructions::refund::refund(ctx)
     }
 
+    /// Pays `claimant` its split of a released escrow into the ATA of its payout
+    /// destination, creating the ATA if needed.
     pub fn claim(ctx: Context<Claim>) -> Result<()> {
         instructions::claim::claim(ctx)
     }
+
+    /// Pays `recipient` its split to its payout destination. Permissionless; `payer` funds the ATA.
+    pub fn claim_on_behalf(ctx: Context<ClaimOnBehalf>) -> Result<()> {
+        instructions::claim_on_behalf::claim_on_behalf(ctx)
+    }
+
+    /// Sends all of `recipient`'s future payouts to `destination`'s ATA. `recipient` only.
+    pub fn set_payout_destination(ctx: Context<SetPayoutDestination>, destination: Pubkey) -> Result<()> {
+        instructions::payout_destination::set_payout_destination(ctx, destination)
+    }
+
+    /// Sends `recipient`'s payout from `escrow` to `destination`'s ATA; overrides the global entry.
+    pub fn set_escrow_payout_destination(ctx: Context<SetEscrowPayoutDestination>, destination: Pubkey) -> Result<()> {
+        instructions::payout_destination::set_escrow_payout_destination(ctx, destination)
+    }
+
+    /// Removes a payout destination and refunds its rent to `recipient`.
+    pub fn clear_payout_destination(ctx: Context<ClearPayoutDestination>) -> Result<()> {
+        instructions::payout_destination::clear_payout_destination(ctx)
+    }
+
+    /// Opens index page `[b"index", kind, owner, page]`. Permissionless; `payer` funds the rent.
+    pub fn open_index_page(ctx: Context<OpenIndexPage>, kind: u8, owner: Pubkey, page: u32) -> Result<()> {
+        instructions::open_index_page::open_index_page(ctx, kind, owner, page)
+    }
+
+    /// Closes a refunded or fully claimed escrow, its vault, its index entries and its
+    /// `UsdTerms` (if any). `requester` only.
+    pub fn close_escrow<'info>(ctx: Context<'_, '_, 'info, 'info, CloseEscrow<'info>>) -> Result<()> {
+        instructions::close_escrow::close_escrow(ctx)
+    }
+
+    /// Creates the global `[b"guardian"]` config. Program upgrade authority only.
+    pub fn initialize_guardian(ctx: Context<InitializeGuardian>, guardian: Pubkey) -> Result<()> {
+        instructions::initialize_guardian::initialize_guardian(ctx, guardian)
+    }
+
+    /// Sets the global pause switch and per-instruction `PAUSE_*` bits. Guardian only.
+    /// `refund` and `close_escrow` are never paused.
+    pub fn set_pause(ctx: Context<UpdateGuardian>, paused: bool, pause_flags: u8) -> Result<()> {
+        instructions::update_guardian::set_pause(ctx, paused, pause_flags)
+    }
+
+    /// Hands the guardian role to `new_guardian`. Guardian only.
+    pub fn set_guardian(ctx: Context<UpdateGuardian>, new_guardian: Pubkey) -> Result<()> {
+        instructions::update_guardian::set_guardian(ctx, new_guardian)
+    }
+
+    /// Denominates an unfunded escrow in USD (6 decimals), priced by Pyth feed `price_feed_id`.
+    /// `requester` only.
+    pub fn set_usd_target(ctx: Context<SetUsdTarget>, usd_target: u64, price_feed_id: [u8; 32], max_price_age: u32) -> Result<()> {
+        instructions::set_usd_target::set_usd_target(ctx, usd_target, price_feed_id, max_price_age)
+    }
+
+    /// Converts the USD target of a released escrow into tokens and returns any excess
+    /// to the requester, at a price published within `max_price_age` of the release.
+    /// Requester, recipients or arbiter; must run before `claim`.
+    pub fn settle_usd_target(ctx: Context<SettleUsdTarget>) -> Result<()> {
+        instructions::settle_usd_target::settle_usd_target(ctx)
+    }
+
+    /// Upgrades an escrow written with an older layout to `ESCROW_VERSION`, growing the
+    /// account to `ESCROW_SPACE`. Permissionless; `payer` covers the extra rent.
+    pub fn migrate_escrow(ctx: Context<MigrateEscrow>) -> Result<()> {
+        instructions::migrate_escrow::migrate_escrow(ctx)
+    }
 }
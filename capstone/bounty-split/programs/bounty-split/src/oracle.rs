@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+use crate::errors::EscrowError;
+
+/// Pyth Solana Receiver program, owner of `PriceUpdateV2` accounts
+pub const PYTH_RECEIVER_PROGRAM_ID: Pubkey = pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+/// Anchor discriminator of `PriceUpdateV2` (sha256("account:PriceUpdateV2")[..8])
+pub const PRICE_UPDATE_V2_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+
+/// USD amounts (`UsdTerms::usd_target`) carry 6 decimals
+pub const USD_DECIMALS: u32 = 6;
+
+/// Price of one whole token in USD: `price * 10^expo`
+pub struct OraclePrice {
+    pub feed_id: [u8; 32],
+    pub price: i64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+/// Parse a fully verified Pyth `PriceUpdateV2` account.
+///
+/// Layout: discriminator (8) | write_authority (32) | verification_level (1 or 2)
+/// | feed_id (32) | price i64 | conf u64 | exponent i32 | publish_time i64 | ...
+pub fn load_price(info: &AccountInfo) -> Result<OraclePrice> {
+    require_keys_eq!(*info.owner, PYTH_RECEIVER_PROGRAM_ID, EscrowError::InvalidPriceFeed);
+    let data = info.try_borrow_data()?;
+    require!(data.len() > 8 + 32 && data[..8] == PRICE_UPDATE_V2_DISCRIMINATOR, EscrowError::InvalidPriceFeed);
+
+    // VerificationLevel: 0 = Partial { num_signatures: u8 }, 1 = Full
+    require!(data[40] == 1, EscrowError::InvalidPriceFeed);
+    let msg = &data[41..];
+    require!(msg.len() >= 32 + 8 + 8 + 4 + 8, EscrowError::InvalidPriceFeed);
+
+    let mut feed_id = [0u8; 32];
+    feed_id.copy_from_slice(&msg[..32]);
+    let price = i64::from_le_bytes(msg[32..40].try_into().unwrap());
+    let expo = i32::from_le_bytes(msg[48..52].try_into().unwrap());
+    let publish_time = i64::from_le_bytes(msg[52..60].try_into().unwrap());
+    Ok(OraclePrice { feed_id, price, expo, publish_time })
+}
+
+/// Token base units worth `usd_amount` (6 decimals) at `price`, rounded down
+pub fn usd_to_tokens(usd_amount: u64, price: &OraclePrice, mint_decimals: u8) -> Result<u64> {
+    require!(price.price > 0, EscrowError::InvalidPrice);
+    // amount = usd / 10^6 / (price * 10^expo) * 10^decimals
+    let scale = mint_decimals as i64 - price.expo as i64 - USD_DECIMALS as i64;
+    let pow = |e: i64| 10u128.checked_pow(e as u32).ok_or(error!(EscrowError::Overflow));
+    let (num_scale, den_scale) = if scale >= 0 { (pow(scale)?, 1) } else { (1, pow(-scale)?) };
+    let num = (usd_amount as u128).checked_mul(num_scale).ok_or(error!(EscrowError::Overflow))?;
+    let den = (price.price as u128).checked_mul(den_scale).ok_or(error!(EscrowError::Overflow))?;
+    u64::try_from(num / den).map_err(|_| error!(EscrowError::Overflow))
+}
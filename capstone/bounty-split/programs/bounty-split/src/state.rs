@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use crate::errors::EscrowError;
 
 pub const MAX_RECIPIENTS: usize = 8;
 pub const BASIS_POINTS_DENOM: u16 = 10_000;
@@ -10,9 +11,31 @@ pub const STATUS_PENDING: u8 = 2;
 pub const STATUS_RELEASED: u8 = 3;
 pub const STATUS_DISPUTED: u8 = 4;
 pub const STATUS_REFUNDED: u8 = 5;
+pub const STATUS_CLOSED: u8 = 6;           // event-only: the account no longer exists
+
+// Dispute rulings (DisputeResolved::ruling)
+pub const RULING_RELEASE: u8 = 0;          // split between recipients as configured
+
+// USD target constants (Escrow::usd_state)
+pub const USD_NONE: u8 = 0;                // plain token-denominated escrow
+pub const USD_PENDING: u8 = 1;             // UsdTerms set, converted after release
+pub const USD_SETTLED: u8 = 2;             // total_amount already converted
+
+// Escrow layout versions (Escrow::version)
+//
+// Fields are only ever appended. An account written with an older version is
+// shorter or ends in zeroed padding, so appended fields read as 0 until
+// `migrate_escrow` resizes the account and fills them in. Bump ESCROW_VERSION
+// and add an upgrade step to `migrate_escrow` with every layout change.
+pub const ESCROW_VERSION_LEGACY: u8 = 0;   // created before versioning, LEGACY_ESCROW_SPACE bytes
+// 1: adds `version` and ESCROW_RESERVED
+// 2: adds `approval_nonce`
+// 3: adds `released_at`
+pub const ESCROW_VERSION: u8 = 3;
 
 // main Escrow account
 #[account]
+#[derive(InitSpace)]
 pub struct Escrow {
     pub requester: Pubkey,                 // 32
     pub bounty_id: [u8;32],                // 32
@@ -29,9 +52,95 @@ pub struct Escrow {
     pub created_at: i64,                   // 8
     pub timelock_expiry: i64,              // 8 (0 if none)
     pub bump: u8,                          // 1
-    pub claimed: u8,                       // NEW bitmask of claimed distributions
-    // padding for future fields
+    pub claimed: u8,                       // bitmask of claimed distributions
+    pub indexed: u8,                       // 1 if listed in EscrowIndex pages (0 for pre-index escrows)
+    pub usd_state: u8,                     // see USD_* constants
+    pub version: u8,                       // see ESCROW_VERSION*; 0 on legacy accounts
+    pub approval_nonce: u64,               // bumped when `confirm_release_signed` releases the escrow
+    pub released_at: i64,                  // unix time the escrow reached STATUS_RELEASED, 0 before
+}
+
+// Zeroed bytes kept after the current layout so small additions don't need a realloc
+pub const ESCROW_RESERVED: usize = 64;
+pub const ESCROW_SPACE: usize = 8 + Escrow::INIT_SPACE + ESCROW_RESERVED;
+
+// Size of every pre-versioning escrow: 8 + size_of::<Escrow>() + 32 with the
+// original field set (464 bytes of struct, 462 of which are serialized)
+pub const LEGACY_ESCROW_SPACE: usize = 504;
+
+impl Escrow {
+    /// Deserialize an escrow of any known version. Bodies shorter than the
+    /// current layout are zero-extended, so fields appended since the account
+    /// was written read as 0.
+    pub fn from_account_data(data: &[u8]) -> Result<Self> {
+        require!(data.len() >= 8 && data[..8] == *Escrow::DISCRIMINATOR, ErrorCode::AccountDiscriminatorMismatch);
+        let mut body = data[8..].to_vec();
+        if body.len() < Escrow::INIT_SPACE {
+            body.resize(Escrow::INIT_SPACE, 0);
+        }
+        let escrow = Escrow::deserialize(&mut &body[..]).map_err(|_| error!(ErrorCode::AccountDidNotDeserialize))?;
+        require!(escrow.version <= ESCROW_VERSION, EscrowError::UnsupportedVersion);
+        Ok(escrow)
+    }
+}
+
+// Index pages: one PDA per (kind, owner, page) listing escrow keys
+pub const INDEX_PAGE_CAPACITY: usize = 32;
+pub const INDEX_KIND_REQUESTER: u8 = 0;
+pub const INDEX_KIND_RECIPIENT: u8 = 1;
+pub const INDEX_KIND_MINT: u8 = 2;
+
+// seeds = [b"index", kind, owner, page (u32 le)]
+#[account]
+pub struct EscrowIndex {
+    pub kind: u8,                          // see INDEX_KIND_* constants
+    pub owner: Pubkey,                     // requester, recipient or mint
+    pub page: u32,                         // pages are opened in order starting at 0
+    pub count: u8,                         // live entries in `escrows`; any page may have room after closes
+    pub bump: u8,
+    pub escrows: [Pubkey; INDEX_PAGE_CAPACITY],
+}
+
+pub const ESCROW_INDEX_SPACE: usize = 8 + 1 + 32 + 4 + 1 + 1 + 32 * INDEX_PAGE_CAPACITY;
+
+// Pause bits checked against GuardianConfig::pause_flags
+pub const PAUSE_INITIALIZE: u8 = 1 << 0;   // initialize_escrow
+pub const PAUSE_FUND: u8 = 1 << 1;         // fund_escrow
+pub const PAUSE_RELEASE: u8 = 1 << 2;      // propose / confirm / release / resolve_dispute
+pub const PAUSE_CLAIM: u8 = 1 << 3;        // claim
+pub const PAUSE_ALL: u8 = PAUSE_INITIALIZE | PAUSE_FUND | PAUSE_RELEASE | PAUSE_CLAIM;
+
+// Global protocol guardian, seeds = [b"guardian"]. Refunds never check it.
+#[account]
+pub struct GuardianConfig {
+    pub guardian: Pubkey,                  // may pause / unpause and hand over the role
+    pub paused: bool,                      // global switch, pauses everything in PAUSE_ALL
+    pub pause_flags: u8,                   // per-instruction PAUSE_* bits
+    pub bump: u8,
+}
+
+pub const GUARDIAN_CONFIG_SPACE: usize = 8 + 32 + 1 + 1 + 1;
+
+// Optional USD denomination, seeds = [b"usd_terms", escrow]
+#[account]
+pub struct UsdTerms {
+    pub escrow: Pubkey,
+    pub usd_target: u64,                   // USD with 6 decimals owed to recipients in total
+    pub price_feed_id: [u8; 32],           // Pyth feed id the price update must carry
+    pub max_price_age: u32,                // seconds between publish_time and `released_at`, either side
+    pub bump: u8,
+}
+
+pub const USD_TERMS_SPACE: usize = 8 + 32 + 8 + 32 + 4 + 1;
+
+// Where a recipient's payouts go. seeds = [b"payout", recipient, escrow] for one
+// escrow, [b"payout", recipient] for every escrow; the per-escrow entry wins.
+#[account]
+pub struct PayoutDestination {
+    pub recipient: Pubkey,
+    pub escrow: Pubkey,                    // Pubkey::default() for the global entry
+    pub destination: Pubkey,               // wallet or PDA whose ATA receives the tokens
+    pub bump: u8,
 }
 
-// Computed space constant (use in account init) with safety margin
-pub const ESCROW_SPACE: usize = 8 + core::mem::size_of::<Escrow>() + 32; // +32 bytes future padding
+pub const PAYOUT_DESTINATION_SPACE: usize = 8 + 32 + 32 + 32 + 1;
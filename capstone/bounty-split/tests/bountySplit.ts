@@ -2,7 +2,7 @@ import * as fs from "fs";
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { BountySplit } from "../target/types/bounty_split";
-import { Keypair, SystemProgram, PublicKey } from "@solana/web3.js";
+import { Keypair, SystemProgram, PublicKey, Ed25519Program, ComputeBudgetProgram } from "@solana/web3.js";
 import {
   TOKEN_PROGRAM_ID,
   createMint,
@@ -47,6 +47,67 @@ describe("Bounty Split - Test Suite", () => {
   const STATUS_PENDING = 2;
   const STATUS_RELEASED = 3;
   const STATUS_DISPUTED = 4;
+  const STATUS_REFUNDED = 5;
+
+  const PAUSE_FUND = 1 << 1;
+  const BPF_UPGRADEABLE_LOADER_ID = new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111");
+  const [guardianConfigPda] = PublicKey.findProgramAddressSync([Buffer.from("guardian")], program.programId);
+
+  const INDEX_PAGE_CAPACITY = 32;
+  const INDEX_KIND_REQUESTER = 0;
+  const INDEX_KIND_RECIPIENT = 1;
+  const INDEX_KIND_MINT = 2;
+
+  const indexPagePda = (kind: number, owner: PublicKey, page: number) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("index"), Buffer.from([kind]), owner.toBuffer(), new anchor.BN(page).toArrayLike(Buffer, "le", 4)],
+      program.programId
+    )[0];
+
+  // First index page with room for `owner`, opening the next page when all are full
+  const indexPageFor = async (kind: number, owner: PublicKey): Promise<PublicKey> => {
+    let previous: PublicKey | null = null;
+    for (let page = 0; ; page++) {
+      const pda = indexPagePda(kind, owner, page);
+      const info = await program.account.escrowIndex.fetchNullable(pda);
+      if (info === null) {
+        await program.methods
+          .openIndexPage(kind, owner, page)
+          .accountsPartial({ payer: requester.publicKey, previousPage: previous, indexPage: pda })
+          .signers([requester])
+          .rpc();
+        return pda;
+      }
+      if (info.count < INDEX_PAGE_CAPACITY) return pda;
+      previous = pda;
+    }
+  };
+
+  // remaining_accounts for initialize_escrow / close_escrow: requester, mint, then each recipient
+  const indexAccounts = async (owner: PublicKey, tokenMint: PublicKey, recipients: PublicKey[]) => {
+    const pages = [
+      await indexPageFor(INDEX_KIND_REQUESTER, owner),
+      await indexPageFor(INDEX_KIND_MINT, tokenMint),
+    ];
+    for (const r of recipients) pages.push(await indexPageFor(INDEX_KIND_RECIPIENT, r));
+    return pages.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }));
+  };
+
+  // Pages that currently list `escrow`, in the same order
+  const indexedPages = async (escrow: PublicKey, owners: [number, PublicKey][]) => {
+    const pages = [];
+    for (const [kind, owner] of owners) {
+      for (let page = 0; ; page++) {
+        const pda = indexPagePda(kind, owner, page);
+        const info = await program.account.escrowIndex.fetch(pda);
+        if (info.escrows.slice(0, info.count).some((k) => k.equals(escrow))) {
+          pages.push({ pubkey: pda, isSigner: false, isWritable: true });
+          break;
+        }
+      }
+    }
+    return pages;
+  };
 
   before(async () => {
     // Only fund the requester (who will pay for everything)
@@ -135,6 +196,15 @@ describe("Bounty Split - Test Suite", () => {
       unauthorizedUser.publicKey
     )).address;
 
+    // Protocol guardian: the provider wallet, which also holds the upgrade authority
+    if ((await provider.connection.getAccountInfo(guardianConfigPda)) === null) {
+      const [programData] = PublicKey.findProgramAddressSync([program.programId.toBuffer()], BPF_UPGRADEABLE_LOADER_ID);
+      await program.methods
+        .initializeGuardian(provider.wallet.publicKey)
+        .accountsPartial({ authority: provider.wallet.publicKey, programData })
+        .rpc();
+    }
+
     // Mint tokens to requester
     await mintTo(
       provider.connection,
@@ -186,9 +256,11 @@ describe("Bounty Split - Test Suite", () => {
 
           vault: vaultAta,
           requester: requester.publicKey,
+          payer: requester.publicKey,
           systemProgram: SystemProgram.programId,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         })
+        .remainingAccounts(await indexAccounts(requester.publicKey, params.tokenMint, params.recipients))
         .signers([requester])
         .rpc();
 
@@ -228,9 +300,11 @@ describe("Bounty Split - Test Suite", () => {
 
           vault: vaultAta,
           requester: requester.publicKey,
+          payer: requester.publicKey,
           systemProgram: SystemProgram.programId,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         })
+        .remainingAccounts(await indexAccounts(requester.publicKey, params.tokenMint, params.recipients))
         .signers([requester])
         .rpc();
 
@@ -258,9 +332,11 @@ describe("Bounty Split - Test Suite", () => {
 
           vault: vaultAta,
           requester: requester.publicKey,
+          payer: requester.publicKey,
           systemProgram: SystemProgram.programId,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         })
+        .remainingAccounts(await indexAccounts(requester.publicKey, params.tokenMint, params.recipients))
         .signers([requester])
         .rpc();
 
@@ -286,9 +362,11 @@ describe("Bounty Split - Test Suite", () => {
 
             vault: vaultAta,
             requester: requester.publicKey,
+            payer: requester.publicKey,
             systemProgram: SystemProgram.programId,
             rent: anchor.web3.SYSVAR_RENT_PUBKEY,
           })
+          .remainingAccounts(await indexAccounts(requester.publicKey, params.tokenMint, params.recipients))
           .signers([requester])
           .rpc();
         assert.fail("Should have thrown DuplicateRecipient error");
@@ -315,9 +393,11 @@ describe("Bounty Split - Test Suite", () => {
 
             vault: vaultAta,
             requester: requester.publicKey,
+            payer: requester.publicKey,
             systemProgram: SystemProgram.programId,
             rent: anchor.web3.SYSVAR_RENT_PUBKEY,
           })
+          .remainingAccounts(await indexAccounts(requester.publicKey, params.tokenMint, params.recipients))
           .signers([requester])
           .rpc();
         assert.fail("Should have thrown InvalidSplits error");
@@ -344,9 +424,11 @@ describe("Bounty Split - Test Suite", () => {
 
             vault: vaultAta,
             requester: requester.publicKey,
+            payer: requester.publicKey,
             systemProgram: SystemProgram.programId,
             rent: anchor.web3.SYSVAR_RENT_PUBKEY,
           })
+          .remainingAccounts(await indexAccounts(requester.publicKey, params.tokenMint, params.recipients))
           .signers([requester])
           .rpc();
         assert.fail("Should have thrown ZeroSplit error");
@@ -375,9 +457,11 @@ describe("Bounty Split - Test Suite", () => {
 
             vault: vaultAta,
             requester: requester.publicKey,
+            payer: requester.publicKey,
             systemProgram: SystemProgram.programId,
             rent: anchor.web3.SYSVAR_RENT_PUBKEY,
           })
+          .remainingAccounts(await indexAccounts(requester.publicKey, params.tokenMint, params.recipients))
           .signers([requester])
           .rpc();
         assert.fail("Should have thrown InvalidTimelock error");
@@ -404,9 +488,11 @@ describe("Bounty Split - Test Suite", () => {
 
             vault: vaultAta,
             requester: requester.publicKey,
+            payer: requester.publicKey,
             systemProgram: SystemProgram.programId,
             rent: anchor.web3.SYSVAR_RENT_PUBKEY,
           })
+          .remainingAccounts(await indexAccounts(requester.publicKey, params.tokenMint, params.recipients))
           .signers([requester])
           .rpc();
         assert.fail("Should have thrown InvalidRecipientCount error");
@@ -433,9 +519,11 @@ describe("Bounty Split - Test Suite", () => {
 
             vault: vaultAta,
             requester: requester.publicKey,
+            payer: requester.publicKey,
             systemProgram: SystemProgram.programId,
             rent: anchor.web3.SYSVAR_RENT_PUBKEY,
           })
+          .remainingAccounts(await indexAccounts(requester.publicKey, params.tokenMint, params.recipients))
           .signers([requester])
           .rpc();
         assert.fail("Should have thrown InvalidArbiter error");
@@ -462,9 +550,11 @@ describe("Bounty Split - Test Suite", () => {
 
             vault: vaultAta,
             requester: unauthorizedUser.publicKey, // Wrong signer
+            payer: unauthorizedUser.publicKey,
             systemProgram: SystemProgram.programId,
             rent: anchor.web3.SYSVAR_RENT_PUBKEY,
           })
+          .remainingAccounts(await indexAccounts(unauthorizedUser.publicKey, params.tokenMint, params.recipients))
           .signers([unauthorizedUser])
           .rpc();
         assert.fail("Should have failed with seed constraint error");
@@ -510,9 +600,11 @@ describe("Bounty Split - Test Suite", () => {
 
           vault: vaultAta,
           requester: requester.publicKey,
+          payer: requester.publicKey,
           systemProgram: SystemProgram.programId,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         })
+        .remainingAccounts(await indexAccounts(requester.publicKey, params.tokenMint, params.recipients))
         .signers([requester])
         .rpc();
     });
@@ -692,9 +784,11 @@ describe("Bounty Split - Test Suite", () => {
 
           vault: vaultAta,
           requester: requester.publicKey,
+          payer: requester.publicKey,
           systemProgram: SystemProgram.programId,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         })
+        .remainingAccounts(await indexAccounts(requester.publicKey, params.tokenMint, params.recipients))
         .signers([requester])
         .rpc();
 
@@ -804,9 +898,11 @@ describe("Bounty Split - Test Suite", () => {
 
           vault: vaultAta,
           requester: requester.publicKey,
+          payer: requester.publicKey,
           systemProgram: SystemProgram.programId,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         })
+        .remainingAccounts(await indexAccounts(requester.publicKey, params.tokenMint, params.recipients))
         .signers([requester])
         .rpc();
 
@@ -999,9 +1095,11 @@ describe("Bounty Split - Test Suite", () => {
 
           vault: vaultAta,
           requester: requester.publicKey,
+          payer: requester.publicKey,
           systemProgram: SystemProgram.programId,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         })
+        .remainingAccounts(await indexAccounts(requester.publicKey, params.tokenMint, params.recipients))
         .signers([requester])
         .rpc();
 
@@ -1051,7 +1149,7 @@ describe("Bounty Split - Test Suite", () => {
 
           vault: vaultAta,
           claimant: recipient1.publicKey,
-          claimantAta: recipient1Ata,
+          destination: recipient1.publicKey,
         })
         .signers([recipient1])
         .rpc();
@@ -1075,7 +1173,7 @@ describe("Bounty Split - Test Suite", () => {
 
           vault: vaultAta,
           claimant: recipient1.publicKey,
-          claimantAta: recipient1Ata,
+          destination: recipient1.publicKey,
         })
         .signers([recipient1])
         .rpc();
@@ -1091,7 +1189,7 @@ describe("Bounty Split - Test Suite", () => {
 
           vault: vaultAta,
           claimant: recipient2.publicKey,
-          claimantAta: recipient2Ata,
+          destination: recipient2.publicKey,
         })
         .signers([recipient2])
         .rpc();
@@ -1116,7 +1214,7 @@ describe("Bounty Split - Test Suite", () => {
 
           vault: vaultAta,
           claimant: recipient1.publicKey,
-          claimantAta: recipient1Ata,
+          destination: recipient1.publicKey,
         })
         .signers([recipient1])
         .rpc();
@@ -1130,7 +1228,7 @@ describe("Bounty Split - Test Suite", () => {
 
             vault: vaultAta,
             claimant: recipient1.publicKey,
-            claimantAta: recipient1Ata,
+            destination: recipient1.publicKey,
           })
           .signers([recipient1])
           .rpc();
@@ -1149,7 +1247,7 @@ describe("Bounty Split - Test Suite", () => {
 
             vault: vaultAta,
             claimant: unauthorizedUser.publicKey,
-            claimantAta: unauthorizedAta,
+            destination: unauthorizedUser.publicKey,
           })
           .signers([unauthorizedUser])
           .rpc();
@@ -1193,9 +1291,11 @@ describe("Bounty Split - Test Suite", () => {
           escrow: newEscrowPda,
           vault: newVaultAta,
           requester: requester.publicKey,
+          payer: requester.publicKey,
           systemProgram: SystemProgram.programId,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         })
+        .remainingAccounts(await indexAccounts(requester.publicKey, params.tokenMint, params.recipients))
         .signers([requester])
         .rpc();
 
@@ -1221,7 +1321,7 @@ describe("Bounty Split - Test Suite", () => {
             escrow: newEscrowPda,
             vault: newVaultAta,
             claimant: recipient1.publicKey,
-            claimantAta: recipient1Ata,
+            destination: recipient1.publicKey,
           })
           .signers([recipient1])
           .rpc();
@@ -1266,9 +1366,11 @@ describe("Bounty Split - Test Suite", () => {
 
           vault: vaultAta,
           requester: requester.publicKey,
+          payer: requester.publicKey,
           systemProgram: SystemProgram.programId,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         })
+        .remainingAccounts(await indexAccounts(requester.publicKey, params.tokenMint, params.recipients))
         .signers([requester])
         .rpc();
 
@@ -1315,7 +1417,7 @@ describe("Bounty Split - Test Suite", () => {
 
           vault: vaultAta,
           claimant: recipient1.publicKey,
-          claimantAta: recipient1Ata,
+          destination: recipient1.publicKey,
         })
         .signers([recipient1])
         .rpc();
@@ -1369,9 +1471,11 @@ describe("Bounty Split - Test Suite", () => {
 
           vault: vaultAta,
           requester: requester.publicKey,
+          payer: requester.publicKey,
           systemProgram: SystemProgram.programId,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         })
+        .remainingAccounts(await indexAccounts(requester.publicKey, params.tokenMint, params.recipients))
         .signers([requester])
         .rpc();
 
@@ -1413,9 +1517,11 @@ describe("Bounty Split - Test Suite", () => {
 
           vault: vaultAta,
           requester: requester.publicKey,
+          payer: requester.publicKey,
           systemProgram: SystemProgram.programId,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         })
+        .remainingAccounts(await indexAccounts(requester.publicKey, params.tokenMint, params.recipients))
         .signers([requester])
         .rpc();
 
@@ -1471,9 +1577,11 @@ describe("Bounty Split - Test Suite", () => {
 
           vault: vaultAta,
           requester: requester.publicKey,
+          payer: requester.publicKey,
           systemProgram: SystemProgram.programId,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         })
+        .remainingAccounts(await indexAccounts(requester.publicKey, params.tokenMint, params.recipients))
         .signers([requester])
         .rpc();
 
@@ -1541,7 +1649,7 @@ describe("Bounty Split - Test Suite", () => {
 
           vault: vaultAta,
           claimant: recipient1.publicKey,
-          claimantAta: recipient1Ata,
+          destination: recipient1.publicKey,
         })
         .signers([recipient1])
         .rpc();
@@ -1563,7 +1671,7 @@ describe("Bounty Split - Test Suite", () => {
 
           vault: vaultAta,
           claimant: recipient2.publicKey,
-          claimantAta: recipient2Ata,
+          destination: recipient2.publicKey,
         })
         .signers([recipient2])
         .rpc();
@@ -1585,4 +1693,1077 @@ describe("Bounty Split - Test Suite", () => {
       assert.equal(recipient1Received + recipient2Received, fundAmount);
     });
   });
+
+  describe("Escrow Index", () => {
+    let params;
+
+    beforeEach(async () => {
+      bountyId = Buffer.from(Array(32).fill(0));
+      const uniqueId = Date.now().toString(); bountyId.set(Buffer.from(uniqueId.slice(-20)));
+
+      [escrowPda, escrowBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow"), requester.publicKey.toBuffer(), bountyId],
+        program.programId
+      );
+
+      vaultAta = (await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        requester,
+        mint,
+        escrowPda,
+        true
+      )).address;
+
+      params = {
+        bountyId: Array.from(bountyId),
+        tokenMint: mint,
+        recipients: [recipient1.publicKey, recipient2.publicKey],
+        splits: [5000, 5000],
+        requiredConfirmations: 1,
+        arbiter: arbiter.publicKey,
+        timelockExpiry: new anchor.BN(0),
+      };
+
+      await program.methods
+        .initializeEscrow(params)
+        .accounts({
+          vault: vaultAta,
+          requester: requester.publicKey,
+          payer: requester.publicKey,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .remainingAccounts(await indexAccounts(requester.publicKey, params.tokenMint, params.recipients))
+        .signers([requester])
+        .rpc();
+    });
+
+    const escrowOwners = (): [number, PublicKey][] => [
+      [INDEX_KIND_REQUESTER, requester.publicKey],
+      [INDEX_KIND_MINT, mint],
+      [INDEX_KIND_RECIPIENT, recipient1.publicKey],
+      [INDEX_KIND_RECIPIENT, recipient2.publicKey],
+    ];
+
+    it("Should list a new escrow under its requester, mint and recipients", async () => {
+      const pages = await indexedPages(escrowPda, escrowOwners());
+      assert.equal(pages.length, 4);
+
+      const escrow = await program.account.escrow.fetch(escrowPda);
+      assert.equal(escrow.indexed, 1);
+    });
+
+    it("Should close a refunded escrow and remove it from every page", async () => {
+      await program.methods
+        .fundEscrow(new anchor.BN(1_000_000))
+        .accounts({
+          escrow: escrowPda,
+          payer: requester.publicKey,
+          payerTokenAccount: requesterAta,
+          mint: mint,
+          vault: vaultAta,
+        })
+        .signers([requester])
+        .rpc();
+
+      await program.methods
+        .refund()
+        .accounts({
+          escrow: escrowPda,
+          vault: vaultAta,
+          requesterAta: requesterAta,
+          requester: requester.publicKey,
+        })
+        .signers([requester])
+        .rpc();
+
+      const pages = await indexedPages(escrowPda, escrowOwners());
+      await program.methods
+        .closeEscrow()
+        .accounts({
+          escrow: escrowPda,
+          vault: vaultAta,
+          requesterAta: requesterAta,
+          requester: requester.publicKey,
+          usdTerms: null,
+        })
+        .remainingAccounts(pages)
+        .signers([requester])
+        .rpc();
+
+      assert.isNull(await provider.connection.getAccountInfo(escrowPda));
+      assert.isNull(await provider.connection.getAccountInfo(vaultAta));
+      for (const page of pages) {
+        const info = await program.account.escrowIndex.fetch(page.pubkey);
+        assert.isFalse(info.escrows.slice(0, info.count).some((k) => k.equals(escrowPda)));
+      }
+    });
+
+    it("Should fail to close an escrow that is not settled", async () => {
+      try {
+        await program.methods
+          .closeEscrow()
+          .accounts({
+            escrow: escrowPda,
+            vault: vaultAta,
+            requesterAta: requesterAta,
+            requester: requester.publicKey,
+            usdTerms: null,
+          })
+          .remainingAccounts(await indexedPages(escrowPda, escrowOwners()))
+          .signers([requester])
+          .rpc();
+        assert.fail("Should have thrown NotSettled error");
+      } catch (error) {
+        assert.include(error.toString(), "NotSettled");
+      }
+    });
+
+    it("Should fail to open a page before the previous one is full", async () => {
+      const owner = Keypair.generate().publicKey;
+      const page0 = await indexPageFor(INDEX_KIND_RECIPIENT, owner);
+
+      try {
+        await program.methods
+          .openIndexPage(INDEX_KIND_RECIPIENT, owner, 1)
+          .accountsPartial({
+            payer: requester.publicKey,
+            previousPage: page0,
+            indexPage: indexPagePda(INDEX_KIND_RECIPIENT, owner, 1),
+          })
+          .signers([requester])
+          .rpc();
+        assert.fail("Should have thrown InvalidIndexPage error");
+      } catch (error) {
+        assert.include(error.toString(), "InvalidIndexPage");
+      }
+    });
+  });
+
+  describe("Protocol Guardian", () => {
+    const setPause = (paused: boolean, flags: number) =>
+      program.methods.setPause(paused, flags).accounts({ guardian: provider.wallet.publicKey }).rpc();
+
+    beforeEach(async () => {
+      bountyId = Buffer.from(Array(32).fill(0));
+      const uniqueId = Date.now().toString(); bountyId.set(Buffer.from(uniqueId.slice(-20)));
+
+      [escrowPda, escrowBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow"), requester.publicKey.toBuffer(), bountyId],
+        program.programId
+      );
+
+      vaultAta = (await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        requester,
+        mint,
+        escrowPda,
+        true
+      )).address;
+    });
+
+    afterEach(async () => {
+      await setPause(false, 0);
+    });
+
+    const initializeParams = () => ({
+      bountyId: Array.from(bountyId),
+      tokenMint: mint,
+      recipients: [recipient1.publicKey],
+      splits: [10000],
+      requiredConfirmations: 1,
+      arbiter: arbiter.publicKey,
+      timelockExpiry: new anchor.BN(0),
+    });
+
+    const initializeEscrow = async () => {
+      const params = initializeParams();
+      await program.methods
+        .initializeEscrow(params)
+        .accounts({
+          vault: vaultAta,
+          requester: requester.publicKey,
+          payer: requester.publicKey,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .remainingAccounts(await indexAccounts(requester.publicKey, params.tokenMint, params.recipients))
+        .signers([requester])
+        .rpc();
+    };
+
+    const fundEscrow = (amount: number) =>
+      program.methods
+        .fundEscrow(new anchor.BN(amount))
+        .accounts({
+          escrow: escrowPda,
+          payer: requester.publicKey,
+          payerTokenAccount: requesterAta,
+          mint: mint,
+          vault: vaultAta,
+        })
+        .signers([requester])
+        .rpc();
+
+    it("Should block funding while paused but keep refunds available", async () => {
+      await initializeEscrow();
+      await fundEscrow(1_000_000);
+      await setPause(false, PAUSE_FUND);
+
+      try {
+        await fundEscrow(1_000_000);
+        assert.fail("Should have thrown ProtocolPaused error");
+      } catch (error) {
+        assert.include(error.toString(), "ProtocolPaused");
+      }
+
+      await program.methods
+        .refund()
+        .accounts({
+          escrow: escrowPda,
+          vault: vaultAta,
+          requesterAta: requesterAta,
+          requester: requester.publicKey,
+        })
+        .signers([requester])
+        .rpc();
+
+      const escrow = await program.account.escrow.fetch(escrowPda);
+      assert.equal(escrow.status, STATUS_REFUNDED);
+    });
+
+    it("Should block new escrows under the global pause", async () => {
+      await setPause(true, 0);
+
+      try {
+        await initializeEscrow();
+        assert.fail("Should have thrown ProtocolPaused error");
+      } catch (error) {
+        assert.include(error.toString(), "ProtocolPaused");
+      }
+
+      const guardian = await program.account.guardianConfig.fetch(guardianConfigPda);
+      assert.isTrue(guardian.paused);
+    });
+
+    it("Should fail when a non-guardian tries to pause", async () => {
+      try {
+        await program.methods
+          .setPause(true, 0)
+          .accounts({ guardian: unauthorizedUser.publicKey })
+          .signers([unauthorizedUser])
+          .rpc();
+        assert.fail("Should have thrown Unauthorized error");
+      } catch (error) {
+        assert.include(error.toString(), "Unauthorized");
+      }
+    });
+  });
+
+  describe("USD Target", () => {
+    // Loaded by the local validator from tests/fixtures (see Anchor.toml): $150 per token
+    const PRICE_UPDATE = new PublicKey("GGmWg8XhWHMYMVMEpESjJJbSWoHUE9WzJfEZLs1FMzTU");
+    const FEED_ID = Array.from(Buffer.from("ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d", "hex"));
+    const USD_PENDING = 1;
+    const USD_SETTLED = 2;
+
+    const setupReleasedEscrow = async (usdTarget: number, maxPriceAge: number, fundAmount: number) => {
+      bountyId = Buffer.from(Array(32).fill(0));
+      const uniqueId = Date.now().toString(); bountyId.set(Buffer.from(uniqueId.slice(-20)));
+
+      [escrowPda, escrowBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow"), requester.publicKey.toBuffer(), bountyId],
+        program.programId
+      );
+
+      vaultAta = (await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        requester,
+        mint,
+        escrowPda,
+        true
+      )).address;
+
+      const params = {
+        bountyId: Array.from(bountyId),
+        tokenMint: mint,
+        recipients: [recipient1.publicKey],
+        splits: [10000],
+        requiredConfirmations: 1,
+        arbiter: arbiter.publicKey,
+        timelockExpiry: new anchor.BN(0),
+      };
+
+      await program.methods
+        .initializeEscrow(params)
+        .accounts({
+          vault: vaultAta,
+          requester: requester.publicKey,
+          payer: requester.publicKey,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .remainingAccounts(await indexAccounts(requester.publicKey, params.tokenMint, params.recipients))
+        .signers([requester])
+        .rpc();
+
+      await program.methods
+        .setUsdTarget(new anchor.BN(usdTarget), FEED_ID, maxPriceAge)
+        .accounts({
+          escrow: escrowPda,
+          payer: requester.publicKey,
+          requester: requester.publicKey,
+        })
+        .signers([requester])
+        .rpc();
+
+      await program.methods
+        .fundEscrow(new anchor.BN(fundAmount))
+        .accounts({
+          escrow: escrowPda,
+          payer: requester.publicKey,
+          payerTokenAccount: requesterAta,
+          mint: mint,
+          vault: vaultAta,
+        })
+        .signers([requester])
+        .rpc();
+
+      await program.methods
+        .release()
+        .accounts({ escrow: escrowPda, requester: requester.publicKey })
+        .signers([requester])
+        .rpc();
+    };
+
+    const settle = (settler: Keypair = recipient1) =>
+      program.methods
+        .settleUsdTarget()
+        .accounts({
+          escrow: escrowPda,
+          priceUpdate: PRICE_UPDATE,
+          mint: mint,
+          vault: vaultAta,
+          requesterAta: requesterAta,
+          settler: settler.publicKey,
+        })
+        .signers([settler])
+        .rpc();
+
+    const closeEscrow = async (usdTerms: PublicKey | null) =>
+      program.methods
+        .closeEscrow()
+        .accounts({
+          escrow: escrowPda,
+          vault: vaultAta,
+          requesterAta: requesterAta,
+          requester: requester.publicKey,
+          usdTerms,
+        })
+        .remainingAccounts(
+          await indexedPages(escrowPda, [
+            [INDEX_KIND_REQUESTER, requester.publicKey],
+            [INDEX_KIND_MINT, mint],
+            [INDEX_KIND_RECIPIENT, recipient1.publicKey],
+          ])
+        )
+        .signers([requester])
+        .rpc();
+
+    it("Should convert the USD target at settlement and return the excess", async () => {
+      // $300 at $150 per token = 2 tokens owed, 3 tokens funded
+      await setupReleasedEscrow(300_000_000, 4_000_000_000, 3_000_000);
+
+      let escrow = await program.account.escrow.fetch(escrowPda);
+      assert.equal(escrow.usdState, USD_PENDING);
+
+      try {
+        await program.methods
+          .claim()
+          .accounts({
+            escrow: escrowPda,
+            vault: vaultAta,
+            claimant: recipient1.publicKey,
+            destination: recipient1.publicKey,
+          })
+          .signers([recipient1])
+          .rpc();
+        assert.fail("Should have thrown UsdNotSettled error");
+      } catch (error) {
+        assert.include(error.toString(), "UsdNotSettled");
+      }
+
+      const requesterBefore = await getAccount(provider.connection, requesterAta);
+      await settle();
+      const requesterAfter = await getAccount(provider.connection, requesterAta);
+      assert.equal(Number(requesterAfter.amount) - Number(requesterBefore.amount), 1_000_000);
+
+      escrow = await program.account.escrow.fetch(escrowPda);
+      assert.equal(escrow.usdState, USD_SETTLED);
+      assert.equal(escrow.totalAmount.toNumber(), 2_000_000);
+
+      const recipientBefore = await getAccount(provider.connection, recipient1Ata);
+      await program.methods
+        .claim()
+        .accounts({
+          escrow: escrowPda,
+          vault: vaultAta,
+          claimant: recipient1.publicKey,
+          destination: recipient1.publicKey,
+        })
+        .signers([recipient1])
+        .rpc();
+      const recipientAfter = await getAccount(provider.connection, recipient1Ata);
+      assert.equal(Number(recipientAfter.amount) - Number(recipientBefore.amount), 2_000_000);
+    });
+
+    it("Should only let a party to the escrow settle", async () => {
+      await setupReleasedEscrow(300_000_000, 4_000_000_000, 3_000_000);
+
+      try {
+        await settle(unauthorizedUser);
+        assert.fail("Should have thrown Unauthorized error");
+      } catch (error) {
+        assert.include(error.toString(), "Unauthorized");
+      }
+
+      await settle(arbiter);
+      const escrow = await program.account.escrow.fetch(escrowPda);
+      assert.equal(escrow.usdState, USD_SETTLED);
+    });
+
+    it("Should reclaim the USD terms when the escrow is closed", async () => {
+      await setupReleasedEscrow(300_000_000, 4_000_000_000, 3_000_000);
+      const [usdTerms] = PublicKey.findProgramAddressSync([Buffer.from("usd_terms"), escrowPda.toBuffer()], program.programId);
+      await settle(requester);
+      await program.methods
+        .claim()
+        .accounts({
+          escrow: escrowPda,
+          vault: vaultAta,
+          claimant: recipient1.publicKey,
+          destination: recipient1.publicKey,
+        })
+        .signers([recipient1])
+        .rpc();
+
+      try {
+        await closeEscrow(null);
+        assert.fail("Should have thrown MissingUsdTerms error");
+      } catch (error) {
+        assert.include(error.toString(), "MissingUsdTerms");
+      }
+
+      await closeEscrow(usdTerms);
+      assert.isNull(await provider.connection.getAccountInfo(escrowPda));
+      assert.isNull(await provider.connection.getAccountInfo(usdTerms));
+    });
+
+    it("Should reject a stale price", async () => {
+      await setupReleasedEscrow(300_000_000, 60, 3_000_000);
+
+      try {
+        await settle();
+        assert.fail("Should have thrown StalePrice error");
+      } catch (error) {
+        assert.include(error.toString(), "StalePrice");
+      }
+    });
+  });
+
+  describe("Events", () => {
+    const eventParser = new anchor.EventParser(program.programId, program.coder);
+
+    // Decoded events of a confirmed transaction, in emission order
+    const eventsOf = async (signature: string) => {
+      await provider.connection.confirmTransaction(signature, "confirmed");
+      const tx = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      return [...eventParser.parseLogs(tx.meta.logMessages)];
+    };
+
+    beforeEach(async () => {
+      bountyId = Buffer.from(Array(32).fill(0));
+      const uniqueId = Date.now().toString(); bountyId.set(Buffer.from(uniqueId.slice(-20)));
+
+      [escrowPda, escrowBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow"), requester.publicKey.toBuffer(), bountyId],
+        program.programId
+      );
+
+      vaultAta = (await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        requester,
+        mint,
+        escrowPda,
+        true
+      )).address;
+
+      const params = {
+        bountyId: Array.from(bountyId),
+        tokenMint: mint,
+        recipients: [recipient1.publicKey, recipient2.publicKey],
+        splits: [6000, 4000],
+        requiredConfirmations: 1,
+        arbiter: arbiter.publicKey,
+        timelockExpiry: new anchor.BN(0),
+      };
+
+      await program.methods
+        .initializeEscrow(params)
+        .accounts({
+          vault: vaultAta,
+          requester: requester.publicKey,
+          payer: requester.publicKey,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .remainingAccounts(await indexAccounts(requester.publicKey, params.tokenMint, params.recipients))
+        .signers([requester])
+        .rpc();
+
+      await program.methods
+        .fundEscrow(new anchor.BN(1_000_000))
+        .accounts({
+          escrow: escrowPda,
+          payer: requester.publicKey,
+          payerTokenAccount: requesterAta,
+          mint: mint,
+          vault: vaultAta,
+        })
+        .signers([requester])
+        .rpc();
+    });
+
+    it("Should emit chained status changes through confirmation and claim", async () => {
+      const proposed = await eventsOf(await program.methods
+        .proposeRelease()
+        .accounts({ escrow: escrowPda, proposer: recipient1.publicKey })
+        .signers([recipient1])
+        .rpc());
+      assert.deepEqual(proposed.map((e) => e.name), ["releaseProposed"]);
+      assert.equal(proposed[0].data.statusBefore, STATUS_FUNDED);
+      assert.equal(proposed[0].data.statusAfter, STATUS_PENDING);
+
+      const confirmed = await eventsOf(await program.methods
+        .confirmRelease()
+        .accounts({ escrow: escrowPda, recipient: recipient2.publicKey })
+        .signers([recipient2])
+        .rpc());
+      assert.deepEqual(confirmed.map((e) => e.name), ["releaseConfirmed", "escrowReleased"]);
+      assert.equal(confirmed[0].data.index, 1);
+      assert.equal(confirmed[0].data.statusAfter, STATUS_PENDING);
+      assert.equal(confirmed[1].data.statusBefore, STATUS_PENDING);
+      assert.equal(confirmed[1].data.statusAfter, STATUS_RELEASED);
+      assert.equal(confirmed[1].data.totalAmount.toNumber(), 1_000_000);
+      assert.isAbove(confirmed[1].data.slot.toNumber(), 0);
+      assert.isAbove(confirmed[1].data.timestamp.toNumber(), 0);
+
+      const claimed = await eventsOf(await program.methods
+        .claim()
+        .accounts({
+          escrow: escrowPda,
+          vault: vaultAta,
+          claimant: recipient2.publicKey,
+          destination: recipient2.publicKey,
+        })
+        .signers([recipient2])
+        .rpc());
+      assert.deepEqual(claimed.map((e) => e.name), ["recipientClaimed"]);
+      assert.equal(claimed[0].data.recipient.toBase58(), recipient2.publicKey.toBase58());
+      assert.equal(claimed[0].data.index, 1);
+      assert.equal(claimed[0].data.amount.toNumber(), 400_000);
+      assert.equal(claimed[0].data.claimed, 0b10);
+    });
+
+    it("Should emit DisputeResolved with the ruling", async () => {
+      await program.methods
+        .raiseDispute(Array(32).fill(1))
+        .accounts({ escrow: escrowPda, initiator: recipient1.publicKey })
+        .signers([recipient1])
+        .rpc();
+
+      const resolved = await eventsOf(await program.methods
+        .resolveDispute()
+        .accounts({ escrow: escrowPda, arbiter: arbiter.publicKey })
+        .signers([arbiter])
+        .rpc());
+      assert.deepEqual(resolved.map((e) => e.name), ["disputeResolved"]);
+      assert.equal(resolved[0].data.arbiter.toBase58(), arbiter.publicKey.toBase58());
+      assert.equal(resolved[0].data.ruling, 0); // RULING_RELEASE
+      assert.equal(resolved[0].data.statusBefore, STATUS_DISPUTED);
+      assert.equal(resolved[0].data.statusAfter, STATUS_RELEASED);
+    });
+  });
+
+  describe("Escrow Migration", () => {
+    // Escrow written with the pre-versioning layout (tests/fixtures/legacy_escrow.json)
+    const LEGACY_ESCROW = new PublicKey("HiNNuoFbdR1LiJA5zxnr9UsKA5fdpkfDssBYcBdRMhb8");
+    const LEGACY_ESCROW_SPACE = 504;
+    const ESCROW_SPACE = 553;
+    const ESCROW_VERSION = 3;
+
+    const migrate = (escrow: PublicKey) =>
+      program.methods
+        .migrateEscrow()
+        .accounts({ escrow, payer: provider.wallet.publicKey })
+        .rpc();
+
+    it("Should read a legacy escrow as version 0", async () => {
+      const info = await provider.connection.getAccountInfo(LEGACY_ESCROW);
+      assert.equal(info.data.length, LEGACY_ESCROW_SPACE);
+
+      const escrow = await program.account.escrow.fetch(LEGACY_ESCROW);
+      assert.equal(escrow.version, 0);
+      assert.equal(escrow.status, STATUS_FUNDED);
+      assert.equal(escrow.totalAmount.toNumber(), 1_000_000);
+      assert.equal(escrow.indexed, 0);
+    });
+
+    it("Should migrate a legacy escrow in place", async () => {
+      const before = await program.account.escrow.fetch(LEGACY_ESCROW);
+      await migrate(LEGACY_ESCROW);
+
+      const info = await provider.connection.getAccountInfo(LEGACY_ESCROW);
+      assert.equal(info.data.length, ESCROW_SPACE);
+      assert.isAtLeast(info.lamports, await provider.connection.getMinimumBalanceForRentExemption(ESCROW_SPACE));
+
+      const after = await program.account.escrow.fetch(LEGACY_ESCROW);
+      assert.equal(after.version, ESCROW_VERSION);
+      assert.equal(after.requester.toBase58(), before.requester.toBase58());
+      assert.equal(after.recipientCount, before.recipientCount);
+      assert.deepEqual(after.recipients.map((k) => k.toBase58()), before.recipients.map((k) => k.toBase58()));
+      assert.deepEqual(after.splits, before.splits);
+      assert.equal(after.totalAmount.toNumber(), before.totalAmount.toNumber());
+      assert.equal(after.status, before.status);
+      assert.equal(after.createdAt.toNumber(), before.createdAt.toNumber());
+    });
+
+    it("Should create new escrows at the current version and refuse to migrate them", async () => {
+      bountyId = Buffer.from(Array(32).fill(0));
+      const uniqueId = Date.now().toString(); bountyId.set(Buffer.from(uniqueId.slice(-20)));
+
+      [escrowPda, escrowBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow"), requester.publicKey.toBuffer(), bountyId],
+        program.programId
+      );
+
+      vaultAta = (await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        requester,
+        mint,
+        escrowPda,
+        true
+      )).address;
+
+      const params = {
+        bountyId: Array.from(bountyId),
+        tokenMint: mint,
+        recipients: [recipient1.publicKey],
+        splits: [10000],
+        requiredConfirmations: 1,
+        arbiter: arbiter.publicKey,
+        timelockExpiry: new anchor.BN(0),
+      };
+
+      await program.methods
+        .initializeEscrow(params)
+        .accounts({
+          vault: vaultAta,
+          requester: requester.publicKey,
+          payer: requester.publicKey,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .remainingAccounts(await indexAccounts(requester.publicKey, params.tokenMint, params.recipients))
+        .signers([requester])
+        .rpc();
+
+      const info = await provider.connection.getAccountInfo(escrowPda);
+      assert.equal(info.data.length, ESCROW_SPACE);
+      const escrow = await program.account.escrow.fetch(escrowPda);
+      assert.equal(escrow.version, ESCROW_VERSION);
+
+      try {
+        await migrate(escrowPda);
+        assert.fail("Should have thrown AlreadyMigrated error");
+      } catch (error) {
+        assert.include(error.toString(), "AlreadyMigrated");
+      }
+    });
+  });
+
+  describe("Signed Approvals", () => {
+    const APPROVAL_ACTION_CONFIRM_RELEASE = 0;
+
+    let createdAt: anchor.BN;
+
+    // "bounty-split/approval" | escrow | created_at i64 le | nonce u64 le | action
+    const approvalMessage = (escrow: PublicKey, created: anchor.BN, nonce: number, action = APPROVAL_ACTION_CONFIRM_RELEASE) =>
+      Buffer.concat([
+        Buffer.from("bounty-split/approval"),
+        escrow.toBuffer(),
+        created.toTwos(64).toArrayLike(Buffer, "le", 8),
+        new anchor.BN(nonce).toArrayLike(Buffer, "le", 8),
+        Buffer.from([action]),
+      ]);
+
+    const approve = (signer: Keypair, nonce: number, created = createdAt) =>
+      Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: signer.secretKey,
+        message: approvalMessage(escrowPda, created, nonce),
+      });
+
+    // Submitted by the provider wallet; recipients never pay fees
+    const relay = (approvals: anchor.web3.TransactionInstruction[]) =>
+      program.methods
+        .confirmReleaseSigned()
+        .accounts({ escrow: escrowPda })
+        .preInstructions(approvals)
+        .rpc();
+
+    beforeEach(async () => {
+      bountyId = Buffer.from(Array(32).fill(0));
+      const uniqueId = Date.now().toString(); bountyId.set(Buffer.from(uniqueId.slice(-20)));
+
+      [escrowPda, escrowBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow"), requester.publicKey.toBuffer(), bountyId],
+        program.programId
+      );
+
+      vaultAta = (await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        requester,
+        mint,
+        escrowPda,
+        true
+      )).address;
+
+      const params = {
+        bountyId: Array.from(bountyId),
+        tokenMint: mint,
+        recipients: [recipient1.publicKey, recipient2.publicKey, recipient3.publicKey],
+        splits: [5000, 3000, 2000],
+        requiredConfirmations: 3,
+        arbiter: arbiter.publicKey,
+        timelockExpiry: new anchor.BN(0),
+      };
+
+      await program.methods
+        .initializeEscrow(params)
+        .accounts({
+          vault: vaultAta,
+          requester: requester.publicKey,
+          payer: requester.publicKey,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .remainingAccounts(await indexAccounts(requester.publicKey, params.tokenMint, params.recipients))
+        .signers([requester])
+        .rpc();
+
+      await program.methods
+        .fundEscrow(new anchor.BN(1_000_000))
+        .accounts({
+          escrow: escrowPda,
+          payer: requester.publicKey,
+          payerTokenAccount: requesterAta,
+          mint: mint,
+          vault: vaultAta,
+        })
+        .signers([requester])
+        .rpc();
+
+      await program.methods
+        .proposeRelease()
+        .accounts({ escrow: escrowPda, proposer: requester.publicKey })
+        .signers([requester])
+        .rpc();
+
+      createdAt = (await program.account.escrow.fetch(escrowPda)).createdAt;
+    });
+
+    it("Should release with every approval relayed in one transaction", async () => {
+      const balanceBefore = await provider.connection.getBalance(recipient1.publicKey);
+      await relay([approve(recipient1, 0), approve(recipient2, 0), approve(recipient3, 0)]);
+
+      const escrow = await program.account.escrow.fetch(escrowPda);
+      assert.equal(escrow.status, STATUS_RELEASED);
+      assert.equal(escrow.confirmations, 0b111);
+      assert.equal(escrow.approvalNonce.toNumber(), 1);
+      assert.equal(await provider.connection.getBalance(recipient1.publicKey), balanceBefore);
+    });
+
+    it("Should ignore Ed25519 verifications that read another instruction's data", async () => {
+      // Another protocol's signature, verified against the message of the instruction at index 0
+      const foreign = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: unauthorizedUser.secretKey,
+        message: Buffer.from("another protocol"),
+      });
+      foreign.data.writeUInt16LE(0, 14); // message_instruction_index
+      await relay([foreign, approve(recipient1, 0), approve(recipient2, 0), approve(recipient3, 0)]);
+
+      const escrow = await program.account.escrow.fetch(escrowPda);
+      assert.equal(escrow.status, STATUS_RELEASED);
+      assert.equal(escrow.confirmations, 0b111);
+    });
+
+    it("Should reject a replayed approval without invalidating unsent ones", async () => {
+      await relay([approve(recipient1, 0)]);
+      let escrow = await program.account.escrow.fetch(escrowPda);
+      assert.equal(escrow.status, STATUS_PENDING);
+      assert.equal(escrow.confirmations, 0b001);
+      assert.equal(escrow.approvalNonce.toNumber(), 0);
+
+      try {
+        // Extra instruction keeps the replay from being deduplicated as the same transaction
+        await relay([ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }), approve(recipient1, 0)]);
+        assert.fail("Should have thrown AlreadyConfirmed error");
+      } catch (error) {
+        assert.include(error.toString(), "AlreadyConfirmed");
+      }
+
+      // Signed at the same nonce as the first batch
+      await relay([approve(recipient2, 0), approve(recipient3, 0)]);
+      escrow = await program.account.escrow.fetch(escrowPda);
+      assert.equal(escrow.status, STATUS_RELEASED);
+      assert.equal(escrow.approvalNonce.toNumber(), 1);
+    });
+
+    it("Should ignore approvals signed for another escrow instance", async () => {
+      try {
+        await relay([approve(recipient1, 0, createdAt.subn(1))]);
+        assert.fail("Should have thrown NoApprovals error");
+      } catch (error) {
+        assert.include(error.toString(), "NoApprovals");
+      }
+    });
+
+    it("Should reject an approval signed by a non-recipient", async () => {
+      try {
+        await relay([approve(unauthorizedUser, 0)]);
+        assert.fail("Should have thrown RecipientNotFound error");
+      } catch (error) {
+        assert.include(error.toString(), "RecipientNotFound");
+      }
+    });
+
+    it("Should fail without any approval for the escrow", async () => {
+      try {
+        await relay([]);
+        assert.fail("Should have thrown NoApprovals error");
+      } catch (error) {
+        assert.include(error.toString(), "NoApprovals");
+      }
+    });
+  });
+
+  describe("Payout Destinations", () => {
+    const [globalPayoutPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("payout"), recipient1.publicKey.toBuffer()],
+      program.programId
+    );
+
+    beforeEach(async () => {
+      bountyId = Buffer.from(Array(32).fill(0));
+      const uniqueId = Date.now().toString(); bountyId.set(Buffer.from(uniqueId.slice(-20)));
+
+      [escrowPda, escrowBump] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow"), requester.publicKey.toBuffer(), bountyId],
+        program.programId
+      );
+
+      vaultAta = (await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        requester,
+        mint,
+        escrowPda,
+        true
+      )).address;
+
+      const params = {
+        bountyId: Array.from(bountyId),
+        tokenMint: mint,
+        recipients: [recipient1.publicKey, recipient2.publicKey],
+        splits: [7000, 3000],
+        requiredConfirmations: 1,
+        arbiter: arbiter.publicKey,
+        timelockExpiry: new anchor.BN(0),
+      };
+
+      await program.methods
+        .initializeEscrow(params)
+        .accounts({
+          vault: vaultAta,
+          requester: requester.publicKey,
+          payer: requester.publicKey,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .remainingAccounts(await indexAccounts(requester.publicKey, params.tokenMint, params.recipients))
+        .signers([requester])
+        .rpc();
+
+      await program.methods
+        .fundEscrow(new anchor.BN(1_000_000))
+        .accounts({
+          escrow: escrowPda,
+          payer: requester.publicKey,
+          payerTokenAccount: requesterAta,
+          mint: mint,
+          vault: vaultAta,
+        })
+        .signers([requester])
+        .rpc();
+
+      await program.methods
+        .release()
+        .accounts({ escrow: escrowPda, requester: requester.publicKey })
+        .signers([requester])
+        .rpc();
+    });
+
+    afterEach(async () => {
+      if ((await provider.connection.getAccountInfo(globalPayoutPda)) !== null) {
+        await program.methods
+          .clearPayoutDestination()
+          .accounts({ payoutDestination: globalPayoutPda, recipient: recipient1.publicKey })
+          .signers([recipient1])
+          .rpc();
+      }
+    });
+
+    const setGlobalDestination = (destination: PublicKey) =>
+      program.methods
+        .setPayoutDestination(destination)
+        .accounts({ recipient: recipient1.publicKey, payer: provider.wallet.publicKey })
+        .signers([recipient1])
+        .rpc();
+
+    it("Should pay the global destination and create its token account", async () => {
+      const treasury = Keypair.generate();
+      await setGlobalDestination(treasury.publicKey);
+
+      await program.methods
+        .claim()
+        .accounts({
+          escrow: escrowPda,
+          vault: vaultAta,
+          claimant: recipient1.publicKey,
+          destination: treasury.publicKey,
+        })
+        .signers([recipient1])
+        .rpc();
+
+      const treasuryAta = anchor.utils.token.associatedAddress({ mint, owner: treasury.publicKey });
+      const account = await getAccount(provider.connection, treasuryAta);
+      assert.equal(Number(account.amount), 700_000);
+    });
+
+    it("Should let anyone claim on behalf into the per-escrow destination", async () => {
+      const globalDestination = Keypair.generate().publicKey;
+      const [vaultOwner] = PublicKey.findProgramAddressSync([Buffer.from("some-vault")], program.programId);
+      await setGlobalDestination(globalDestination);
+      await program.methods
+        .setEscrowPayoutDestination(vaultOwner)
+        .accounts({ escrow: escrowPda, recipient: recipient1.publicKey, payer: provider.wallet.publicKey })
+        .signers([recipient1])
+        .rpc();
+
+      await program.methods
+        .claimOnBehalf()
+        .accounts({
+          escrow: escrowPda,
+          vault: vaultAta,
+          recipient: recipient1.publicKey,
+          destination: vaultOwner,
+          payer: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      // PDA owners get an off-curve associated token account
+      const account = await getAccount(
+        provider.connection,
+        anchor.utils.token.associatedAddress({ mint, owner: vaultOwner })
+      );
+      assert.equal(Number(account.amount), 700_000);
+      const escrow = await program.account.escrow.fetch(escrowPda);
+      assert.equal(escrow.claimed, 0b01);
+    });
+
+    it("Should close per-escrow destinations with the escrow", async () => {
+      const [escrowPayoutPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("payout"), recipient1.publicKey.toBuffer(), escrowPda.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .setEscrowPayoutDestination(recipient1.publicKey)
+        .accounts({ escrow: escrowPda, recipient: recipient1.publicKey, payer: provider.wallet.publicKey })
+        .signers([recipient1])
+        .rpc();
+
+      for (const recipient of [recipient1, recipient2]) {
+        await program.methods
+          .claimOnBehalf()
+          .accounts({
+            escrow: escrowPda,
+            vault: vaultAta,
+            recipient: recipient.publicKey,
+            destination: recipient.publicKey,
+            payer: provider.wallet.publicKey,
+          })
+          .rpc();
+      }
+
+      const pages = await indexedPages(escrowPda, [
+        [INDEX_KIND_REQUESTER, requester.publicKey],
+        [INDEX_KIND_MINT, mint],
+        [INDEX_KIND_RECIPIENT, recipient1.publicKey],
+        [INDEX_KIND_RECIPIENT, recipient2.publicKey],
+      ]);
+      const rentBefore = await provider.connection.getBalance(recipient1.publicKey);
+      await program.methods
+        .closeEscrow()
+        .accounts({
+          escrow: escrowPda,
+          vault: vaultAta,
+          requesterAta: requesterAta,
+          requester: requester.publicKey,
+          usdTerms: null,
+        })
+        .remainingAccounts([
+          ...pages,
+          { pubkey: escrowPayoutPda, isSigner: false, isWritable: true },
+          { pubkey: recipient1.publicKey, isSigner: false, isWritable: true },
+        ])
+        .signers([requester])
+        .rpc();
+
+      assert.isNull(await provider.connection.getAccountInfo(escrowPda));
+      assert.isNull(await provider.connection.getAccountInfo(escrowPayoutPda));
+      assert.isAbove(await provider.connection.getBalance(recipient1.publicKey), rentBefore);
+    });
+
+    it("Should fail to claim anywhere but the registered destination", async () => {
+      await setGlobalDestination(Keypair.generate().publicKey);
+
+      try {
+        await program.methods
+          .claimOnBehalf()
+          .accounts({
+            escrow: escrowPda,
+            vault: vaultAta,
+            recipient: recipient1.publicKey,
+            destination: unauthorizedUser.publicKey,
+            payer: provider.wallet.publicKey,
+          })
+          .rpc();
+        assert.fail("Should have thrown InvalidDestination error");
+      } catch (error) {
+        assert.include(error.toString(), "InvalidDestination");
+      }
+    });
+  });
 });
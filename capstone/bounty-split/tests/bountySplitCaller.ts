@@ -0,0 +1,275 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { BountySplit } from "../target/types/bounty_split";
+import { BountySplitCaller } from "../target/types/bounty_split_caller";
+import { Keypair, SystemProgram, PublicKey } from "@solana/web3.js";
+import {
+  createMint,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+  getAccount,
+} from "@solana/spl-token";
+import { assert } from "chai";
+
+describe("Bounty Split - CPI Caller", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.BountySplit as Program<BountySplit>;
+  const caller = anchor.workspace.BountySplitCaller as Program<BountySplitCaller>;
+
+  // The provider wallet plays the governance executor that owns the DAO
+  const authority = (provider.wallet as anchor.Wallet).payer;
+  const outsider = Keypair.generate();
+  const recipient1 = Keypair.generate();
+  const recipient2 = Keypair.generate();
+
+  const STATUS_FUNDED = 1;
+  const STATUS_RELEASED = 3;
+  const BPF_UPGRADEABLE_LOADER_ID = new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111");
+  const [guardianConfig] = PublicKey.findProgramAddressSync([Buffer.from("guardian")], program.programId);
+
+  let mint: PublicKey;
+  let daoPda: PublicKey;
+  let treasuryPda: PublicKey;
+  let councilPda: PublicKey;
+  let treasuryAta: PublicKey;
+  let recipient1Ata: PublicKey;
+
+  // First bounty-split index page with room for `owner` (see bountySplit.ts)
+  const indexPageFor = async (kind: number, owner: PublicKey): Promise<PublicKey> => {
+    let previous: PublicKey | null = null;
+    for (let page = 0; ; page++) {
+      const [pda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("index"), Buffer.from([kind]), owner.toBuffer(), new anchor.BN(page).toArrayLike(Buffer, "le", 4)],
+        program.programId
+      );
+      const info = await program.account.escrowIndex.fetchNullable(pda);
+      if (info === null) {
+        await program.methods
+          .openIndexPage(kind, owner, page)
+          .accountsPartial({ payer: authority.publicKey, previousPage: previous, indexPage: pda })
+          .rpc();
+        return pda;
+      }
+      if (info.count < 32) return pda;
+      previous = pda;
+    }
+  };
+
+  const indexAccounts = async (recipients: PublicKey[]) => {
+    const pages = [await indexPageFor(0, treasuryPda), await indexPageFor(2, mint)];
+    for (const r of recipients) pages.push(await indexPageFor(1, r));
+    return pages.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }));
+  };
+
+  const newBounty = async (splits: number[]) => {
+    const bountyId = Buffer.from(Array(32).fill(0));
+    bountyId.set(Buffer.from(Date.now().toString().slice(-20)));
+
+    const [escrowPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), treasuryPda.toBuffer(), bountyId],
+      program.programId
+    );
+    const vaultAta = (await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      authority,
+      mint,
+      escrowPda,
+      true
+    )).address;
+
+    const params = {
+      bountyId: Array.from(bountyId),
+      tokenMint: mint,
+      recipients: [recipient1.publicKey, recipient2.publicKey],
+      splits,
+      requiredConfirmations: 2,
+      arbiter: PublicKey.default, // overwritten with the council PDA by the caller
+      timelockExpiry: new anchor.BN(0),
+    };
+
+    await caller.methods
+      .createBounty(params)
+      .accounts({
+        authority: authority.publicKey,
+        escrow: escrowPda,
+        vault: vaultAta,
+        guardianConfig,
+      })
+      .remainingAccounts(await indexAccounts(params.recipients))
+      .rpc();
+
+    return { escrowPda, vaultAta };
+  };
+
+  const fundBounty = async (escrowPda: PublicKey, vaultAta: PublicKey, amount: number) => {
+    await caller.methods
+      .fundBounty(new anchor.BN(amount))
+      .accounts({
+        authority: authority.publicKey,
+        treasuryTokenAccount: treasuryAta,
+        mint,
+        escrow: escrowPda,
+        vault: vaultAta,
+        guardianConfig,
+      })
+      .rpc();
+  };
+
+  before(async () => {
+    const transferTx = new anchor.web3.Transaction().add(
+      SystemProgram.transfer({
+        fromPubkey: authority.publicKey,
+        toPubkey: recipient1.publicKey,
+        lamports: anchor.web3.LAMPORTS_PER_SOL / 10,
+      }),
+      SystemProgram.transfer({
+        fromPubkey: authority.publicKey,
+        toPubkey: outsider.publicKey,
+        lamports: anchor.web3.LAMPORTS_PER_SOL / 10,
+      })
+    );
+    await provider.sendAndConfirm(transferTx, [authority]);
+
+    [daoPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("dao"), authority.publicKey.toBuffer()],
+      caller.programId
+    );
+    [treasuryPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("treasury"), daoPda.toBuffer()],
+      caller.programId
+    );
+    [councilPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("council"), daoPda.toBuffer()],
+      caller.programId
+    );
+
+    if ((await provider.connection.getAccountInfo(guardianConfig)) === null) {
+      const [programData] = PublicKey.findProgramAddressSync([program.programId.toBuffer()], BPF_UPGRADEABLE_LOADER_ID);
+      await program.methods
+        .initializeGuardian(authority.publicKey)
+        .accountsPartial({ authority: authority.publicKey, programData })
+        .rpc();
+    }
+
+    if ((await provider.connection.getAccountInfo(daoPda)) === null) {
+      await caller.methods.initDao().accounts({ authority: authority.publicKey }).rpc();
+    }
+
+    mint = await createMint(provider.connection, authority, authority.publicKey, null, 6);
+    treasuryAta = (await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      authority,
+      mint,
+      treasuryPda,
+      true
+    )).address;
+    recipient1Ata = (await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      authority,
+      mint,
+      recipient1.publicKey
+    )).address;
+    await mintTo(provider.connection, authority, mint, treasuryAta, authority, 10_000_000);
+  });
+
+  it("Should create an escrow with PDA requester and arbiter", async () => {
+    const { escrowPda } = await newBounty([6000, 4000]);
+
+    const escrow = await program.account.escrow.fetch(escrowPda);
+    assert.equal(escrow.requester.toBase58(), treasuryPda.toBase58());
+    assert.equal(escrow.arbiter.toBase58(), councilPda.toBase58());
+    assert.equal(escrow.recipientCount, 2);
+  });
+
+  it("Should fund an escrow from the treasury PDA", async () => {
+    const { escrowPda, vaultAta } = await newBounty([6000, 4000]);
+    await fundBounty(escrowPda, vaultAta, 1_000_000);
+
+    const escrow = await program.account.escrow.fetch(escrowPda);
+    assert.equal(escrow.status, STATUS_FUNDED);
+    assert.equal(escrow.totalAmount.toNumber(), 1_000_000);
+    const vault = await getAccount(provider.connection, vaultAta);
+    assert.equal(Number(vault.amount), 1_000_000);
+  });
+
+  it("Should release an escrow as the treasury PDA", async () => {
+    const { escrowPda, vaultAta } = await newBounty([6000, 4000]);
+    await fundBounty(escrowPda, vaultAta, 1_000_000);
+
+    await caller.methods
+      .releaseBounty()
+      .accounts({ authority: authority.publicKey, escrow: escrowPda, guardianConfig })
+      .rpc();
+
+    const escrow = await program.account.escrow.fetch(escrowPda);
+    assert.equal(escrow.status, STATUS_RELEASED);
+  });
+
+  it("Should resolve a dispute as the council PDA and let recipients claim", async () => {
+    const { escrowPda, vaultAta } = await newBounty([6000, 4000]);
+    await fundBounty(escrowPda, vaultAta, 1_000_000);
+
+    await program.methods
+      .raiseDispute(Array(32).fill(7))
+      .accounts({ escrow: escrowPda, initiator: recipient1.publicKey })
+      .signers([recipient1])
+      .rpc();
+
+    await caller.methods
+      .resolveBounty()
+      .accounts({ authority: authority.publicKey, escrow: escrowPda, guardianConfig })
+      .rpc();
+
+    const escrow = await program.account.escrow.fetch(escrowPda);
+    assert.equal(escrow.status, STATUS_RELEASED);
+
+    const before = await getAccount(provider.connection, recipient1Ata);
+    await program.methods
+      .claim()
+      .accounts({
+        escrow: escrowPda,
+        vault: vaultAta,
+        claimant: recipient1.publicKey,
+        destination: recipient1.publicKey,
+      })
+      .signers([recipient1])
+      .rpc();
+    const after = await getAccount(provider.connection, recipient1Ata);
+    assert.equal(Number(after.amount) - Number(before.amount), 600_000);
+  });
+
+  it("Should reject callers that are not the DAO authority", async () => {
+    const bountyId = Buffer.from(Array(32).fill(9));
+    const [escrowPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), treasuryPda.toBuffer(), bountyId],
+      program.programId
+    );
+
+    try {
+      await caller.methods
+        .createBounty({
+          bountyId: Array.from(bountyId),
+          tokenMint: mint,
+          recipients: [recipient1.publicKey],
+          splits: [10000],
+          requiredConfirmations: 1,
+          arbiter: PublicKey.default,
+          timelockExpiry: new anchor.BN(0),
+        })
+        .accountsPartial({
+          authority: outsider.publicKey,
+          dao: daoPda,
+          escrow: escrowPda,
+          vault: treasuryAta,
+          guardianConfig,
+        })
+        .remainingAccounts(await indexAccounts([recipient1.publicKey]))
+        .signers([outsider])
+        .rpc();
+      assert.fail("Should have rejected a non-authority signer");
+    } catch (error) {
+      assert.match(error.toString(), /ConstraintSeeds|ConstraintHasOne/);
+    }
+  });
+});
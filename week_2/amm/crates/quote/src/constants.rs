@@ -0,0 +1,9 @@
+/// LP tokens minted to the pool's lock account on the first deposit and never withdrawable,
+/// so the LP supply can't return to zero and the share price can't be inflated cheaply
+pub const MINIMUM_LIQUIDITY: u64 = 1_000;
+
+/// Seconds for the volatility behind a dynamic fee to decay by half
+pub const VOLATILITY_HALF_LIFE: i64 = 300;
+
+/// Basis points of recent price movement that add one basis point to a dynamic fee
+pub const VOLATILITY_FEE_DIVISOR: u64 = 10;
@@ -0,0 +1,58 @@
+use core::fmt;
+
+/// Why a quote could not be computed
+/// The program maps each variant to the `AmmError` of the same meaning
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuoteError {
+    /// A result doesn't fit its integer type.
+    Overflow,
+    /// A subtraction went below zero.
+    Underflow,
+    /// A divisor or a reserve that must be positive is zero.
+    ZeroBalance,
+    /// The pool has no liquidity to price against.
+    NoLiquidityInPool,
+    /// The pool can't pay out the amount asked for.
+    InsufficientBalance,
+    /// The fee rate is 100% or more.
+    InvalidFee,
+    /// The amount is too small to trade.
+    InvalidAmount,
+    /// The pool's curve can't be quoted here.
+    InvalidCurve,
+    /// The StableSwap invariant didn't converge.
+    NotConverged,
+    /// The first deposit is worth no more than the locked minimum liquidity.
+    InsufficientInitialLiquidity,
+    /// A flash loan is in progress, so the vaults don't reflect the pool.
+    FlashLoanActive,
+}
+
+impl fmt::Display for QuoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            QuoteError::Overflow => "overflow",
+            QuoteError::Underflow => "underflow",
+            QuoteError::ZeroBalance => "zero balance",
+            QuoteError::NoLiquidityInPool => "no liquidity in pool",
+            QuoteError::InsufficientBalance => "insufficient balance",
+            QuoteError::InvalidFee => "invalid fee",
+            QuoteError::InvalidAmount => "invalid amount",
+            QuoteError::InvalidCurve => "curve can't be quoted",
+            QuoteError::NotConverged => "stableswap invariant didn't converge",
+            QuoteError::InsufficientInitialLiquidity => "insufficient initial liquidity",
+            QuoteError::FlashLoanActive => "flash loan in progress",
+        };
+        f.write_str(message)
+    }
+}
+
+/// Returns `error` from the enclosing function unless `condition` holds
+macro_rules! require {
+    ($condition:expr, $error:expr) => {
+        if !$condition {
+            return Err($error);
+        }
+    };
+}
+pub(crate) use require;
@@ -0,0 +1,26 @@
+//! Quotes for swaps, deposits and withdrawals, computed from a pool's `Config` and vault balances
+//!
+//! The AMM program prices every instruction with these functions, so a client that deserializes
+//! the same accounts gets exactly the amounts the program will use, either by linking this
+//! crate or by simulating the program's `quote_*` instructions. The crate is `no_std` and
+//! has no dependencies: nothing here reads a sysvar or allocates, the current time is an
+//! argument and the math is `core` integer arithmetic. Errors are `QuoteError`s, which the
+//! program maps to its own error codes.
+//!
+//! Amounts are what the vaults send and receive. Token-2022 transfer fees come on top of
+//! them and are left to the caller, as `amount_before_fee` does on-chain.
+
+#![no_std]
+
+pub mod constants;
+pub mod error;
+pub mod math;
+pub mod pool;
+pub mod stable_swap;
+
+mod quote;
+
+pub use constants::*;
+pub use error::QuoteError;
+pub use pool::{Curve, PoolState};
+pub use quote::*;
@@ -0,0 +1,169 @@
+//! 256-bit intermediate arithmetic behind the curves: `a * b / c` and square roots of
+//! products that don't fit in a `u128`
+
+use crate::error::{require, QuoteError};
+
+/// Full 256-bit product of `a` and `b` as (high, low) 128-bit halves
+///
+/// # Arguments
+/// * `a`, `b` - Factors
+pub fn full_mul(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+    let (a_hi, a_lo) = (a >> 64, a & MASK);
+    let (b_hi, b_lo) = (b >> 64, b & MASK);
+    let lo_lo = a_lo * b_lo;
+    let mid = (a_hi * b_lo) + (lo_lo >> 64);
+    let mid2 = (a_lo * b_hi) + (mid & MASK);
+    let low = (mid2 << 64) | (lo_lo & MASK);
+    let high = a_hi * b_hi + (mid >> 64) + (mid2 >> 64);
+    (high, low)
+}
+
+/// Divides the 256-bit number (`high`, `low`) by `c`
+/// Returns the quotient, which must fit in 128 bits, and the remainder
+fn div_rem_256(mut high: u128, mut low: u128, c: u128) -> Result<(u128, u128), QuoteError> {
+    require!(c != 0, QuoteError::ZeroBalance);
+    require!(high < c, QuoteError::Overflow);
+
+    // Shift-subtract long division; the quotient fits in 128 bits because high < c
+    let mut quotient = 0u128;
+    for _ in 0..128 {
+        let carry = high >> 127;
+        high = (high << 1) | (low >> 127);
+        low <<= 1;
+        quotient <<= 1;
+        if carry == 1 || high >= c {
+            high = high.wrapping_sub(c);
+            quotient |= 1;
+        }
+    }
+    Ok((quotient, high))
+}
+
+/// Computes `a * b / c` with a 256-bit intermediate product
+/// Returns the quotient rounded down and the remainder
+fn mul_div_rem(a: u128, b: u128, c: u128) -> Result<(u128, u128), QuoteError> {
+    require!(c != 0, QuoteError::ZeroBalance);
+    if let Some(product) = a.checked_mul(b) {
+        return Ok((product / c, product % c));
+    }
+    let (high, low) = full_mul(a, b);
+    div_rem_256(high, low, c)
+}
+
+/// Computes `a * b / c` rounded down, with a 256-bit intermediate product
+///
+/// # Arguments
+/// * `a`, `b` - Factors of the numerator
+/// * `c` - Denominator, must not be zero
+pub fn mul_div(a: u128, b: u128, c: u128) -> Result<u128, QuoteError> {
+    Ok(mul_div_rem(a, b, c)?.0)
+}
+
+/// Computes `a * b / c` rounded up, with a 256-bit intermediate product
+///
+/// # Arguments
+/// * `a`, `b` - Factors of the numerator
+/// * `c` - Denominator, must not be zero
+pub fn mul_div_ceil(a: u128, b: u128, c: u128) -> Result<u128, QuoteError> {
+    let (quotient, remainder) = mul_div_rem(a, b, c)?;
+    match remainder {
+        0 => Ok(quotient),
+        _ => quotient.checked_add(1).ok_or(QuoteError::Overflow),
+    }
+}
+
+/// Integer square root of the 256-bit number (`high`, `low`), rounded down
+///
+/// # Arguments
+/// * `high`, `low` - Upper and lower 128-bit halves of the number
+pub fn sqrt_u256(high: u128, low: u128) -> Result<u128, QuoteError> {
+    let bits = match high {
+        0 => 128 - low.leading_zeros(),
+        _ => 256 - high.leading_zeros(),
+    };
+    if bits == 0 {
+        return Ok(0);
+    }
+
+    // Newton's method from above: start at a power of two no smaller than the root
+    let mut x: u128 = match bits.div_ceil(2) {
+        128 => u128::MAX,
+        half => 1 << half,
+    };
+    loop {
+        // n / x >= 2^128 > x: the next step can't go lower, so x is the root.
+        // This also covers every n above (2^128 - 1)^2, whose root is 2^128 - 1
+        if high >= x {
+            return Ok(x);
+        }
+        let (quotient, _) = div_rem_256(high, low, x)?;
+        let next = ((x >> 1) + (quotient >> 1)) + (x & quotient & 1);
+        if next >= x {
+            return Ok(x);
+        }
+        x = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_mul_matches_known_products() {
+        assert_eq!(full_mul(0, u128::MAX), (0, 0));
+        assert_eq!(full_mul(1 << 64, 1 << 64), (1, 0));
+        assert_eq!(full_mul(u128::MAX, 2), (1, u128::MAX - 1));
+        // (2^128 - 1)^2 = 2^256 - 2^129 + 1
+        assert_eq!(full_mul(u128::MAX, u128::MAX), (u128::MAX - 1, 1));
+
+        // Agrees with native multiplication whenever the product fits
+        for (a, b) in [(3u128, 7u128), (u64::MAX as u128, u64::MAX as u128), (1 << 100, 1 << 27)] {
+            assert_eq!(full_mul(a, b), (0, a * b));
+        }
+    }
+
+    #[test]
+    fn mul_div_rounds_down_and_up() {
+        assert_eq!(mul_div(10, 10, 3).unwrap(), 33);
+        assert_eq!(mul_div_ceil(10, 10, 3).unwrap(), 34);
+        assert_eq!(mul_div_ceil(10, 9, 3).unwrap(), 30);
+
+        // Products past 128 bits go through the 256-bit path
+        assert_eq!(mul_div(1 << 100, 1 << 100, 1 << 90).unwrap(), 1 << 110);
+        assert_eq!(mul_div(u128::MAX, u128::MAX, u128::MAX).unwrap(), u128::MAX);
+        assert_eq!(mul_div(u128::MAX, 3, 4).unwrap(), u128::MAX / 4 * 3 + 2);
+        assert_eq!(mul_div_ceil(u128::MAX, 3, 4).unwrap(), u128::MAX / 4 * 3 + 3);
+    }
+
+    #[test]
+    fn mul_div_rejects_zero_denominators_and_overflow() {
+        assert!(mul_div(1, 1, 0).is_err());
+        assert!(mul_div(u128::MAX, u128::MAX, 0).is_err());
+        // The quotient needs more than 128 bits
+        assert!(mul_div(u128::MAX, 2, 1).is_err());
+        assert!(mul_div_ceil(u128::MAX, u128::MAX, u128::MAX - 1).is_err());
+    }
+
+    #[test]
+    fn sqrt_u256_is_the_floor_of_the_root() {
+        assert_eq!(sqrt_u256(0, 0).unwrap(), 0);
+        assert_eq!(sqrt_u256(0, 1).unwrap(), 1);
+        assert_eq!(sqrt_u256(0, 15).unwrap(), 3);
+        assert_eq!(sqrt_u256(0, 16).unwrap(), 4);
+        assert_eq!(sqrt_u256(1, 0).unwrap(), 1 << 64);
+        assert_eq!(sqrt_u256(u128::MAX, u128::MAX).unwrap(), u128::MAX);
+
+        for root in [2u128, 1_000_003, u64::MAX as u128, 1 << 100, u128::MAX - 1, u128::MAX] {
+            let (high, low) = full_mul(root, root);
+            assert_eq!(sqrt_u256(high, low).unwrap(), root);
+            // One below a perfect square still rounds down to the previous root
+            let (high, low) = match low {
+                0 => (high - 1, u128::MAX),
+                _ => (high, low - 1),
+            };
+            assert_eq!(sqrt_u256(high, low).unwrap(), root - 1);
+        }
+    }
+}
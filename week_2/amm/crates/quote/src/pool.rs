@@ -0,0 +1,132 @@
+use crate::constants::{VOLATILITY_FEE_DIVISOR, VOLATILITY_HALF_LIFE};
+use crate::error::{require, QuoteError};
+
+/// Invariant a pool prices swaps with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Curve {
+    /// x * y = k, for uncorrelated pairs.
+    ConstantProduct,
+    /// Curve-style StableSwap with amplification, for pegged pairs.
+    StableSwap,
+    /// Liquidity over tick ranges, priced by the pool's own account rather than its reserves.
+    Concentrated,
+}
+
+/// The parts of a pool's `Config` account that its quotes depend on
+/// The program builds one with `Config::pool_state`; clients can fill one in from the
+/// deserialized account without linking the program
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolState {
+    /// Invariant used to price swaps.
+    pub curve: Curve,
+
+    /// Static swap fee (in basis points).
+    pub fee: u16,
+
+    /// Whether swaps pay the dynamic fee instead of `fee`.
+    pub dynamic_fee: bool,
+
+    /// Dynamic fee charged when the price has been still (in basis points).
+    pub min_fee: u16,
+
+    /// Highest dynamic fee (in basis points).
+    pub max_fee: u16,
+
+    /// Recent price movement (in basis points) as of `volatility_updated`.
+    pub volatility: u64,
+
+    /// Unix timestamp `volatility` was last decayed to.
+    pub volatility_updated: i64,
+
+    /// Share of every swap fee set aside for the protocol (in basis points of the fee).
+    pub protocol_fee: u16,
+
+    /// Token X protocol fees held in vault X but not yet collected.
+    pub protocol_fees_x: u64,
+
+    /// Token Y protocol fees held in vault Y but not yet collected.
+    pub protocol_fees_y: u64,
+
+    /// Token X a flash loan in progress owes vault X.
+    pub flash_loan_x: u64,
+
+    /// Token Y a flash loan in progress owes vault Y.
+    pub flash_loan_y: u64,
+
+    /// StableSwap amplification coefficient at `amp_ramp_start`.
+    pub amp_initial: u64,
+
+    /// StableSwap amplification coefficient reached at `amp_ramp_end`.
+    pub amp_target: u64,
+
+    /// Unix timestamp the current amplification ramp started at.
+    pub amp_ramp_start: i64,
+
+    /// Unix timestamp the current amplification ramp ends at.
+    pub amp_ramp_end: i64,
+}
+
+impl PoolState {
+    /// Pool reserves backing the curve: vault balances minus uncollected protocol fees
+    ///
+    /// # Arguments
+    /// * `vault_x` - Current balance of vault X
+    /// * `vault_y` - Current balance of vault Y
+    pub fn reserves(&self, vault_x: u64, vault_y: u64) -> Result<(u64, u64), QuoteError> {
+        // Lent-out vaults don't reflect the pool, so nothing may price against them
+        require!(!self.flash_loan_active(), QuoteError::FlashLoanActive);
+        Ok((
+            vault_x.checked_sub(self.protocol_fees_x).ok_or(QuoteError::Underflow)?,
+            vault_y.checked_sub(self.protocol_fees_y).ok_or(QuoteError::Underflow)?,
+        ))
+    }
+
+    /// Whether a flash loan is waiting to be repaid
+    pub fn flash_loan_active(&self) -> bool {
+        self.flash_loan_x != 0 || self.flash_loan_y != 0
+    }
+
+    /// Swap fee in basis points charged at `now`: the static `fee`, or the dynamic fee
+    /// `min_fee + volatility / VOLATILITY_FEE_DIVISOR` capped at `max_fee`
+    ///
+    /// # Arguments
+    /// * `now` - Current unix timestamp
+    pub fn swap_fee(&self, now: i64) -> u16 {
+        if !self.dynamic_fee {
+            return self.fee;
+        }
+        let extra = self.volatility_at(now) / VOLATILITY_FEE_DIVISOR;
+        (self.min_fee as u64).saturating_add(extra).min(self.max_fee as u64) as u16
+    }
+
+    /// Volatility decayed to `now`
+    ///
+    /// # Arguments
+    /// * `now` - Current unix timestamp
+    pub fn volatility_at(&self, now: i64) -> u64 {
+        let halvings = now.saturating_sub(self.volatility_updated).max(0) / VOLATILITY_HALF_LIFE;
+        match halvings {
+            0..=63 => self.volatility >> halvings,
+            _ => 0,
+        }
+    }
+
+    /// StableSwap amplification coefficient at `now`
+    /// Moves linearly from `amp_initial` to `amp_target` over the ramp
+    ///
+    /// # Arguments
+    /// * `now` - Current unix timestamp
+    pub fn amp(&self, now: i64) -> u64 {
+        if now >= self.amp_ramp_end {
+            return self.amp_target;
+        }
+        let elapsed = now.saturating_sub(self.amp_ramp_start).max(0) as u128;
+        let duration = (self.amp_ramp_end - self.amp_ramp_start) as u128;
+        let (initial, target) = (self.amp_initial as u128, self.amp_target as u128);
+        let amp = match target >= initial {
+            true => initial + (target - initial) * elapsed / duration,
+            false => initial - (initial - target) * elapsed / duration,
+        };
+        amp as u64
+    }
+}
@@ -0,0 +1,617 @@
+use crate::constants::MINIMUM_LIQUIDITY;
+use crate::error::{require, QuoteError};
+use crate::math::{mul_div, mul_div_ceil, sqrt_u256};
+use crate::pool::{Curve, PoolState};
+use crate::stable_swap;
+
+/// Result of quoting a swap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapQuote {
+    /// Input the vault receives, including the fee.
+    pub amount_in: u64,
+
+    /// Output the vault sends.
+    pub amount_out: u64,
+
+    /// Swap fee charged, in the input token.
+    pub fee: u64,
+
+    /// Part of `fee` set aside for the protocol.
+    pub protocol_fee: u64,
+
+    /// Swap fee rate applied (in basis points).
+    pub fee_bps: u16,
+
+    /// Fee actually paid as a share of the input (in basis points), after rounding.
+    pub effective_fee_bps: u64,
+
+    /// How much worse the execution price is than the price before the swap, ignoring
+    /// the fee (in basis points). The price is the reserve ratio, which for StableSwap
+    /// pools only approximates the marginal price.
+    pub price_impact_bps: u64,
+
+    /// Pool reserves of token X after the swap, excluding protocol fees.
+    pub reserve_x: u64,
+
+    /// Pool reserves of token Y after the swap, excluding protocol fees.
+    pub reserve_y: u64,
+}
+
+/// Result of quoting a deposit or withdrawal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LiquidityQuote {
+    /// Token X the vault receives or sends.
+    pub amount_x: u64,
+
+    /// Token Y the vault receives or sends.
+    pub amount_y: u64,
+
+    /// LP tokens minted to or burned from the user.
+    pub lp_amount: u64,
+}
+
+/// Quotes selling `amount_in` into a constant product or StableSwap pool
+///
+/// # Arguments
+/// * `pool` - State of the pool
+/// * `vault_x`, `vault_y` - Vault balances, including uncollected protocol fees
+/// * `lp_supply` - Current LP token supply
+/// * `is_x` - true if selling token X for Y, false if selling Y for X
+/// * `amount_in` - Amount the vault receives
+/// * `now` - Unix timestamp the fee and amplification are read at
+///
+/// # Returns
+/// * `Result<SwapQuote, QuoteError>` - Amounts, fees and price impact of the swap
+pub fn quote_swap_exact_in(
+    pool: &PoolState,
+    vault_x: u64,
+    vault_y: u64,
+    lp_supply: u64,
+    is_x: bool,
+    amount_in: u64,
+    now: i64,
+) -> Result<SwapQuote, QuoteError> {
+    let (reserve_x, reserve_y) = pool.reserves(vault_x, vault_y)?;
+    let (amount_out, fee) =
+        quote_exact_in(pool, is_x, reserve_x, reserve_y, lp_supply, amount_in, now)?;
+    swap_quote(pool, is_x, (reserve_x, reserve_y), amount_in, amount_out, fee, now)
+}
+
+/// Quotes buying exactly `amount_out` from a constant product or StableSwap pool
+///
+/// # Arguments
+/// * `pool` - State of the pool
+/// * `vault_x`, `vault_y` - Vault balances, including uncollected protocol fees
+/// * `is_x` - true if selling token X for Y, false if selling Y for X
+/// * `amount_out` - Amount the vault sends
+/// * `now` - Unix timestamp the fee and amplification are read at
+///
+/// # Returns
+/// * `Result<SwapQuote, QuoteError>` - Amounts, fees and price impact of the swap
+pub fn quote_swap_exact_out(
+    pool: &PoolState,
+    vault_x: u64,
+    vault_y: u64,
+    is_x: bool,
+    amount_out: u64,
+    now: i64,
+) -> Result<SwapQuote, QuoteError> {
+    let (reserve_x, reserve_y) = pool.reserves(vault_x, vault_y)?;
+    let (reserve_in, reserve_out) = match is_x {
+        true => (reserve_x, reserve_y),   // Swapping X for Y
+        false => (reserve_y, reserve_x),  // Swapping Y for X
+    };
+
+    let fee_bps = pool.swap_fee(now);
+    let (amount_in, fee) = match pool.curve {
+        Curve::ConstantProduct => quote_exact_out(reserve_in, reserve_out, amount_out, fee_bps)?,
+        Curve::StableSwap => stable_swap::swap_exact_out(
+            pool.amp(now),
+            reserve_in,
+            reserve_out,
+            amount_out,
+            fee_bps,
+        )?,
+        // Concentrated pools trade through swap_concentrated
+        Curve::Concentrated => return Err(QuoteError::InvalidCurve),
+    };
+    swap_quote(pool, is_x, (reserve_x, reserve_y), amount_in, amount_out, fee, now)
+}
+
+/// Fills in the fee split, post-swap reserves and price impact of a priced swap
+fn swap_quote(
+    pool: &PoolState,
+    is_x: bool,
+    reserves: (u64, u64),
+    amount_in: u64,
+    amount_out: u64,
+    fee: u64,
+    now: i64,
+) -> Result<SwapQuote, QuoteError> {
+    // protocol_fee <= 10_000 so the share always fits back into a u64
+    let protocol_fee = (fee as u128 * pool.protocol_fee as u128 / 10_000) as u64;
+    let (reserve_x, reserve_y) =
+        post_swap_reserves(is_x, reserves, amount_in - protocol_fee, amount_out)?;
+
+    let (reserve_in, reserve_out) = match is_x {
+        true => reserves,
+        false => (reserves.1, reserves.0),
+    };
+    Ok(SwapQuote {
+        amount_in,
+        amount_out,
+        fee,
+        protocol_fee,
+        fee_bps: pool.swap_fee(now),
+        effective_fee_bps: match amount_in {
+            0 => 0,
+            _ => (fee as u128 * 10_000 / amount_in as u128) as u64,
+        },
+        price_impact_bps: price_impact_bps(reserve_in, reserve_out, amount_in - fee, amount_out)?,
+        reserve_x,
+        reserve_y,
+    })
+}
+
+/// Price impact of a swap (in basis points): the shortfall of its execution price
+/// `amount_out / net_in` against the reserve ratio `reserve_out / reserve_in` before it
+///
+/// `|r_out * net_in - amount_out * r_in| / (r_out * net_in)`, rounded down
+///
+/// # Arguments
+/// * `reserve_in` - Pool reserves of the token sold, before the swap
+/// * `reserve_out` - Pool reserves of the token bought, before the swap
+/// * `net_in` - Input after the fee
+/// * `amount_out` - Output of the swap
+pub fn price_impact_bps(
+    reserve_in: u64,
+    reserve_out: u64,
+    net_in: u64,
+    amount_out: u64,
+) -> Result<u64, QuoteError> {
+    let spot_out = reserve_out as u128 * net_in as u128;
+    if spot_out == 0 {
+        return Ok(0);
+    }
+    let shortfall = spot_out.abs_diff(amount_out as u128 * reserve_in as u128);
+    let impact = mul_div(shortfall, 10_000, spot_out)?;
+    Ok(u64::try_from(impact).unwrap_or(u64::MAX))
+}
+
+/// Quotes the tokens a deposit minting `lp_amount` LP tokens needs, rounded up
+///
+/// Deposits are proportional to the reserves on every curve. Empty pools have no
+/// proportions; their first deposit is quoted with `quote_initial_deposit`.
+///
+/// # Arguments
+/// * `pool` - State of the pool
+/// * `vault_x`, `vault_y` - Vault balances, including uncollected protocol fees
+/// * `lp_supply` - Current LP token supply
+/// * `lp_amount` - LP tokens to mint
+///
+/// # Returns
+/// * `Result<LiquidityQuote, QuoteError>` - Amounts the vaults must receive
+pub fn quote_deposit(
+    pool: &PoolState,
+    vault_x: u64,
+    vault_y: u64,
+    lp_supply: u64,
+    lp_amount: u64,
+) -> Result<LiquidityQuote, QuoteError> {
+    let (reserve_x, reserve_y) = pool.reserves(vault_x, vault_y)?;
+    require!(lp_supply != 0, QuoteError::NoLiquidityInPool);
+
+    // Rounded up so minting never dilutes the LPs already in the pool
+    let share = |reserve: u64| {
+        let amount = mul_div_ceil(reserve as u128, lp_amount as u128, lp_supply as u128)?;
+        u64::try_from(amount).map_err(|_| QuoteError::Overflow)
+    };
+    Ok(LiquidityQuote {
+        amount_x: share(reserve_x)?,
+        amount_y: share(reserve_y)?,
+        lp_amount,
+    })
+}
+
+/// Quotes the LP tokens the first deposit into an empty pool mints to the depositor
+///
+/// The deposit is worth `sqrt(x * y)` LP tokens of the reserves it leaves behind, tokens
+/// donated to the empty pool included, of which `MINIMUM_LIQUIDITY` are locked.
+///
+/// # Arguments
+/// * `reserve_x` - Pool X reserves after the deposit
+/// * `reserve_y` - Pool Y reserves after the deposit
+///
+/// # Returns
+/// * `Result<u64, QuoteError>` - LP tokens minted to the depositor
+pub fn quote_initial_deposit(reserve_x: u64, reserve_y: u64) -> Result<u64, QuoteError> {
+    let liquidity = sqrt_u256(0, reserve_x as u128 * reserve_y as u128)?;
+    let liquidity = u64::try_from(liquidity).map_err(|_| QuoteError::Overflow)?;
+    require!(liquidity > MINIMUM_LIQUIDITY, QuoteError::InsufficientInitialLiquidity);
+    Ok(liquidity - MINIMUM_LIQUIDITY)
+}
+
+/// Quotes the tokens a withdrawal burning `lp_amount` LP tokens returns, rounded down
+///
+/// # Arguments
+/// * `pool` - State of the pool
+/// * `vault_x`, `vault_y` - Vault balances, including uncollected protocol fees
+/// * `lp_supply` - Current LP token supply
+/// * `lp_amount` - LP tokens to burn
+///
+/// # Returns
+/// * `Result<LiquidityQuote, QuoteError>` - Amounts the vaults send
+pub fn quote_withdraw(
+    pool: &PoolState,
+    vault_x: u64,
+    vault_y: u64,
+    lp_supply: u64,
+    lp_amount: u64,
+) -> Result<LiquidityQuote, QuoteError> {
+    let (reserve_x, reserve_y) = pool.reserves(vault_x, vault_y)?;
+    require!(lp_amount <= lp_supply, QuoteError::InsufficientBalance);
+
+    // Rounded down so burning never takes more than the LP tokens' share
+    // lp_amount <= lp_supply, so each share fits back into a u64
+    let share = |reserve: u64| {
+        mul_div(reserve as u128, lp_amount as u128, lp_supply as u128).map(|amount| amount as u64)
+    };
+    Ok(LiquidityQuote {
+        amount_x: share(reserve_x)?,
+        amount_y: share(reserve_y)?,
+        lp_amount,
+    })
+}
+
+/// Output of selling `amount_in` into a constant product or StableSwap pool
+///
+/// # Arguments
+/// * `pool` - State of the pool
+/// * `is_x` - true if selling token X for Y, false if selling Y for X
+/// * `reserve_x`, `reserve_y` - Pool reserves before the swap, excluding protocol fees
+/// * `lp_supply` - Current LP token supply
+/// * `amount_in` - Amount the vault received
+/// * `now` - Unix timestamp the fee and amplification are read at
+///
+/// # Returns
+/// * `Result<(u64, u64), QuoteError>` - Amount the pool sends out, and the swap fee charged
+pub fn quote_exact_in(
+    pool: &PoolState,
+    is_x: bool,
+    reserve_x: u64,
+    reserve_y: u64,
+    lp_supply: u64,
+    amount_in: u64,
+    now: i64,
+) -> Result<(u64, u64), QuoteError> {
+    // Tokens sent to a pool nobody has deposited into yet aren't liquidity to trade against
+    require!(lp_supply != 0, QuoteError::NoLiquidityInPool);
+
+    let (reserve_in, reserve_out) = match is_x {
+        true => (reserve_x, reserve_y),   // Swapping X for Y
+        false => (reserve_y, reserve_x),  // Swapping Y for X
+    };
+    match pool.curve {
+        Curve::ConstantProduct => {
+            constant_product_exact_in(reserve_in, reserve_out, amount_in, pool.swap_fee(now))
+        }
+        Curve::StableSwap => stable_swap::swap_exact_in(
+            pool.amp(now),
+            reserve_in,
+            reserve_out,
+            amount_in,
+            pool.swap_fee(now),
+        ),
+        // Concentrated pools trade through swap_concentrated
+        Curve::Concentrated => Err(QuoteError::InvalidCurve),
+    }
+}
+
+/// Constant product formula: output of selling `amount_in`, the fee being taken from the input
+///
+/// With `r_in`/`r_out` the reserves and `f` the fee in basis points:
+/// - `fee = floor(amount_in * f / 10_000)`
+/// - `amount_out = floor(r_out * net_in / (r_in + net_in))` with `net_in = amount_in - fee`
+///
+/// The output rounds down so `(r_in + net_in) * (r_out - amount_out) >= r_in * r_out`.
+///
+/// # Arguments
+/// * `reserve_in` - Pool reserves of the token being sold
+/// * `reserve_out` - Pool reserves of the token being bought
+/// * `amount_in` - Amount the pool received
+/// * `fee` - Swap fee in basis points
+///
+/// # Returns
+/// * `Result<(u64, u64), QuoteError>` - Amount the pool sends out, and the swap fee charged
+pub fn constant_product_exact_in(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_in: u64,
+    fee: u16,
+) -> Result<(u64, u64), QuoteError> {
+    require!(reserve_in != 0 && reserve_out != 0, QuoteError::NoLiquidityInPool);
+    require!(fee < 10_000, QuoteError::InvalidFee);
+    require!(amount_in != 0, QuoteError::InvalidAmount);
+
+    let fee_amount = (amount_in as u128 * fee as u128 / 10_000) as u64;
+    let net_in = (amount_in - fee_amount) as u128;
+    // Below r_out, so the output always fits back into a u64
+    let amount_out = mul_div(reserve_out as u128, net_in, reserve_in as u128 + net_in)?;
+    Ok((amount_out as u64, fee_amount))
+}
+
+/// Inverse constant product formula: input needed to take `amount_out` from the pool
+///
+/// With `r_in`/`r_out` the reserves and `f` the fee in basis points, the input left after
+/// the fee must satisfy `(r_in + net_in) * (r_out - amount_out) >= r_in * r_out`:
+/// - `net_in = ceil(r_in * amount_out / (r_out - amount_out))`
+/// - `amount_in = ceil(net_in * 10_000 / (10_000 - f))`
+///
+/// Both divisions round up so the pool never gives away value to rounding.
+///
+/// # Arguments
+/// * `reserve_in` - Pool reserves of the token being sold
+/// * `reserve_out` - Pool reserves of the token being bought
+/// * `amount_out` - Amount of the output token leaving the pool
+/// * `fee` - Swap fee in basis points
+///
+/// # Returns
+/// * `Result<(u64, u64), QuoteError>` - Input the pool must receive, and the swap fee included in it
+pub fn quote_exact_out(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_out: u64,
+    fee: u16,
+) -> Result<(u64, u64), QuoteError> {
+    require!(reserve_in != 0 && reserve_out != 0, QuoteError::NoLiquidityInPool);
+    // The pool can never be emptied of the output token
+    require!(amount_out < reserve_out, QuoteError::InsufficientBalance);
+    require!(fee < 10_000, QuoteError::InvalidFee);
+
+    let remaining_out = (reserve_out - amount_out) as u128;
+    let net_in = (reserve_in as u128 * amount_out as u128).div_ceil(remaining_out);
+    // Taking nearly the whole output reserve needs an input far past u64, or even u128
+    let amount_in = net_in
+        .checked_mul(10_000)
+        .ok_or(QuoteError::Overflow)?
+        .div_ceil(10_000 - fee as u128);
+
+    // amount_in >= net_in, so net_in fits a u64 whenever amount_in does
+    let amount_in = u64::try_from(amount_in).map_err(|_| QuoteError::Overflow)?;
+    Ok((amount_in, amount_in - net_in as u64))
+}
+
+/// Pool reserves (x, y) after a swap
+///
+/// # Arguments
+/// * `is_x` - true if token X was sold for Y, false if Y was sold for X
+/// * `reserves` - Pool reserves (x, y) before the swap
+/// * `pool_in` - Input added to the pool, excluding the protocol's share of the fee
+/// * `pool_out` - Output that left the pool
+///
+/// # Returns
+/// * `Result<(u64, u64), QuoteError>` - Pool reserves (x, y) after the swap
+pub fn post_swap_reserves(
+    is_x: bool,
+    reserves: (u64, u64),
+    pool_in: u64,
+    pool_out: u64,
+) -> Result<(u64, u64), QuoteError> {
+    let (reserve_in, reserve_out) = match is_x {
+        true => reserves,
+        false => (reserves.1, reserves.0),
+    };
+    let reserve_in = reserve_in.checked_add(pool_in).ok_or(QuoteError::Overflow)?;
+    let reserve_out = reserve_out.checked_sub(pool_out).ok_or(QuoteError::Underflow)?;
+    Ok(match is_x {
+        true => (reserve_in, reserve_out),
+        false => (reserve_out, reserve_in),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    /// Static-fee pool with no protocol fees accrued and no ramp in progress
+    fn pool(curve: Curve, fee: u16) -> PoolState {
+        PoolState {
+            curve,
+            fee,
+            dynamic_fee: false,
+            min_fee: fee,
+            max_fee: fee,
+            volatility: 0,
+            volatility_updated: NOW,
+            protocol_fee: 0,
+            protocol_fees_x: 0,
+            protocol_fees_y: 0,
+            flash_loan_x: 0,
+            flash_loan_y: 0,
+            amp_initial: 100,
+            amp_target: 100,
+            amp_ramp_start: NOW,
+            amp_ramp_end: NOW,
+        }
+    }
+
+    /// Deterministic xorshift, so every run checks the same spread of pools
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        /// Roughly log-uniform in [1, 2^bits)
+        fn amount(&mut self, bits: u32) -> u64 {
+            let width = self.next() % bits as u64 + 1;
+            (self.next() >> (64 - width)).max(1)
+        }
+    }
+
+    #[test]
+    fn constant_product_exact_in_pays_the_most_that_keeps_k() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..10_000 {
+            let (reserve_in, reserve_out) = (rng.amount(60), rng.amount(60));
+            let amount_in = rng.amount(60);
+            let fee = (rng.next() % 1_001) as u16;
+            let (out, fee_amount) = constant_product_exact_in(reserve_in, reserve_out, amount_in, fee).unwrap();
+            assert_eq!(fee_amount, (amount_in as u128 * fee as u128 / 10_000) as u64);
+
+            // Brute force: k holds after paying `out`, and would break paying one more
+            let k = reserve_in as u128 * reserve_out as u128;
+            let new_in = reserve_in as u128 + (amount_in - fee_amount) as u128;
+            assert!(new_in * (reserve_out - out) as u128 >= k);
+            if out + 1 < reserve_out {
+                assert!(new_in * (reserve_out - out - 1) as u128 <= k);
+            }
+        }
+    }
+
+    #[test]
+    fn exact_out_input_buys_at_least_the_output() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        for curve in [Curve::ConstantProduct, Curve::StableSwap] {
+            for _ in 0..2_000 {
+                let (vault_x, vault_y) = (rng.amount(48) + 1_000, rng.amount(48) + 1_000);
+                let pool = pool(curve, (rng.next() % 1_001) as u16);
+                let amount_out = rng.amount(64) % (vault_y / 2) + 1;
+
+                let bought = quote_swap_exact_out(&pool, vault_x, vault_y, true, amount_out, NOW).unwrap();
+                let sold = quote_swap_exact_in(&pool, vault_x, vault_y, 1, true, bought.amount_in, NOW).unwrap();
+                // StableSwap rounds each Newton solve up by a unit in the pool's favor
+                let slack = match curve {
+                    Curve::StableSwap => 2,
+                    _ => 0,
+                };
+                assert!(sold.amount_out + slack >= amount_out, "{curve:?}: {} < {amount_out}", sold.amount_out);
+            }
+        }
+    }
+
+    #[test]
+    fn exact_out_reports_inputs_past_u64_as_overflow() {
+        // Buying all but one unit needs 2^120 net in, which overflows u128 with the fee added
+        let reserve = 1u64 << 60;
+        assert_eq!(quote_exact_out(reserve, reserve + 1, reserve, 30), Err(QuoteError::Overflow));
+        // Twice a full u64 reserve fits a u128 but not a u64
+        assert_eq!(quote_exact_out(u64::MAX, 3, 2, 30), Err(QuoteError::Overflow));
+    }
+
+    #[test]
+    fn exact_in_output_costs_no_more_than_rounding_bought_exactly() {
+        let mut rng = Rng(0x8f1b_bcdc_bfa5_3e0b);
+        for curve in [Curve::ConstantProduct, Curve::StableSwap] {
+            for _ in 0..2_000 {
+                let (vault_x, vault_y) = (rng.amount(48) + 1_000, rng.amount(48) + 1_000);
+                let pool = pool(curve, (rng.next() % 1_001) as u16);
+                let amount_in = rng.amount(48);
+
+                let sold = quote_swap_exact_in(&pool, vault_x, vault_y, 1, false, amount_in, NOW).unwrap();
+                if sold.amount_out == 0 {
+                    continue;
+                }
+                let bought = quote_swap_exact_out(&pool, vault_x, vault_y, false, sold.amount_out, NOW).unwrap();
+                // Both the net input and its gross-up for the fee round up in the pool's favor
+                let slack = match curve {
+                    Curve::StableSwap => 3,
+                    _ => 2,
+                };
+                assert!(bought.amount_in <= amount_in + slack, "{curve:?}: {} > {amount_in}", bought.amount_in);
+            }
+        }
+    }
+
+    #[test]
+    fn swap_quote_moves_the_reserves_by_its_amounts() {
+        let mut pool = pool(Curve::ConstantProduct, 30);
+        pool.protocol_fee = 5_000;
+        pool.protocol_fees_x = 400;
+
+        let quote = quote_swap_exact_in(&pool, 1_000_400, 1_000_000, 1, true, 100_000, NOW).unwrap();
+        assert_eq!(quote.fee, 300);
+        assert_eq!(quote.protocol_fee, 150);
+        assert_eq!(quote.fee_bps, 30);
+        assert_eq!(quote.effective_fee_bps, 30);
+        // Protocol fees, accrued or new, stay out of the reserves
+        assert_eq!(quote.reserve_x, 1_000_000 + 100_000 - 150);
+        assert_eq!(quote.reserve_y, 1_000_000 - quote.amount_out);
+        // 1_000_000 * 99_700 / 1_099_700
+        assert_eq!(quote.amount_out, 90_661);
+    }
+
+    #[test]
+    fn price_impact_matches_the_constant_product_closed_form() {
+        // On x * y = k the shortfall against the reserve ratio is net_in / (r_in + net_in)
+        let mut rng = Rng(0xdead_beef_cafe_f00d);
+        for _ in 0..2_000 {
+            let (reserve_in, reserve_out) = (rng.amount(48) + 1_000, rng.amount(48) + 1_000);
+            let amount_in = rng.amount(48);
+            let (out, fee) = constant_product_exact_in(reserve_in, reserve_out, amount_in, 30).unwrap();
+            let net_in = amount_in - fee;
+            // Rounding out down costs up to r_in / (r_out * net_in) of the spot price
+            let rounding = reserve_in as u128 * 10_000 / (reserve_out as u128 * net_in.max(1) as u128);
+            if rounding > 10 {
+                continue;
+            }
+
+            let impact = price_impact_bps(reserve_in, reserve_out, net_in, out).unwrap();
+            let expected = (net_in as u128 * 10_000 / (reserve_in as u128 + net_in as u128)) as u64;
+            assert!(impact >= expected && impact as u128 <= expected as u128 + 1 + rounding, "{impact} vs {expected}");
+        }
+    }
+
+    #[test]
+    fn withdrawing_a_deposit_never_returns_more_than_it_took() {
+        let pool = pool(Curve::ConstantProduct, 30);
+        let mut rng = Rng(0x0123_4567_89ab_cdef);
+        for _ in 0..5_000 {
+            let (vault_x, vault_y, supply) = (rng.amount(50), rng.amount(50), rng.amount(50));
+            // Up to doubling the pool keeps every amount within a u64
+            let lp_amount = rng.amount(50) % supply + 1;
+
+            let deposit = quote_deposit(&pool, vault_x, vault_y, supply, lp_amount).unwrap();
+            let withdraw = quote_withdraw(
+                &pool,
+                vault_x + deposit.amount_x,
+                vault_y + deposit.amount_y,
+                supply + lp_amount,
+                lp_amount,
+            )
+            .unwrap();
+            assert!(withdraw.amount_x <= deposit.amount_x && withdraw.amount_y <= deposit.amount_y);
+        }
+    }
+
+    #[test]
+    fn quotes_refuse_pools_they_cannot_price() {
+        let mut lent = pool(Curve::ConstantProduct, 30);
+        lent.flash_loan_y = 1;
+        assert_eq!(
+            quote_swap_exact_in(&lent, 1_000, 1_000, 1, true, 10, NOW),
+            Err(QuoteError::FlashLoanActive)
+        );
+
+        let concentrated = pool(Curve::Concentrated, 30);
+        assert_eq!(
+            quote_swap_exact_out(&concentrated, 1_000, 1_000, true, 10, NOW),
+            Err(QuoteError::InvalidCurve)
+        );
+
+        // Tokens donated before the first deposit aren't liquidity to trade against
+        let empty = pool(Curve::ConstantProduct, 30);
+        assert_eq!(
+            quote_swap_exact_in(&empty, 5_000, 0, 0, true, 10, NOW),
+            Err(QuoteError::NoLiquidityInPool)
+        );
+        assert_eq!(quote_withdraw(&empty, 1_000, 1_000, 10, 11), Err(QuoteError::InsufficientBalance));
+        assert_eq!(quote_initial_deposit(1_000, 1_000), Err(QuoteError::InsufficientInitialLiquidity));
+    }
+}
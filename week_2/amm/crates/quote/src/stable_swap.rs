@@ -0,0 +1,274 @@
+//! Curve-style StableSwap invariant for two tokens:
+//!
+//! `A * n^n * (x + y) + D = A * D * n^n + D^(n+1) / (n^n * x * y)` with `n = 2`
+//!
+//! The amplification coefficient `A` flattens the curve around the 1:1 price so pegged
+//! pairs trade with far less slippage than on x * y = k. `D` is the total value of the
+//! pool at the peg, and both `D` and the new reserves are found with Newton's method.
+//! Every result is rounded in the pool's favor.
+
+use crate::error::{require, QuoteError};
+use crate::math::mul_div;
+
+/// Newton's method converges in a handful of steps for any sane pool; give up after this many
+const MAX_ITERATIONS: usize = 255;
+
+/// Computes the invariant `D` for reserves `x` and `y`, rounded up
+///
+/// # Arguments
+/// * `amp` - Amplification coefficient `A`
+/// * `x`, `y` - Pool reserves
+pub fn compute_d(amp: u64, x: u64, y: u64) -> Result<u128, QuoteError> {
+    let (x, y) = (x as u128, y as u128);
+    let sum = x + y;
+    if sum == 0 {
+        return Ok(0);
+    }
+    require!(x != 0 && y != 0, QuoteError::NoLiquidityInPool);
+
+    let ann = amp as u128 * 4;
+    // Dividing by the smaller reserve first keeps the rounding error of d_p within a unit;
+    // the other way round it grows with the imbalance and Newton's method never settles
+    let (low, high) = (x.min(y), x.max(y));
+    let mut d = sum;
+    for _ in 0..MAX_ITERATIONS {
+        // d_p = D^3 / (4 * x * y)
+        let d_p = mul_div(mul_div(d, d, low * 2)?, d, high * 2)?;
+        let previous = d;
+        let numerator = ann
+            .checked_mul(sum)
+            .and_then(|n| n.checked_add(d_p * 2))
+            .ok_or(QuoteError::Overflow)?;
+        let denominator = (ann - 1)
+            .checked_mul(d)
+            .and_then(|n| n.checked_add(d_p * 3))
+            .ok_or(QuoteError::Overflow)?;
+        d = mul_div(numerator, d, denominator)?;
+        if d.abs_diff(previous) <= 1 {
+            // A larger D asks more of the reserves after a swap, so round up
+            return Ok(d + 1);
+        }
+    }
+    Err(QuoteError::NotConverged)
+}
+
+/// Computes the reserve of one token that keeps the invariant at `d`
+/// when the other token's reserve is `x`, rounded up
+///
+/// # Arguments
+/// * `amp` - Amplification coefficient `A`
+/// * `x` - New reserve of the other token
+/// * `d` - Invariant to preserve
+pub fn compute_y(amp: u64, x: u128, d: u128) -> Result<u128, QuoteError> {
+    require!(x != 0, QuoteError::ZeroBalance);
+    let ann = amp as u128 * 4;
+
+    // c = D^3 / (4 * x * Ann), b = x + D / Ann
+    let c = mul_div(mul_div(d, d, x * 2)?, d, ann * 2)?;
+    let b = x + d / ann;
+
+    let mut y = d;
+    for _ in 0..MAX_ITERATIONS {
+        let previous = y;
+        // y = (y^2 + c) / (2y + b - D)
+        let denominator = (y * 2 + b).checked_sub(d).ok_or(QuoteError::Underflow)?;
+        y = mul_div(y, y, denominator)? + c / denominator;
+        if y.abs_diff(previous) <= 1 {
+            // The rounded-down iterate can sit just below the true root; round up
+            return Ok(y + 1);
+        }
+    }
+    Err(QuoteError::NotConverged)
+}
+
+/// Output of selling `amount_in` into the pool, the fee being taken from the input
+///
+/// # Arguments
+/// * `amp` - Amplification coefficient `A`
+/// * `reserve_in`, `reserve_out` - Pool reserves of the tokens being sold and bought
+/// * `amount_in` - Amount the pool received
+/// * `fee` - Swap fee in basis points
+///
+/// # Returns
+/// * `Result<(u64, u64)>` - Amount the pool sends out, and the swap fee charged
+pub fn swap_exact_in(
+    amp: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_in: u64,
+    fee: u16,
+) -> Result<(u64, u64), QuoteError> {
+    require!(fee < 10_000, QuoteError::InvalidFee);
+    let fee_amount = (amount_in as u128 * fee as u128 / 10_000) as u64;
+    let net_in = amount_in - fee_amount;
+
+    let d = compute_d(amp, reserve_in, reserve_out)?;
+    let new_in = reserve_in as u128 + net_in as u128;
+    let new_out = compute_y(amp, new_in, d)?;
+
+    // Round the output down by one unit so the invariant never decreases
+    let amount_out = (reserve_out as u128)
+        .saturating_sub(new_out)
+        .saturating_sub(1);
+    Ok((amount_out as u64, fee_amount))
+}
+
+/// Input needed to take `amount_out` from the pool, the fee being added on top
+///
+/// # Arguments
+/// * `amp` - Amplification coefficient `A`
+/// * `reserve_in`, `reserve_out` - Pool reserves of the tokens being sold and bought
+/// * `amount_out` - Amount leaving the pool
+/// * `fee` - Swap fee in basis points
+///
+/// # Returns
+/// * `Result<(u64, u64)>` - Input the pool must receive, and the swap fee included in it
+pub fn swap_exact_out(
+    amp: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_out: u64,
+    fee: u16,
+) -> Result<(u64, u64), QuoteError> {
+    // The pool can never be emptied of the output token
+    require!(amount_out < reserve_out, QuoteError::InsufficientBalance);
+    require!(fee < 10_000, QuoteError::InvalidFee);
+
+    let d = compute_d(amp, reserve_in, reserve_out)?;
+    let new_out = (reserve_out - amount_out) as u128;
+    let new_in = compute_y(amp, new_out, d)?;
+
+    // Round the input up by one unit so the invariant never decreases
+    let net_in = new_in.saturating_sub(reserve_in as u128) + 1;
+    let amount_in = net_in
+        .checked_mul(10_000)
+        .ok_or(QuoteError::Overflow)?
+        .div_ceil(10_000 - fee as u128);
+
+    // amount_in >= net_in, so net_in fits a u64 whenever amount_in does
+    let amount_in = u64::try_from(amount_in).map_err(|_| QuoteError::Overflow)?;
+    Ok((amount_in, amount_in - net_in as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AMPS: [u64; 5] = [1, 10, 100, 1_000, 1_000_000];
+
+    #[test]
+    fn compute_d_is_the_sum_of_balanced_reserves() {
+        assert_eq!(compute_d(100, 0, 0).unwrap(), 0);
+        assert!(compute_d(100, 1_000, 0).is_err());
+
+        for amp in AMPS {
+            let d = compute_d(amp, 1_000_000, 1_000_000).unwrap();
+            assert!(d.abs_diff(2_000_000) <= 2, "amp {amp}: {d}");
+        }
+    }
+
+    #[test]
+    fn compute_d_sits_between_the_product_and_sum_invariants() {
+        // D of x * y = k is 2 * sqrt(x * y), of x + y = k it is x + y
+        for amp in AMPS {
+            let d = compute_d(amp, 4_000_000, 1_000_000).unwrap();
+            assert!(d > 4_000_000 && d <= 5_000_001, "amp {amp}: {d}");
+        }
+        // A larger amplification is closer to the sum
+        let flat = compute_d(1, 4_000_000, 1_000_000).unwrap();
+        let steep = compute_d(1_000, 4_000_000, 1_000_000).unwrap();
+        assert!(steep > flat);
+    }
+
+    #[test]
+    fn compute_d_converges_on_imbalanced_pools() {
+        for amp in AMPS {
+            for (x, y) in [(10_000_000u64, 1_000u64), (10_005_000, 1), (1, 1_000_999), (1 << 63, 1)] {
+                let d = compute_d(amp, x, y).unwrap();
+                assert_eq!(compute_d(amp, y, x).unwrap(), d);
+                assert!(d <= x as u128 + y as u128 + 1, "amp {amp}: {d}");
+            }
+        }
+    }
+
+    #[test]
+    fn compute_y_inverts_compute_d() {
+        for amp in AMPS {
+            let d = compute_d(amp, 3_000_000, 2_000_000).unwrap();
+            let y = compute_y(amp, 3_000_000, d).unwrap();
+            // Rounded up, so never below the reserve that produced D
+            assert!((2_000_000..=2_000_003).contains(&y), "amp {amp}: {y}");
+        }
+        assert!(compute_y(100, 0, 2_000_000).is_err());
+    }
+
+    #[test]
+    fn swap_exact_in_never_decreases_d() {
+        for amp in AMPS {
+            for (reserve_in, reserve_out, amount_in) in [
+                (1_000_000u64, 1_000_000u64, 100_000u64),
+                (1_000_000, 1_000_000, 1),
+                (10_000_000, 1_000, 5_000),
+                (1_000, 10_000_000, 999),
+                (1 << 40, 1 << 40, 1 << 39),
+            ] {
+                for fee in [0u16, 30, 1_000] {
+                    let (out, fee_amount) = swap_exact_in(amp, reserve_in, reserve_out, amount_in, fee).unwrap();
+                    assert!(out < reserve_out);
+                    assert_eq!(fee_amount, (amount_in as u128 * fee as u128 / 10_000) as u64);
+
+                    // The fee stays in the pool too, but D must hold on the net input alone
+                    let before = compute_d(amp, reserve_in, reserve_out).unwrap();
+                    let after = compute_d(amp, reserve_in + amount_in - fee_amount, reserve_out - out).unwrap();
+                    assert!(after >= before, "amp {amp} fee {fee}: {before} -> {after}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn swap_exact_in_beats_the_constant_product_near_the_peg() {
+        let (out, fee) = swap_exact_in(100, 1_000_000, 1_000_000, 100_000, 30).unwrap();
+        assert_eq!(fee, 300);
+        // x * y = k would pay 1_000_000 * 99_700 / 1_099_700 = 90_661
+        assert_eq!(out, 99_648);
+    }
+
+    #[test]
+    fn swap_exact_out_never_decreases_d() {
+        for amp in AMPS {
+            for (reserve_in, reserve_out, amount_out) in [
+                (1_000_000u64, 1_000_000u64, 50_000u64),
+                (1_000_000, 1_000_000, 1),
+                (10_000_000, 1_000, 500),
+                (1 << 40, 1 << 40, 1 << 39),
+            ] {
+                for fee in [0u16, 30, 1_000] {
+                    let (amount_in, fee_amount) =
+                        swap_exact_out(amp, reserve_in, reserve_out, amount_out, fee).unwrap();
+                    assert!(fee_amount as u128 * 10_000 >= amount_in as u128 * fee as u128);
+
+                    let before = compute_d(amp, reserve_in, reserve_out).unwrap();
+                    let after =
+                        compute_d(amp, reserve_in + amount_in - fee_amount, reserve_out - amount_out).unwrap();
+                    assert!(after >= before, "amp {amp} fee {fee}: {before} -> {after}");
+
+                    // Selling the quoted input pays out at least the requested amount
+                    let (out, _) = swap_exact_in(amp, reserve_in, reserve_out, amount_in, fee).unwrap();
+                    assert!(out + 2 >= amount_out, "amp {amp} fee {fee}: {out} < {amount_out}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn swap_exact_out_never_empties_the_pool() {
+        assert!(swap_exact_out(100, 1_000_000, 1_000_000, 1_000_000, 30).is_err());
+        assert!(swap_exact_out(100, 1_000_000, 1_000_000, 1_000, 10_000).is_err());
+        // Draining a large pool needs more input than a u64 holds
+        assert_eq!(
+            swap_exact_out(1, 1 << 60, 1 << 60, (1 << 60) - 1, 30),
+            Err(QuoteError::Overflow)
+        );
+    }
+}
@@ -0,0 +1,348 @@
+//! Concentrated-liquidity math in the style of Uniswap v3:
+//!
+//! `sqrt_price = sqrt(y / x) * 2^64`, and tick `i` sits at the price `1.0001^i`
+//!
+//! Between two initialized ticks the pool behaves like a constant product pool with
+//! virtual reserves `x = L / sqrt_price` and `y = L * sqrt_price`, where `L` is the
+//! liquidity of every position whose range contains the current price. Square-root
+//! prices are Q64.64 fixed-point numbers and every result is rounded in the pool's favor.
+
+use anchor_lang::prelude::*;
+
+use crate::constants::{MAX_TICK, MIN_TICK};
+use crate::errors::AmmError;
+use crate::utils::{full_mul, mul_div, mul_div_ceil};
+
+/// 1.0 in Q64.64
+pub const Q64: u128 = 1 << 64;
+
+/// `2^128 / sqrt(1.0001)^(2^i)` for every bit `i` of a tick index
+const TICK_FACTORS: [u128; 19] = [
+    0xfffcb933bd6fad37aa2d162d1a594001,
+    0xfff97272373d413259a46990580e213a,
+    0xfff2e50f5f656932ef12357cf3c7fdcc,
+    0xffe5caca7e10e4e61c3624eaa0941cd0,
+    0xffcb9843d60f6159c9db58835c926644,
+    0xff973b41fa98c081472e6896dfb254c0,
+    0xff2ea16466c96a3843ec78b326b52861,
+    0xfe5dee046a99a2a811c461f1969c3053,
+    0xfcbe86c7900a88aedcffc83b479aa3a4,
+    0xf987a7253ac413176f2b074cf7815e54,
+    0xf3392b0822b70005940c7a398e4b70f3,
+    0xe7159475a2c29b7443b29c7fa6e889d9,
+    0xd097f3bdfd2022b8845ad8f792aa5825,
+    0xa9f746462d870fdf8a65dc1f90e061e5,
+    0x70d869a156d2a1b890bb3df62baf32f7,
+    0x31be135f97d08fd981231505542fcfa6,
+    0x9aa508b5b7a84e1c677de54f3e99bc9,
+    0x5d6af8dedb81196699c329225ee604,
+    0x2216e584f5fa1ea926041bedfe98,
+];
+
+/// Square-root price at `tick`, in Q64.64
+///
+/// # Arguments
+/// * `tick` - Tick index between `MIN_TICK` and `MAX_TICK`
+pub fn sqrt_price_at_tick(tick: i32) -> Result<u128> {
+    require!((MIN_TICK..=MAX_TICK).contains(&tick), AmmError::InvalidTickRange);
+    let abs_tick = tick.unsigned_abs();
+
+    // 1 / sqrt(1.0001)^|tick| in Q0.128, multiplying in one factor per set bit
+    let mut ratio: Option<u128> = None;
+    for (bit, factor) in TICK_FACTORS.iter().enumerate() {
+        if abs_tick & (1 << bit) != 0 {
+            ratio = Some(match ratio {
+                Some(ratio) => full_mul(ratio, *factor).0,
+                None => *factor,
+            });
+        }
+    }
+    let Some(ratio) = ratio else {
+        return Ok(Q64);
+    };
+
+    // Back to Q64.64, inverting the ratio for positive ticks
+    match tick > 0 {
+        true => mul_div(1 << 127, 1 << 65, ratio),
+        false => Ok(ratio >> 64),
+    }
+}
+
+/// Highest tick whose square-root price is at most `sqrt_price`
+///
+/// # Arguments
+/// * `sqrt_price` - Square-root price in Q64.64, within the prices of `MIN_TICK` and `MAX_TICK`
+pub fn tick_at_sqrt_price(sqrt_price: u128) -> Result<i32> {
+    require!(
+        sqrt_price >= sqrt_price_at_tick(MIN_TICK)? && sqrt_price <= sqrt_price_at_tick(MAX_TICK)?,
+        AmmError::InvalidPrice
+    );
+
+    // Binary search: sqrt_price_at_tick(low) <= sqrt_price < sqrt_price_at_tick(high + 1)
+    let (mut low, mut high) = (MIN_TICK, MAX_TICK);
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+        match sqrt_price_at_tick(mid)? <= sqrt_price {
+            true => low = mid,
+            false => high = mid - 1,
+        }
+    }
+    Ok(low)
+}
+
+/// Amount of token X backing `liquidity` between two square-root prices:
+/// `L * (upper - lower) / (lower * upper)`
+///
+/// # Arguments
+/// * `sqrt_price_a`, `sqrt_price_b` - Bounds of the price range, in any order
+/// * `liquidity` - Liquidity spread over the range
+/// * `round_up` - true when the pool receives the amount, false when it pays it out
+pub fn amount_x(sqrt_price_a: u128, sqrt_price_b: u128, liquidity: u128, round_up: bool) -> Result<u128> {
+    let (lower, upper) = (sqrt_price_a.min(sqrt_price_b), sqrt_price_a.max(sqrt_price_b));
+    require!(lower != 0, AmmError::InvalidPrice);
+    match round_up {
+        true => mul_div_ceil(mul_div_ceil(liquidity, Q64, lower)?, upper - lower, upper),
+        false => mul_div(mul_div(liquidity, Q64, lower)?, upper - lower, upper),
+    }
+}
+
+/// Amount of token Y backing `liquidity` between two square-root prices:
+/// `L * (upper - lower)`
+///
+/// # Arguments
+/// * `sqrt_price_a`, `sqrt_price_b` - Bounds of the price range, in any order
+/// * `liquidity` - Liquidity spread over the range
+/// * `round_up` - true when the pool receives the amount, false when it pays it out
+pub fn amount_y(sqrt_price_a: u128, sqrt_price_b: u128, liquidity: u128, round_up: bool) -> Result<u128> {
+    let (lower, upper) = (sqrt_price_a.min(sqrt_price_b), sqrt_price_a.max(sqrt_price_b));
+    match round_up {
+        true => mul_div_ceil(liquidity, upper - lower, Q64),
+        false => mul_div(liquidity, upper - lower, Q64),
+    }
+}
+
+/// Token amounts backing `liquidity` over a position's range at the current price
+///
+/// Below the range the position is all token X, above it all token Y,
+/// and inside it a mix of both.
+///
+/// # Arguments
+/// * `sqrt_price` - Current square-root price of the pool
+/// * `sqrt_price_lower`, `sqrt_price_upper` - Square-root prices of the position's ticks
+/// * `liquidity` - Liquidity being added or removed
+/// * `round_up` - true when the pool receives the amounts, false when it pays them out
+///
+/// # Returns
+/// * `Result<(u64, u64)>` - Amounts of token X and token Y
+pub fn amounts_for_liquidity(
+    sqrt_price: u128,
+    sqrt_price_lower: u128,
+    sqrt_price_upper: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<(u64, u64)> {
+    let price = sqrt_price.clamp(sqrt_price_lower, sqrt_price_upper);
+    let x = amount_x(price, sqrt_price_upper, liquidity, round_up)?;
+    let y = amount_y(sqrt_price_lower, price, liquidity, round_up)?;
+    Ok((
+        u64::try_from(x).map_err(|_| AmmError::Overflow)?,
+        u64::try_from(y).map_err(|_| AmmError::Overflow)?,
+    ))
+}
+
+/// Square-root price after `amount_in` (net of fees) is sold into the current range
+///
+/// # Arguments
+/// * `sqrt_price` - Current square-root price
+/// * `liquidity` - Active liquidity, must not be zero
+/// * `amount_in` - Input added to the pool
+/// * `x_to_y` - true when selling token X (price moves down), false when selling token Y
+pub fn next_sqrt_price_from_input(
+    sqrt_price: u128,
+    liquidity: u128,
+    amount_in: u64,
+    x_to_y: bool,
+) -> Result<u128> {
+    require!(liquidity != 0, AmmError::NoLiquidityInPool);
+    match x_to_y {
+        // L * P / (L + dx * P), rounded up so the price moves a little less
+        true => {
+            let denominator = liquidity
+                .checked_add(mul_div(amount_in as u128, sqrt_price, Q64)?)
+                .ok_or(AmmError::Overflow)?;
+            mul_div_ceil(liquidity, sqrt_price, denominator)
+        }
+        // P + dy / L, rounded down so the price moves a little less
+        false => sqrt_price
+            .checked_add(mul_div(amount_in as u128, Q64, liquidity)?)
+            .ok_or(AmmError::Overflow.into()),
+    }
+}
+
+/// Result of trading within a single price range
+pub struct SwapStep {
+    /// Square-root price after the step.
+    pub sqrt_price_next: u128,
+    /// Input consumed by the step, excluding the fee.
+    pub amount_in: u64,
+    /// Output paid out by the step.
+    pub amount_out: u64,
+    /// Swap fee charged on the input.
+    pub fee_amount: u64,
+}
+
+/// Trades as much of `amount_remaining` as fits between the current price and `sqrt_price_target`
+///
+/// # Arguments
+/// * `sqrt_price` - Current square-root price
+/// * `sqrt_price_target` - Price of the next initialized tick, or the pool's price bound
+/// * `liquidity` - Active liquidity between the two prices
+/// * `amount_remaining` - Input left to trade, fees included
+/// * `fee` - Swap fee in basis points
+pub fn compute_swap_step(
+    sqrt_price: u128,
+    sqrt_price_target: u128,
+    liquidity: u128,
+    amount_remaining: u64,
+    fee: u16,
+) -> Result<SwapStep> {
+    require!(fee < 10_000, AmmError::InvalidFee);
+    let x_to_y = sqrt_price_target <= sqrt_price;
+    let remaining_less_fee = (amount_remaining as u128 * (10_000 - fee) as u128 / 10_000) as u64;
+
+    // Input needed to move the price all the way to the target
+    let amount_to_target = match x_to_y {
+        true => amount_x(sqrt_price_target, sqrt_price, liquidity, true)?,
+        false => amount_y(sqrt_price, sqrt_price_target, liquidity, true)?,
+    };
+
+    let reached = remaining_less_fee as u128 >= amount_to_target;
+    let sqrt_price_next = match reached {
+        true => sqrt_price_target,
+        false => next_sqrt_price_from_input(sqrt_price, liquidity, remaining_less_fee, x_to_y)?,
+    };
+
+    let amount_in = match (reached, x_to_y) {
+        // At most remaining_less_fee, so it fits in a u64
+        (true, _) => amount_to_target as u64,
+        // Rounding both the price and the amount up can overshoot the input by a unit
+        (false, true) => amount_x(sqrt_price_next, sqrt_price, liquidity, true)?
+            .min(remaining_less_fee as u128) as u64,
+        (false, false) => amount_y(sqrt_price, sqrt_price_next, liquidity, true)?
+            .min(remaining_less_fee as u128) as u64,
+    };
+    let amount_out = match x_to_y {
+        true => amount_y(sqrt_price_next, sqrt_price, liquidity, false)?,
+        false => amount_x(sqrt_price, sqrt_price_next, liquidity, false)?,
+    };
+    let amount_out = u64::try_from(amount_out).map_err(|_| AmmError::Overflow)?;
+
+    let fee_amount = match reached {
+        // Fee on the input actually used
+        true => (amount_in as u128 * fee as u128).div_ceil((10_000 - fee) as u128) as u64,
+        // The step uses up the whole input, whatever is left over is fee
+        false => amount_remaining - amount_in,
+    };
+
+    Ok(SwapStep {
+        sqrt_price_next,
+        amount_in,
+        amount_out,
+        fee_amount,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Uniswap v3 `TickMath.getSqrtRatioAtTick`, a Q64.96 square-root price
+    const UNISWAP_SQRT_RATIOS: [(i32, u128); 12] = [
+        (1, 79232123823359799118286999568),
+        (-1, 79224201403219477170569942574),
+        (10, 79267784519130042428790663799),
+        (-10, 79188560314459151373725315960),
+        (100, 79625275426524748796330556128),
+        (-100, 78833030112140176575862854579),
+        (1_000, 83290069058676223003182343270),
+        (-1_000, 75364347830767020784054125655),
+        (50_000, 965075977353221155028623082916),
+        (-50_000, 6504256538020985011912221507),
+        (MAX_TICK, 340275971719517849884101479065584693834),
+        (MIN_TICK, 18447090764788882728),
+    ];
+
+    #[test]
+    fn sqrt_price_at_tick_matches_uniswap() {
+        assert_eq!(sqrt_price_at_tick(0).unwrap(), Q64);
+        for (tick, ratio) in UNISWAP_SQRT_RATIOS {
+            // Same factors as Uniswap, rounded once more when dropping to 64 fractional bits
+            let expected = ratio >> 32;
+            let actual = sqrt_price_at_tick(tick).unwrap();
+            assert!(actual.abs_diff(expected) <= 1, "tick {tick}: {actual} vs {expected}");
+        }
+    }
+
+    #[test]
+    fn sqrt_price_at_tick_rejects_ticks_out_of_range() {
+        assert!(sqrt_price_at_tick(MIN_TICK - 1).is_err());
+        assert!(sqrt_price_at_tick(MAX_TICK + 1).is_err());
+    }
+
+    #[test]
+    fn tick_at_sqrt_price_round_trips() {
+        for tick in [MIN_TICK, MIN_TICK + 1, -50_000, -1_000, -1, 0, 1, 1_000, 50_000, MAX_TICK - 1, MAX_TICK] {
+            let sqrt_price = sqrt_price_at_tick(tick).unwrap();
+            assert_eq!(tick_at_sqrt_price(sqrt_price).unwrap(), tick);
+            // Anywhere inside the tick's range still maps to the tick
+            if tick < MAX_TICK {
+                let next = sqrt_price_at_tick(tick + 1).unwrap();
+                assert_eq!(tick_at_sqrt_price(next - 1).unwrap(), tick);
+            }
+        }
+        assert!(tick_at_sqrt_price(sqrt_price_at_tick(MIN_TICK).unwrap() - 1).is_err());
+        assert!(tick_at_sqrt_price(sqrt_price_at_tick(MAX_TICK).unwrap() + 1).is_err());
+    }
+
+    #[test]
+    fn compute_swap_step_charges_at_least_the_fee_rate() {
+        let lower = sqrt_price_at_tick(-100).unwrap();
+        let upper = sqrt_price_at_tick(100).unwrap();
+        let liquidity = 1_000_000_000u128;
+
+        // Steps that stop inside the range and steps that reach the target
+        for (target, amount) in [(lower, 1_000_000u64), (lower, 100_000_000), (upper, 1_000_000), (upper, 100_000_000), (lower, 1)] {
+            for fee in [0u16, 1, 30, 100, 1_000] {
+                let step = compute_swap_step(Q64, target, liquidity, amount, fee).unwrap();
+                let gross = step.amount_in as u128 + step.fee_amount as u128;
+
+                assert!(gross <= amount as u128, "fee {fee}: spent {gross} of {amount}");
+                assert!(step.fee_amount as u128 * 10_000 >= gross * fee as u128, "fee {fee}: {} on {gross}", step.fee_amount);
+                if step.sqrt_price_next == target {
+                    // Only the fee on the input used is kept, rounded up by at most a unit
+                    assert!(step.fee_amount as u128 * 10_000 < gross * fee as u128 + 10_000);
+                } else {
+                    assert_eq!(gross, amount as u128);
+                }
+                if fee == 0 {
+                    assert_eq!(step.fee_amount, 0);
+                }
+            }
+        }
+        assert!(compute_swap_step(Q64, lower, liquidity, 1_000, 10_000).is_err());
+    }
+
+    #[test]
+    fn compute_swap_step_pays_out_less_than_it_takes_at_par() {
+        let liquidity = 1_000_000_000u128;
+        let step = compute_swap_step(Q64, sqrt_price_at_tick(-100).unwrap(), liquidity, 1_000_000, 30).unwrap();
+        assert!(step.sqrt_price_next < Q64);
+        assert!(step.amount_out < step.amount_in);
+
+        // The input and output both come from the same price move
+        let amount_in = amount_x(step.sqrt_price_next, Q64, liquidity, true).unwrap();
+        let amount_out = amount_y(step.sqrt_price_next, Q64, liquidity, false).unwrap();
+        assert!(amount_in.abs_diff(step.amount_in as u128) <= 1);
+        assert_eq!(amount_out, step.amount_out as u128);
+    }
+}
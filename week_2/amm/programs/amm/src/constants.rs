@@ -3,3 +3,74 @@ use anchor_lang::prelude::*;
 
 #[constant]
 pub const SEED: &str = "anchor";
+
+/// Highest swap fee a pool can be created or updated with (in basis points, 1_000 = 10%)
+#[constant]
+pub const MAX_FEE_BPS: u16 = 1_000;
+
+/// Highest share of the swap fee the protocol can take (in basis points of the fee, 5_000 = half)
+#[constant]
+pub const MAX_PROTOCOL_FEE_BPS: u16 = 5_000;
+
+/// Number of price observations kept in each pool's oracle ring buffer
+#[constant]
+pub const OBSERVATION_COUNT: usize = 32;
+
+/// Smallest StableSwap amplification coefficient
+#[constant]
+pub const MIN_AMP: u64 = 1;
+
+/// Largest StableSwap amplification coefficient
+#[constant]
+pub const MAX_AMP: u64 = 1_000_000;
+
+/// Shortest time an amplification ramp can take (one day)
+#[constant]
+pub const MIN_RAMP_DURATION: i64 = 86_400;
+
+/// Largest factor the amplification coefficient can move by in a single ramp
+#[constant]
+pub const MAX_AMP_CHANGE: u64 = 10;
+
+/// Lowest tick of a concentrated-liquidity pool (price 1.0001^MIN_TICK ≈ 2^-64)
+#[constant]
+pub const MIN_TICK: i32 = -443_636;
+
+/// Highest tick of a concentrated-liquidity pool (price 1.0001^MAX_TICK ≈ 2^64)
+#[constant]
+pub const MAX_TICK: i32 = 443_636;
+
+/// Widest tick spacing a concentrated-liquidity pool can be created with
+#[constant]
+pub const MAX_TICK_SPACING: u16 = 16_384;
+
+/// Number of initialized ticks a concentrated-liquidity pool can track at once
+#[constant]
+pub const MAX_POOL_TICKS: usize = 64;
+
+/// Least liquidity a concentrated-liquidity position can hold unless it is empty
+/// Every position takes up to two of the pool's `MAX_POOL_TICKS` slots, so this makes
+/// filling them with dust positions tie up real capital
+#[constant]
+pub const MIN_POSITION_LIQUIDITY: u128 = 100_000_000;
+
+/// Most pools a single `route_swap` can go through
+#[constant]
+pub const MAX_ROUTE_HOPS: usize = 4;
+
+/// LP tokens minted to the pool's lock account on the first deposit and never withdrawable,
+/// so the LP supply can't return to zero and the share price can't be inflated cheaply
+#[constant]
+pub const MINIMUM_LIQUIDITY: u64 = amm_quote::MINIMUM_LIQUIDITY;
+
+/// Most fee tiers the pool factory can allow at once
+#[constant]
+pub const MAX_FEE_TIERS: usize = 8;
+
+/// Seconds for the volatility behind a dynamic fee to decay by half
+#[constant]
+pub const VOLATILITY_HALF_LIFE: i64 = amm_quote::VOLATILITY_HALF_LIFE;
+
+/// Basis points of recent price movement that add one basis point to a dynamic fee
+#[constant]
+pub const VOLATILITY_FEE_DIVISOR: u64 = amm_quote::VOLATILITY_FEE_DIVISOR;
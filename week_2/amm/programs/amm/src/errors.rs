@@ -1,5 +1,5 @@
 use anchor_lang::error_code;
-use constant_product_curve::CurveError;
+use amm_quote::QuoteError;
 
 #[error_code]
 pub enum AmmError {
@@ -25,7 +25,7 @@ pub enum AmmError {
     BumpError,
     #[msg("Curve error.")]
     CurveError,
-    #[msg("Fee is greater than 100%. This is not a very good deal.")]
+    #[msg("Fee is greater than the maximum allowed. This is not a very good deal.")]
     InvalidFee,
     #[msg("Invalid update authority.")]
     InvalidAuthority,
@@ -39,18 +39,72 @@ pub enum AmmError {
     InsufficientBalance,
     #[msg("Zero balance.")]
     ZeroBalance,
+    #[msg("Mint has a Token-2022 extension the pool does not support.")]
+    UnsupportedMintExtension,
+    #[msg("Protocol fee share is greater than the maximum allowed.")]
+    InvalidProtocolFee,
+    #[msg("No protocol treasury set.")]
+    NoTreasurySet,
+    #[msg("Treasury does not match the pool config.")]
+    InvalidTreasury,
+    #[msg("Amplification coefficient is out of range.")]
+    InvalidAmp,
+    #[msg("Amplification ramp is too short or changes the coefficient too much.")]
+    InvalidRamp,
+    #[msg("Instruction is not supported by this pool's curve.")]
+    InvalidCurve,
+    #[msg("Tick spacing is out of range.")]
+    InvalidTickSpacing,
+    #[msg("Tick range is invalid for this pool.")]
+    InvalidTickRange,
+    #[msg("Price is out of range.")]
+    InvalidPrice,
+    #[msg("Pool has no room for another initialized tick.")]
+    TickLimitReached,
+    #[msg("Position still holds liquidity or uncollected fees.")]
+    PositionNotEmpty,
+    #[msg("Route is empty, too long, visits a pool twice or does not match its accounts.")]
+    InvalidRoute,
+    #[msg("First deposit must be worth more than the locked minimum liquidity.")]
+    InsufficientInitialLiquidity,
+    #[msg("Factory pools need mint X to sort before mint Y.")]
+    MintsNotSorted,
+    #[msg("Fee is not one of the factory's fee tiers.")]
+    FeeTierNotAllowed,
+    #[msg("Fee tier is already allowed.")]
+    FeeTierExists,
+    #[msg("Factory has no room for another fee tier.")]
+    FeeTierLimitReached,
+    #[msg("A flash loan is in progress on this pool.")]
+    FlashLoanActive,
+    #[msg("No flash loan is in progress on this pool.")]
+    NoFlashLoan,
+    #[msg("Flash borrow must be a top-level instruction followed by flash_repay for the same pool.")]
+    MissingFlashRepay,
+    #[msg("Reward period is too short, or too little is funded to emit anything per second.")]
+    InvalidRewardPeriod,
+    #[msg("Position liquidity must be zero or at least the minimum.")]
+    PositionTooSmall,
+    #[msg("Factory pools keep the fee of their tier and the factory as their authority.")]
+    FactoryPool,
+    #[msg("Flash loans are disabled for this pool.")]
+    FlashLoansDisabled,
 }
 
-impl From<CurveError> for AmmError {
-    fn from(error: CurveError) -> AmmError {
+impl From<QuoteError> for AmmError {
+    fn from(error: QuoteError) -> AmmError {
         match error {
-            CurveError::InvalidPrecision => AmmError::InvalidPrecision,
-            CurveError::Overflow => AmmError::Overflow,
-            CurveError::Underflow => AmmError::Underflow,
-            CurveError::InvalidFeeAmount => AmmError::InvalidFee,
-            CurveError::InsufficientBalance => AmmError::InsufficientBalance,
-            CurveError::ZeroBalance => AmmError::ZeroBalance,
-            CurveError::SlippageLimitExceeded => AmmError::SlippageExceeded,
+            QuoteError::Overflow => AmmError::Overflow,
+            QuoteError::Underflow => AmmError::Underflow,
+            QuoteError::ZeroBalance => AmmError::ZeroBalance,
+            QuoteError::NoLiquidityInPool => AmmError::NoLiquidityInPool,
+            QuoteError::InsufficientBalance => AmmError::InsufficientBalance,
+            QuoteError::InvalidFee => AmmError::InvalidFee,
+            QuoteError::InvalidAmount => AmmError::InvalidAmount,
+            QuoteError::InvalidCurve => AmmError::InvalidCurve,
+            QuoteError::NotConverged => AmmError::CurveError,
+            QuoteError::InsufficientInitialLiquidity => AmmError::InsufficientInitialLiquidity,
+            QuoteError::FlashLoanActive => AmmError::FlashLoanActive,
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,191 @@
+use anchor_lang::prelude::*;
+
+use crate::states::CurveType;
+
+/// Emitted when a pool is created, by `initialize`, `create_pool` or `initialize_concentrated`.
+#[event]
+pub struct PoolInitialized {
+    /// Configuration account of the new pool.
+    pub config: Pubkey,
+
+    /// Seed the pool's PDAs are derived from.
+    pub seed: u64,
+
+    /// First token in the pair.
+    pub mint_x: Pubkey,
+
+    /// Second token in the pair.
+    pub mint_y: Pubkey,
+
+    /// Swap fee the pool starts with (in basis points).
+    pub fee: u16,
+
+    /// Invariant used to price swaps.
+    pub curve_type: CurveType,
+
+    /// Authority allowed to update the pool, if any.
+    pub authority: Option<Pubkey>,
+}
+
+/// Emitted by every constant product or StableSwap swap.
+#[event]
+pub struct Swap {
+    /// Pool the swap traded against.
+    pub config: Pubkey,
+
+    /// true if token X was sold for Y, false if Y was sold for X.
+    pub is_x: bool,
+
+    /// Input the vault received, fee included.
+    pub amount_in: u64,
+
+    /// Output that left the pool, before any Token-2022 transfer fee.
+    pub amount_out: u64,
+
+    /// Swap fee charged, in the input token.
+    pub fee: u64,
+
+    /// Share of `fee` set aside for the protocol rather than added to the pool.
+    pub protocol_fee: u64,
+
+    /// Swap fee rate applied (in basis points), which moves with volatility
+    /// when the pool has a dynamic fee.
+    pub fee_bps: u16,
+
+    /// Pool reserves of token X after the swap, excluding protocol fees.
+    pub reserve_x: u64,
+
+    /// Pool reserves of token Y after the swap, excluding protocol fees.
+    pub reserve_y: u64,
+}
+
+/// Emitted by every concentrated-liquidity swap.
+#[event]
+pub struct SwapConcentrated {
+    /// Pool the swap traded against.
+    pub config: Pubkey,
+
+    /// true if token X was sold for Y, false if Y was sold for X.
+    pub is_x: bool,
+
+    /// Input the vault received, fee included.
+    pub amount_in: u64,
+
+    /// Output that left the pool, before any Token-2022 transfer fee.
+    pub amount_out: u64,
+
+    /// Swap fee charged, in the input token.
+    pub fee: u64,
+
+    /// Share of `fee` set aside for the protocol rather than paid to positions.
+    pub protocol_fee: u64,
+
+    /// Square root of the price after the swap, as a Q64.64 number.
+    pub sqrt_price: u128,
+
+    /// Tick the price sits in after the swap.
+    pub tick_current: i32,
+
+    /// Liquidity active at the price after the swap.
+    pub liquidity: u128,
+}
+
+/// Emitted by every deposit, balanced or single-sided.
+#[event]
+pub struct LiquidityAdded {
+    /// Pool the liquidity was added to.
+    pub config: Pubkey,
+
+    /// Depositor the LP tokens were minted to.
+    pub user: Pubkey,
+
+    /// Token X the vault received.
+    pub amount_x: u64,
+
+    /// Token Y the vault received.
+    pub amount_y: u64,
+
+    /// LP tokens minted to the user, excluding any locked minimum liquidity.
+    pub lp_amount: u64,
+
+    /// Pool reserves of token X after the deposit, excluding protocol fees.
+    pub reserve_x: u64,
+
+    /// Pool reserves of token Y after the deposit, excluding protocol fees.
+    pub reserve_y: u64,
+}
+
+/// Emitted by every withdrawal, balanced or single-sided.
+#[event]
+pub struct LiquidityRemoved {
+    /// Pool the liquidity was removed from.
+    pub config: Pubkey,
+
+    /// Withdrawer whose LP tokens were burned.
+    pub user: Pubkey,
+
+    /// Token X the vault sent, before any Token-2022 transfer fee.
+    pub amount_x: u64,
+
+    /// Token Y the vault sent, before any Token-2022 transfer fee.
+    pub amount_y: u64,
+
+    /// LP tokens burned.
+    pub lp_amount: u64,
+
+    /// Pool reserves of token X after the withdrawal, excluding protocol fees.
+    pub reserve_x: u64,
+
+    /// Pool reserves of token Y after the withdrawal, excluding protocol fees.
+    pub reserve_y: u64,
+}
+
+/// Emitted by `increase_liquidity`.
+#[event]
+pub struct PositionIncreased {
+    /// Pool the liquidity was added to.
+    pub config: Pubkey,
+
+    /// Position the liquidity was added to.
+    pub position: Pubkey,
+
+    /// Owner of the position.
+    pub owner: Pubkey,
+
+    /// Liquidity added.
+    pub liquidity: u128,
+
+    /// Token X the vault received.
+    pub amount_x: u64,
+
+    /// Token Y the vault received.
+    pub amount_y: u64,
+
+    /// Liquidity of the position after the increase.
+    pub position_liquidity: u128,
+}
+
+/// Emitted by `decrease_liquidity`.
+#[event]
+pub struct PositionDecreased {
+    /// Pool the liquidity was removed from.
+    pub config: Pubkey,
+
+    /// Position the liquidity was removed from.
+    pub position: Pubkey,
+
+    /// Owner of the position.
+    pub owner: Pubkey,
+
+    /// Liquidity removed.
+    pub liquidity: u128,
+
+    /// Token X the vault sent, before any Token-2022 transfer fee.
+    pub amount_x: u64,
+
+    /// Token Y the vault sent, before any Token-2022 transfer fee.
+    pub amount_y: u64,
+
+    /// Liquidity of the position after the decrease.
+    pub position_liquidity: u128,
+}
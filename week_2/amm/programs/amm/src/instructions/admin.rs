@@ -0,0 +1,194 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{
+    MAX_AMP, MAX_AMP_CHANGE, MAX_FEE_BPS, MAX_PROTOCOL_FEE_BPS, MIN_AMP, MIN_RAMP_DURATION,
+};
+use crate::errors::AmmError;
+use crate::states::{Config, CurveType, Factory};
+
+/// Accounts required for the pool authority to manage an AMM pool
+/// Shared by `lock`, `unlock`, `update_fee`, `set_protocol_fee`, `enable_flash_loans`, `disable_flash_loans`,
+/// `set_flash_loan_fee`, `enable_dynamic_fee`, `disable_dynamic_fee`, `set_treasury`, `ramp_amp`, `stop_ramp_amp`,
+/// `set_authority` and `renounce_authority`
+#[derive(Accounts)]
+pub struct Admin<'info> {
+    /// The current pool authority, or the factory authority for factory pools (must sign the transaction)
+    pub authority: Signer<'info>,
+
+    /// The AMM pool configuration account being updated
+    /// Mutable because every admin instruction changes one of its fields
+    /// Pools created without an authority can never be updated
+    #[account(
+        mut,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+        constraint = config.authority.is_some() @ AmmError::NoAuthoritySet,
+        constraint = config.is_authority(authority.key(), factory.as_ref()) @ AmmError::InvalidAuthority,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The pool factory, required to manage pools created with `create_pool`
+    /// The only `Factory` account is the one at `[b"factory"]`, so its type is enough
+    pub factory: Option<Account<'info, Factory>>,
+}
+
+impl<'info> Admin<'info> {
+    /// Locks the pool, disabling deposits, withdrawals and swaps
+    /// Useful to pause trading during an incident or a migration
+    pub fn lock(&mut self) -> Result<()> {
+        self.config.locked = true;
+        Ok(())
+    }
+
+    /// Unlocks the pool, re-enabling deposits, withdrawals and swaps
+    pub fn unlock(&mut self) -> Result<()> {
+        self.config.locked = false;
+        Ok(())
+    }
+
+    /// Updates the swap fee charged on every trade
+    /// Factory pools keep the fee of their tier
+    ///
+    /// # Arguments
+    /// * `fee` - New trading fee in basis points, at most `MAX_FEE_BPS`
+    pub fn update_fee(&mut self, fee: u16) -> Result<()> {
+        require!(!self.config.is_factory_pool(self.factory.as_ref()), AmmError::FactoryPool);
+        require!(fee <= MAX_FEE_BPS, AmmError::InvalidFee);
+        self.config.fee = fee;
+        Ok(())
+    }
+
+    /// Updates the share of each swap fee set aside for the protocol
+    /// Fees already accrued are unaffected
+    ///
+    /// # Arguments
+    /// * `protocol_fee` - New share in basis points of the swap fee, at most `MAX_PROTOCOL_FEE_BPS`
+    pub fn set_protocol_fee(&mut self, protocol_fee: u16) -> Result<()> {
+        require!(protocol_fee <= MAX_PROTOCOL_FEE_BPS, AmmError::InvalidProtocolFee);
+        self.config.protocol_fee = protocol_fee;
+        Ok(())
+    }
+
+    /// Lets `flash_borrow` lend from the vaults, at `flash_loan_fee`
+    /// Concentrated pools can't lend, their liquidity isn't in full-range reserves
+    pub fn enable_flash_loans(&mut self) -> Result<()> {
+        require!(self.config.curve_type != CurveType::Concentrated, AmmError::InvalidCurve);
+        self.config.flash_loans = true;
+        Ok(())
+    }
+
+    /// Stops `flash_borrow` from lending
+    pub fn disable_flash_loans(&mut self) -> Result<()> {
+        self.config.flash_loans = false;
+        Ok(())
+    }
+
+    /// Updates the fee charged on flash loans
+    ///
+    /// # Arguments
+    /// * `flash_loan_fee` - New fee in basis points of the amount borrowed, at most `MAX_FEE_BPS`
+    pub fn set_flash_loan_fee(&mut self, flash_loan_fee: u16) -> Result<()> {
+        require!(flash_loan_fee <= MAX_FEE_BPS, AmmError::InvalidFee);
+        self.config.flash_loan_fee = flash_loan_fee;
+        Ok(())
+    }
+
+    /// Switches swaps to a fee between `min_fee` and `max_fee` that follows volatility
+    ///
+    /// Concentrated pools and factory pools keep their static fee. Calling this again
+    /// with new bounds restarts volatility from zero.
+    ///
+    /// # Arguments
+    /// * `min_fee` - Fee in basis points charged while the price is still
+    /// * `max_fee` - Highest fee in basis points, at most `MAX_FEE_BPS`
+    pub fn enable_dynamic_fee(&mut self, min_fee: u16, max_fee: u16) -> Result<()> {
+        require!(!self.config.is_factory_pool(self.factory.as_ref()), AmmError::FactoryPool);
+        require!(min_fee <= max_fee && max_fee <= MAX_FEE_BPS, AmmError::InvalidFee);
+        require!(self.config.curve_type != CurveType::Concentrated, AmmError::InvalidCurve);
+
+        self.config.dynamic_fee = true;
+        self.config.min_fee = min_fee;
+        self.config.max_fee = max_fee;
+        self.config.volatility = 0;
+        self.config.volatility_updated = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// Switches swaps back to the static `fee`
+    pub fn disable_dynamic_fee(&mut self) -> Result<()> {
+        self.config.dynamic_fee = false;
+        self.config.volatility = 0;
+        Ok(())
+    }
+
+    /// Sets the owner of the accounts protocol fees are collected to
+    ///
+    /// # Arguments
+    /// * `treasury` - Wallet or PDA that receives collected protocol fees
+    pub fn set_treasury(&mut self, treasury: Pubkey) -> Result<()> {
+        self.config.treasury = Some(treasury);
+        Ok(())
+    }
+
+    /// Starts moving the StableSwap amplification coefficient linearly towards `target_amp`
+    /// Ramps are slow and bounded so LPs can't be front-run by a sudden curve change
+    ///
+    /// # Arguments
+    /// * `target_amp` - Coefficient reached at `ramp_end`, within `MAX_AMP_CHANGE` times the current one
+    /// * `ramp_end` - Unix timestamp the ramp ends at, at least `MIN_RAMP_DURATION` from now
+    pub fn ramp_amp(&mut self, target_amp: u64, ramp_end: i64) -> Result<()> {
+        require!(self.config.curve_type == CurveType::StableSwap, AmmError::InvalidCurve);
+        require!((MIN_AMP..=MAX_AMP).contains(&target_amp), AmmError::InvalidAmp);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(ramp_end >= now.saturating_add(MIN_RAMP_DURATION), AmmError::InvalidRamp);
+
+        // Start from wherever a ramp in progress has got to
+        let current = self.config.amp(now);
+        require!(
+            target_amp <= current.saturating_mul(MAX_AMP_CHANGE)
+                && target_amp.saturating_mul(MAX_AMP_CHANGE) >= current,
+            AmmError::InvalidRamp
+        );
+
+        self.config.amp_initial = current;
+        self.config.amp_target = target_amp;
+        self.config.amp_ramp_start = now;
+        self.config.amp_ramp_end = ramp_end;
+        Ok(())
+    }
+
+    /// Stops an amplification ramp, freezing the coefficient at its current value
+    pub fn stop_ramp_amp(&mut self) -> Result<()> {
+        require!(self.config.curve_type == CurveType::StableSwap, AmmError::InvalidCurve);
+
+        let now = Clock::get()?.unix_timestamp;
+        let current = self.config.amp(now);
+        self.config.amp_initial = current;
+        self.config.amp_target = current;
+        self.config.amp_ramp_start = now;
+        self.config.amp_ramp_end = now;
+        Ok(())
+    }
+
+    /// Hands the pool authority to a new key
+    /// Factory pools stay with the factory; `set_factory_authority` hands them all over
+    ///
+    /// # Arguments
+    /// * `new_authority` - Key that will be allowed to update the pool from now on
+    pub fn set_authority(&mut self, new_authority: Pubkey) -> Result<()> {
+        // Leaving the factory would free the pool to change the fee of its tier
+        require!(!self.config.is_factory_pool(self.factory.as_ref()), AmmError::FactoryPool);
+        self.config.authority = Some(new_authority);
+        Ok(())
+    }
+
+    /// Removes the pool authority for good
+    /// The pool becomes immutable: it can no longer be locked, unlocked or have its fee changed
+    pub fn renounce_authority(&mut self) -> Result<()> {
+        // Renouncing a locked pool would freeze its liquidity forever
+        require!(!self.config.locked, AmmError::PoolLocked);
+        self.config.authority = None;
+        Ok(())
+    }
+}
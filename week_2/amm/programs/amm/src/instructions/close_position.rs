@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+use crate::states::Position;
+
+/// Accounts required for closing an empty position and reclaiming its rent
+#[derive(Accounts)]
+pub struct ClosePosition<'info> {
+    /// The position owner (must sign the transaction)
+    /// Mutable because the position's rent is returned to them
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// The position being closed
+    /// Must hold no liquidity and no uncollected fees
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner,
+        constraint = position.liquidity == 0
+            && position.fees_owed_x == 0
+            && position.fees_owed_y == 0 @ AmmError::PositionNotEmpty,
+    )]
+    pub position: Account<'info, Position>,
+}
+
+impl<'info> ClosePosition<'info> {
+    /// Closes the position; Anchor returns its rent to the owner once the checks above pass
+    ///
+    /// # Returns
+    /// * `Result<()>` - Ok if successful, error otherwise
+    pub fn close_position(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::errors::AmmError;
+use crate::states::Config;
+
+/// Accounts required for sending accrued protocol fees to the treasury
+/// Anyone may call this: the fees can only ever go to the treasury set in the config
+#[derive(Accounts)]
+pub struct CollectProtocolFees<'info> {
+    /// The caller (must sign the transaction)
+    /// Mutable because they pay for the treasury token accounts if they don't exist yet
+    #[account(mut)]
+    pub collector: Signer<'info>,
+
+    /// The treasury wallet set by the pool authority
+    /// CHECK: only used as the owner of the treasury token accounts, validated against the config
+    #[account(
+        constraint = config.treasury.is_some() @ AmmError::NoTreasurySet,
+        constraint = config.treasury == Some(treasury.key()) @ AmmError::InvalidTreasury,
+    )]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// The mint account for token X in the trading pair
+    #[account(mint::token_program = token_program_x)]
+    pub mint_x: InterfaceAccount<'info, Mint>,
+
+    /// The mint account for token Y in the trading pair
+    #[account(mint::token_program = token_program_y)]
+    pub mint_y: InterfaceAccount<'info, Mint>,
+
+    /// The AMM pool configuration account
+    /// Mutable because the accrued protocol fees are reset once collected
+    #[account(
+        mut,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+        has_one = mint_x,  // Ensures mint_x matches the one in config
+        has_one = mint_y,  // Ensures mint_y matches the one in config
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The vault holding token X, including the accrued X protocol fees
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_x,
+    )]
+    pub vault_x: InterfaceAccount<'info, TokenAccount>,
+
+    /// The vault holding token Y, including the accrued Y protocol fees
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_y,
+    )]
+    pub vault_y: InterfaceAccount<'info, TokenAccount>,
+
+    /// Treasury's token account for token X
+    /// Will be created if it doesn't exist, collector pays for creation
+    #[account(
+        init_if_needed,
+        payer = collector,
+        associated_token::mint = mint_x,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program_x,
+    )]
+    pub treasury_ata_x: InterfaceAccount<'info, TokenAccount>,
+
+    /// Treasury's token account for token Y
+    /// Will be created if it doesn't exist, collector pays for creation
+    #[account(
+        init_if_needed,
+        payer = collector,
+        associated_token::mint = mint_y,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program_y,
+    )]
+    pub treasury_ata_y: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token program owning mint_x (SPL Token or Token-2022)
+    pub token_program_x: Interface<'info, TokenInterface>,
+    /// Token program owning mint_y (SPL Token or Token-2022)
+    pub token_program_y: Interface<'info, TokenInterface>,
+    /// Associated Token program for ATA operations
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CollectProtocolFees<'info> {
+    /// Sends all accrued protocol fees from the vaults to the treasury
+    /// and resets the accrued amounts in the config
+    ///
+    /// # Returns
+    /// * `Result<()>` - Ok if successful, error otherwise
+    pub fn collect_protocol_fees(&mut self) -> Result<()> {
+        // The share of a flash loan fee in progress isn't in the vaults yet
+        require!(!self.config.flash_loan_active(), AmmError::FlashLoanActive);
+
+        let (fees_x, fees_y) = (self.config.protocol_fees_x, self.config.protocol_fees_y);
+        require!(fees_x != 0 || fees_y != 0, AmmError::ZeroBalance);
+
+        // Reset before transferring so the config never counts fees that already left the vaults
+        self.config.protocol_fees_x = 0;
+        self.config.protocol_fees_y = 0;
+
+        if fees_x != 0 {
+            self.send_to_treasury(true, fees_x)?;
+        }
+        if fees_y != 0 {
+            self.send_to_treasury(false, fees_y)?;
+        }
+
+        Ok(())
+    }
+
+    /// Transfers tokens from a vault to the matching treasury token account
+    ///
+    /// # Arguments
+    /// * `is_x` - true for token X, false for token Y
+    /// * `amount` - Amount of tokens to transfer
+    ///
+    /// # Returns
+    /// * `Result<()>` - Ok if successful, error otherwise
+    pub fn send_to_treasury(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        // Select appropriate accounts based on token type
+        let (from, to, mint, decimals, cpi_program) = match is_x {
+            true => (
+                self.vault_x.to_account_info(),         // Transfer from vault X
+                self.treasury_ata_x.to_account_info(),  // Transfer to treasury's X account
+                self.mint_x.to_account_info(),          // Token X mint
+                self.mint_x.decimals,                   // Token X decimals
+                self.token_program_x.to_account_info(), // Token X program
+            ),
+            false => (
+                self.vault_y.to_account_info(),         // Transfer from vault Y
+                self.treasury_ata_y.to_account_info(),  // Transfer to treasury's Y account
+                self.mint_y.to_account_info(),          // Token Y mint
+                self.mint_y.decimals,                   // Token Y decimals
+                self.token_program_y.to_account_info(), // Token Y program
+            ),
+        };
+
+        // Set up transfer instruction accounts
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            mint,
+            authority: self.config.to_account_info(),  // Config PDA signs the transfer
+        };
+
+        // Create signer seeds for config PDA
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"config",
+            &self.config.seed.to_le_bytes(),
+            &[self.config.config_bump],
+        ]];
+
+        // Create CPI context with PDA signer
+        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        // Execute the transfer with amount and decimal validation
+        transfer_checked(cpi_context, amount, decimals)
+    }
+}
@@ -0,0 +1,222 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::Token,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::constants::{MAX_AMP, MIN_AMP};
+use crate::errors::AmmError;
+use crate::events;
+use crate::states::{Config, CurveType, Factory, Oracle, Pair};
+use crate::utils::validate_mint;
+
+/// Accounts required for creating the canonical pool of a mint pair, fee tier and curve
+/// Same pool accounts as `initialize`, plus the factory and the pair's registry entry,
+/// whose address clients derive from the sorted mints, the fee and the curve to find the pool
+#[derive(Accounts)]
+#[instruction(seed: u64, fee: u16, curve_type: CurveType)]
+pub struct CreatePool<'info> {
+    /// Whoever creates the pool (must sign the transaction)
+    /// Mutable because they will pay for account creation costs
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    /// The pool factory, read for its fee tiers
+    #[account(
+        seeds = [b"factory"],
+        bump = factory.bump,
+    )]
+    pub factory: Account<'info, Factory>,
+
+    /// The mint account for token X, the lower of the two mints
+    /// May be owned by either the SPL Token or the Token-2022 program
+    #[account(mint::token_program = token_program_x)]
+    pub mint_x: InterfaceAccount<'info, Mint>,
+
+    /// The mint account for token Y, the higher of the two mints
+    /// May be owned by either the SPL Token or the Token-2022 program
+    #[account(mint::token_program = token_program_y)]
+    pub mint_y: InterfaceAccount<'info, Mint>,
+
+    /// Registry entry of the pair, fee tier and curve
+    /// Can only be created once, which makes the pool canonical
+    #[account(
+        init,
+        payer = creator,
+        seeds = [
+            b"pair",
+            mint_x.key().as_ref(),
+            mint_y.key().as_ref(),
+            fee.to_le_bytes().as_ref(),
+            &[curve_type as u8],
+        ],
+        bump,
+        space = 8 + Pair::INIT_SPACE
+    )]
+    pub pair: Account<'info, Pair>,
+
+    /// The main configuration account for the pool
+    /// Any unused seed works; the registry entry records which one was taken
+    #[account(
+        init,
+        payer = creator,
+        seeds = [b"config", seed.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + Config::INIT_SPACE
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The TWAP price oracle for this pool
+    /// Boxed because its observation ring buffer is too large for the stack
+    #[account(
+        init,
+        payer = creator,
+        seeds = [b"oracle", config.key().as_ref()],
+        bump,
+        space = 8 + Oracle::INIT_SPACE
+    )]
+    pub oracle: Box<Account<'info, Oracle>>,
+
+    /// The LP token mint for this pool, controlled by the config PDA
+    #[account(
+        init,
+        payer = creator,
+        mint::decimals = 6,
+        mint::authority = config.key(),
+        mint::token_program = token_program,
+        seeds = [b"lp", config.key().as_ref()],
+        bump,
+    )]
+    pub mint_lp: InterfaceAccount<'info, Mint>,
+
+    /// The vault that will hold all deposited token X
+    #[account(
+        init,
+        payer = creator,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_x,
+    )]
+    pub vault_x: InterfaceAccount<'info, TokenAccount>,
+
+    /// The vault that will hold all deposited token Y
+    #[account(
+        init,
+        payer = creator,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_y,
+    )]
+    pub vault_y: InterfaceAccount<'info, TokenAccount>,
+
+    /// SPL Token program for the LP mint
+    pub token_program: Program<'info, Token>,
+    /// Token program owning mint_x (SPL Token or Token-2022)
+    pub token_program_x: Interface<'info, TokenInterface>,
+    /// Token program owning mint_y (SPL Token or Token-2022)
+    pub token_program_y: Interface<'info, TokenInterface>,
+    /// Associated Token program for ATA operations
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreatePool<'info> {
+    /// Creates the canonical pool of a mint pair, fee tier and curve and registers it
+    ///
+    /// The pool works exactly like one made with `initialize`, except that its authority
+    /// is the factory itself rather than the creator, so nobody who happened to create
+    /// a canonical pool can lock it, and whoever manages the factory manages the pool.
+    /// Its fee stays the fee of its tier.
+    ///
+    /// # Arguments
+    /// * `seed` - Unused seed for the pool's config PDA
+    /// * `fee` - Trading fee in basis points, one of the factory's fee tiers
+    /// * `curve_type` - Invariant used to price swaps
+    /// * `amp` - StableSwap amplification coefficient, ignored for constant product pools
+    /// * `bumps` - Canonical bump values for PDAs (provided by Anchor)
+    ///
+    /// # Returns
+    /// * `Result<()>` - Ok if successful, error otherwise
+    pub fn create_pool(
+        &mut self,
+        seed: u64,
+        fee: u16,
+        curve_type: CurveType,
+        amp: u64,
+        bumps: &CreatePoolBumps,
+    ) -> Result<()> {
+        // One ordering per pair, so X/Y and Y/X can't both be registered
+        require!(self.mint_x.key() < self.mint_y.key(), AmmError::MintsNotSorted);
+        require!(self.factory.fee_tiers.contains(&fee), AmmError::FeeTierNotAllowed);
+
+        // Only StableSwap pools use an amplification coefficient
+        let amp = match curve_type {
+            CurveType::ConstantProduct => 0,
+            CurveType::StableSwap => {
+                require!((MIN_AMP..=MAX_AMP).contains(&amp), AmmError::InvalidAmp);
+                amp
+            }
+            // Concentrated pools need the extra state created by initialize_concentrated
+            CurveType::Concentrated => return err!(AmmError::InvalidCurve),
+        };
+        let now = Clock::get()?.unix_timestamp;
+
+        // Reject Token-2022 mints whose extensions would trap or drain the vaults
+        validate_mint(&self.mint_x)?;
+        validate_mint(&self.mint_y)?;
+
+        self.config.set_inner(Config {
+            seed,
+            authority: Some(self.factory.key()), // Canonical pools are managed through the factory
+            mint_x: self.mint_x.key(),
+            mint_y: self.mint_y.key(),
+            fee,
+            dynamic_fee: false,
+            min_fee: fee,
+            max_fee: fee,
+            volatility: 0,
+            volatility_updated: now,
+            protocol_fee: 0,
+            treasury: None,
+            protocol_fees_x: 0,
+            protocol_fees_y: 0,
+            flash_loans: false,
+            flash_loan_fee: fee,
+            flash_loan_x: 0,
+            flash_loan_y: 0,
+            curve_type,
+            amp_initial: amp,
+            amp_target: amp,
+            amp_ramp_start: now,
+            amp_ramp_end: now,
+            locked: false,
+            config_bump: bumps.config,
+            lp_bump: bumps.mint_lp,
+        });
+
+        self.oracle.set_inner(Oracle::new(self.config.key(), now, bumps.oracle));
+
+        self.pair.set_inner(Pair {
+            mint_x: self.mint_x.key(),
+            mint_y: self.mint_y.key(),
+            fee,
+            curve_type,
+            config: self.config.key(),
+            bump: bumps.pair,
+        });
+
+        emit!(events::PoolInitialized {
+            config: self.config.key(),
+            seed,
+            mint_x: self.mint_x.key(),
+            mint_y: self.mint_y.key(),
+            fee,
+            curve_type,
+            authority: self.config.authority,
+        });
+
+        Ok(())
+    }
+}
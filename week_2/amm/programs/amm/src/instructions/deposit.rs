@@ -1,173 +1,546 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    associated_token:AssociatedToken,
-    token::{mint_to, transfer_checked, Mint, MintTo, Token, TokenAccount,TransferChecked},
+    associated_token::AssociatedToken,
+    token::Token,
+    token_interface::{
+        mint_to, transfer_checked, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked,
+    },
 };
-use contant_product_curve::ConstantProduct;
 
-use crate::states::Config;
+use crate::constants::MINIMUM_LIQUIDITY;
 use crate::errors::AmmError;
+use crate::events;
+use crate::quote::{quote_deposit, quote_exact_in, quote_initial_deposit};
+use crate::states::{Config, Oracle};
+use crate::utils::{amount_before_fee, check_expiration, full_mul, mul_div, sqrt_u256};
 
+/// Accounts required for depositing liquidity into the AMM pool
+/// This struct defines all the accounts needed to provide both tokens and receive LP tokens
 #[derive(Accounts)]
-pub struct Deposit<'info>{
+pub struct Deposit<'info> {
+    /// The user who is providing liquidity (must sign the transaction)
+    /// Mutable because they may need to pay for LP token account creation
     #[account(mut)]
-    pub user:Signer<'info>,
-
-
-    pub mint_x: Account<'info, Mint>,
-
-    pub mint_y: Account<'info, Mint>,
-
+    pub user: Signer<'info>,
+
+    /// The mint account for token X in the trading pair
+    /// Immutable as we only need to read mint information for transfers
+    #[account(mint::token_program = token_program_x)]
+    pub mint_x: InterfaceAccount<'info, Mint>,
+
+    /// The mint account for token Y in the trading pair
+    /// Immutable as we only need to read mint information for transfers
+    #[account(mint::token_program = token_program_y)]
+    pub mint_y: InterfaceAccount<'info, Mint>,
+
+    /// The AMM pool configuration account
+    /// Contains pool settings and references to the token mints
+    /// Uses PDA derived from "config" seed and config.seed
+    /// Mutable because `deposit_single` accrues the protocol's share of its internal swap fee
     #[account(
-        seeds =[b"config", config.seed.to_le_bytes().as_ref()],
+        mut,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
         bump = config.config_bump,
-        has_one = mint_x,
-        has_one = mint_y,
+        has_one = mint_x,  // Ensures mint_x matches the one in config
+        has_one = mint_y,  // Ensures mint_y matches the one in config
     )]
     pub config: Account<'info, Config>,
 
+    /// The TWAP price oracle for this pool
+    /// Mutable because the price accumulators are updated before the reserves change
+    #[account(
+        mut,
+        seeds = [b"oracle", config.key().as_ref()],
+        bump = oracle.bump,
+    )]
+    pub oracle: Box<Account<'info, Oracle>>,
+
+    /// The LP (Liquidity Provider) token mint
+    /// Mutable because we mint new LP tokens to the user
+    /// Uses PDA derived from "lp" seed and config pubkey
     #[account(
         mut,
-        seed = [b"lp", config.keys().as_ref()],
-        bump = config.lp_bump
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump,
+        mint::token_program = token_program,
     )]
-    pub mint_lp:Account<'info, Mint>,
+    pub mint_lp: InterfaceAccount<'info, Mint>,
 
+    /// The vault that holds all deposited token X
+    /// Mutable because we're depositing tokens into it
+    /// Associated token account owned by the config PDA
     #[account(
         mut,
         associated_token::mint = mint_x,
-        associated_token::authority = conifg,
-        associated_token::token_program = token_program
+        associated_token::authority = config,
+        associated_token::token_program = token_program_x,
     )]
-    pub vault_x: Account<'info, TokenAccount>,
+    pub vault_x: InterfaceAccount<'info, TokenAccount>,
 
+    /// The vault that holds all deposited token Y
+    /// Mutable because we're depositing tokens into it
+    /// Associated token account owned by the config PDA
     #[account(
         mut,
-        associated_token::mint_y,
+        associated_token::mint = mint_y,
         associated_token::authority = config,
-        associated_token::token_program = token_program
+        associated_token::token_program = token_program_y,
     )]
-    pub vault_y: Account<'info, TokenAccount>,
+    pub vault_y: InterfaceAccount<'info, TokenAccount>,
 
+    /// User's token account for token X
+    /// Mutable because we're transferring tokens from it
     #[account(
         mut,
         associated_token::mint = mint_x,
         associated_token::authority = user,
-        associated_token::token_program = token_program
+        associated_token::token_program = token_program_x,
     )]
-    pub user_ata_x:Account<'info, TokenAccount>,
+    pub user_ata_x: InterfaceAccount<'info, TokenAccount>,
 
+    /// User's token account for token Y
+    /// Mutable because we're transferring tokens from it
     #[account(
         mut,
         associated_token::mint = mint_y,
         associated_token::authority = user,
-        associated_token::token_program = token_program
+        associated_token::token_program = token_program_y,
     )]
-    pub user_ata_y: Account<'info, TokenAccount>,
+    pub user_ata_y: InterfaceAccount<'info, TokenAccount>,
 
+    /// User's token account for LP tokens
+    /// Will be created if it doesn't exist, user pays for creation
     #[account(
         init_if_needed,
         payer = user,
         associated_token::mint = mint_lp,
         associated_token::authority = user,
-        associated_token::token_program = token_program
+        associated_token::token_program = token_program,
+    )]
+    pub user_ata_lp: InterfaceAccount<'info, TokenAccount>,
+
+    /// PDA that owns the locked minimum liquidity
+    /// Holds no data and the program never signs for it, so its LP tokens can't move
+    /// CHECK: only used as the authority of `locked_lp`, verified by its seeds
+    #[account(
+        seeds = [b"lock", config.key().as_ref()],
+        bump,
+    )]
+    pub lp_lock: UncheckedAccount<'info>,
+
+    /// LP token account receiving `MINIMUM_LIQUIDITY` on the first deposit
+    /// Will be created if it doesn't exist, user pays for creation
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_lp,
+        associated_token::authority = lp_lock,
+        associated_token::token_program = token_program,
     )]
-    pub user_ata_lp:Account<'info, TokenAccount>,
+    pub locked_lp: InterfaceAccount<'info, TokenAccount>,
 
+    /// SPL Token program for the LP mint
     pub token_program: Program<'info, Token>,
+    /// Token program owning mint_x (SPL Token or Token-2022)
+    pub token_program_x: Interface<'info, TokenInterface>,
+    /// Token program owning mint_y (SPL Token or Token-2022)
+    pub token_program_y: Interface<'info, TokenInterface>,
+    /// Associated Token program for ATA operations
     pub associated_token_program: Program<'info, AssociatedToken>,
+    /// System program for account creation
     pub system_program: Program<'info, System>,
 }
 
-impl<'info> Deposit<'info>{
-
-
+impl<'info> Deposit<'info> {
+    /// Main deposit function that handles liquidity provision
+    ///
+    /// The process follows this sequence:
+    /// 1. Read current LP supply and vault balances
+    /// 2. Calculate the token amounts the vaults must receive for the requested LP tokens
+    /// 3. Add any Token-2022 transfer fee and validate slippage protection
+    /// 4. Transfer both tokens from the user to the vaults
+    /// 5. Mint the LP tokens to the user and emit a `LiquidityAdded` event
+    ///
+    /// Deposits are always proportional to the reserves, which leaves the price unchanged
+    /// on both constant product and StableSwap curves, so they don't depend on the curve type.
+    ///
+    /// The first deposit, the one into a pool with no LP tokens, sets the price with `max_x`
+    /// and `max_y` and is worth `sqrt(x * y)` LP tokens of the vault balances after the
+    /// transfers, whatever `amount` says. Tokens donated before it are part of those balances.
+    /// `MINIMUM_LIQUIDITY` of them are locked for good, so the supply never returns to zero and
+    /// inflating the share price with a donation would cost the attacker almost all of it.
+    ///
+    /// # Arguments
+    /// * `amount` - Amount of LP tokens to mint, ignored on the first deposit
+    /// * `max_x` - Maximum amount of token X the user is willing to send
+    /// * `max_y` - Maximum amount of token Y the user is willing to send
+    /// * `expiration` - Optional unix timestamp after which the deposit is rejected
+    ///
+    /// # Returns
+    /// * `Result<()>` - Ok if successful, error otherwise
     pub fn deposit(
         &mut self,
         amount: u64,
-        max_x:u64,
+        max_x: u64,
         max_y: u64,
-    )-> Result<()> {
-        require!(self.config.locked == false, AmmError::PoolLocked);
-        require!(amount != 0, AmmError::InvalidAmoiunt);
+        expiration: Option<i64>,
+    ) -> Result<()> {
+        // Ensure the transaction didn't land after the user's deadline
+        check_expiration(expiration)?;
+        // Ensure the pool is not locked for deposits
+        require!(!self.config.locked, AmmError::PoolLocked);
+        // Ensure user is requesting some LP tokens
+        require!(amount != 0, AmmError::InvalidAmount);
+
+        // Uncollected protocol fees sit in the vaults but don't belong to LPs
+        let (vault_x, vault_y) = (self.vault_x.amount, self.vault_y.amount);
+        let (reserve_x, reserve_y) = self.config.reserves(vault_x, vault_y)?;
+
+        // Accrue the price from before this instruction into the TWAP oracle
+        self.oracle.update(reserve_x, reserve_y)?;
+
+        // Without LP tokens nobody owns what the vaults hold, whatever was sent to them
+        let first_deposit = self.mint_lp.supply == 0;
+
+        // Calculate how much of each token the user sends and the vaults must receive
+        let (send_x, send_y, need_x, need_y) = match first_deposit {
+            // First deposit: the user sets the initial price with the maximum amounts
+            true => (max_x, max_y, 0, 0),
+            // Normal case: calculate proportional amounts based on the LP tokens requested
+            false => {
+                let amounts = quote_deposit(&self.config, vault_x, vault_y, self.mint_lp.supply, amount)?;
+                (
+                    // Transfer fees are withheld in transit, so send enough for the vault to
+                    // receive the curve amounts
+                    amount_before_fee(&self.mint_x, amounts.amount_x)?,
+                    amount_before_fee(&self.mint_y, amounts.amount_y)?,
+                    amounts.amount_x,
+                    amounts.amount_y,
+                )
+            }
+        };
 
-        let (x,y) = match self.mint_lp.supply ==0 && self.vault_x.amount == 0&& self.vault_y.amount == 0{
-            true => (max_x, max_y),
+        // Slippage protection: ensure the amounts sent stay within the user's limits
+        require!(send_x <= max_x && send_y <= max_y, AmmError::SlippageExceeded);
 
-            false => {
-                let amounts = ConstantProduct::xy_deposit_amounts_from_l(
-                    self.vault_x.amount,
-                    self.vault_y.amount,
-                    self.mint_lp.supply,
-                    amount,
-                    6
-                ).unwrap();
-                (amounts.x, amounts.y)
+        // Transfer both tokens and check the vaults received what the curve requires
+        let received_x = self.deposit_tokens(true, send_x)?;
+        let received_y = self.deposit_tokens(false, send_y)?;
+        require!(received_x >= need_x && received_y >= need_y, AmmError::SlippageExceeded);
+        require!(received_x != 0 && received_y != 0, AmmError::InvalidAmount);
+
+        let reserve_x = reserve_x.checked_add(received_x).ok_or(AmmError::Overflow)?;
+        let reserve_y = reserve_y.checked_add(received_y).ok_or(AmmError::Overflow)?;
+
+        let amount = match first_deposit {
+            // Value the first deposit from the vaults instead of trusting the caller
+            true => {
+                let amount = quote_initial_deposit(reserve_x, reserve_y)?;
+
+                // Lock the minimum liquidity where nobody can withdraw it
+                self.mint_lp_tokens(self.locked_lp.to_account_info(), MINIMUM_LIQUIDITY)?;
+                amount
             }
+            false => amount,
         };
 
-        require!(x <= max_x && y<=max_y, AmmError::SlippageExceeded);
+        self.mint_lp_tokens(self.user_ata_lp.to_account_info(), amount)?;
+
+        emit!(events::LiquidityAdded {
+            config: self.config.key(),
+            user: self.user.key(),
+            amount_x: received_x,
+            amount_y: received_y,
+            lp_amount: amount,
+            reserve_x,
+            reserve_y,
+        });
 
-        self.deposit_tokens(true, x)?;
-        self.deposit_tokens(false, y)?;
-        self.mint_lp_tokens(amount)
+        Ok(())
     }
 
-    pub fn deposit_tokens(&mut self, is_x: bool, amount: u64) ->Result<()>{
-        let(
-            from,
-            to,
-            mint,
-            decimals
-        )= match is_x{
-            true=> (
-                self.user_ata_x.to_account_info(),
-                self.vault_x.to_account_info(),
-                self.mint_x.to_account_info(),
-                self.mint_x.decimals
+    /// Single-sided ("zap") deposit: provides liquidity with only one of the two tokens
+    ///
+    /// The process follows this sequence:
+    /// 1. Transfer the whole input to its vault
+    /// 2. Swap part of it inside the pool for the other token, priced by the pool's curve
+    /// 3. Mint LP tokens for the remaining input and the swap output at the new reserves
+    /// 4. Emit a `LiquidityAdded` event
+    ///
+    /// No tokens leave the vaults: the swap only moves the split between the user's share and
+    /// the pool's. The swapped amount is the one that leaves both halves in the post-swap
+    /// reserve ratio on a constant product curve (see `zap_swap_amount`). StableSwap pools use
+    /// the same split, which is close but not exact, and whatever doesn't match stays in the
+    /// pool; `min_lp_out` bounds what the user accepts either way.
+    ///
+    /// # Arguments
+    /// * `is_x` - true if depositing token X, false if depositing token Y
+    /// * `amount_in` - Amount of the input token to send
+    /// * `min_lp_out` - Minimum amount of LP tokens the user expects to receive
+    /// * `expiration` - Optional unix timestamp after which the deposit is rejected
+    ///
+    /// # Returns
+    /// * `Result<()>` - Ok if successful, error otherwise
+    pub fn deposit_single(
+        &mut self,
+        is_x: bool,
+        amount_in: u64,
+        min_lp_out: u64,
+        expiration: Option<i64>,
+    ) -> Result<()> {
+        // Ensure the transaction didn't land after the user's deadline
+        check_expiration(expiration)?;
+        // Ensure the pool is not locked for deposits
+        require!(!self.config.locked, AmmError::PoolLocked);
+        // Ensure user is depositing a positive amount
+        require!(amount_in != 0, AmmError::InvalidAmount);
+
+        // Uncollected protocol fees sit in the vaults but don't belong to LPs
+        let (reserve_x, reserve_y) = self
+            .config
+            .reserves(self.vault_x.amount, self.vault_y.amount)?;
+        let supply = self.mint_lp.supply;
+
+        // The first deposit sets the price, which a single token can't do
+        require!(supply != 0, AmmError::NoLiquidityInPool);
+
+        // Accrue the price from before this instruction into the TWAP oracle
+        self.oracle.update(reserve_x, reserve_y)?;
+
+        // Deposit the input first to learn how much reached the vault
+        let received = self.deposit_tokens(is_x, amount_in)?;
+
+        let (reserve_in, reserve_out) = match is_x {
+            true => (reserve_x, reserve_y),
+            false => (reserve_y, reserve_x),
+        };
+
+        // Swap part of the input for the other token with the pool's own curve
+        let now = Clock::get()?.unix_timestamp;
+        let swap_in = zap_swap_amount(reserve_in, received, self.config.swap_fee(now))?;
+        require!(swap_in != 0 && swap_in < received, AmmError::InvalidAmount);
+        let (swap_out, fee) = quote_exact_in(&self.config, is_x, reserve_x, reserve_y, supply, swap_in, now)?;
+
+        // Set aside the protocol's share of the fee, paid in the input token
+        // protocol_fee <= 10_000 so the share always fits back into a u64
+        let share = (fee as u128 * self.config.protocol_fee as u128 / 10_000) as u64;
+        let accrued = match is_x {
+            true => &mut self.config.protocol_fees_x,
+            false => &mut self.config.protocol_fees_y,
+        };
+        *accrued = accrued.checked_add(share).ok_or(AmmError::Overflow)?;
+
+        // Reserves after the internal swap
+        let pool_in = reserve_in
+            .checked_add(swap_in - share)
+            .ok_or(AmmError::Overflow)?;
+        let pool_out = reserve_out
+            .checked_sub(swap_out)
+            .ok_or(AmmError::Underflow)?;
+
+        // The internal swap moves the price like any other swap
+        let after = match is_x {
+            true => (pool_in, pool_out),
+            false => (pool_out, pool_in),
+        };
+        self.config
+            .record_price_move(now, (reserve_x, reserve_y), after)?;
+
+        // LP tokens for the two halves, rounded down; the smaller side sets the amount
+        let lp_in = mul_div((received - swap_in) as u128, supply as u128, pool_in as u128)?;
+        let lp_out = mul_div(swap_out as u128, supply as u128, pool_out as u128)?;
+        let amount = u64::try_from(lp_in.min(lp_out)).map_err(|_| AmmError::Overflow)?;
+
+        require!(amount != 0, AmmError::InvalidAmount);
+        // Slippage protection: ensure the user gets at least the LP tokens they expect
+        require!(amount >= min_lp_out, AmmError::SlippageExceeded);
+
+        self.mint_lp_tokens(self.user_ata_lp.to_account_info(), amount)?;
+
+        // Everything but the protocol's share stays in the pool
+        let reserve_in = reserve_in
+            .checked_add(received - share)
+            .ok_or(AmmError::Overflow)?;
+        let (amount_x, amount_y, reserve_x, reserve_y) = match is_x {
+            true => (received, 0, reserve_in, reserve_out),
+            false => (0, received, reserve_out, reserve_in),
+        };
+        emit!(events::LiquidityAdded {
+            config: self.config.key(),
+            user: self.user.key(),
+            amount_x,
+            amount_y,
+            lp_amount: amount,
+            reserve_x,
+            reserve_y,
+        });
+
+        Ok(())
+    }
+
+    /// Transfers tokens from the user's account to the appropriate vault
+    ///
+    /// # Arguments
+    /// * `is_x` - true for token X, false for token Y
+    /// * `amount` - Amount of tokens to send
+    ///
+    /// # Returns
+    /// * `Result<u64>` - Amount the vault actually received after transfer fees
+    pub fn deposit_tokens(&mut self, is_x: bool, amount: u64) -> Result<u64> {
+        // Select appropriate accounts based on token type
+        let (from, to, mint, decimals, cpi_program, balance_before) = match is_x {
+            true => (
+                self.user_ata_x.to_account_info(),      // Transfer from user's X account
+                self.vault_x.to_account_info(),         // Transfer to vault X
+                self.mint_x.to_account_info(),          // Token X mint
+                self.mint_x.decimals,                   // Token X decimals
+                self.token_program_x.to_account_info(), // Token X program
+                self.vault_x.amount,                    // Vault X balance before transfer
             ),
             false => (
-                self.user_ata_y.to_account_info(),
-                self.vault_y.to_account_info(),
-                self.mint_y.to_account_info(),
-                self.mint_y.decimals
+                self.user_ata_y.to_account_info(),      // Transfer from user's Y account
+                self.vault_y.to_account_info(),         // Transfer to vault Y
+                self.mint_y.to_account_info(),          // Token Y mint
+                self.mint_y.decimals,                   // Token Y decimals
+                self.token_program_y.to_account_info(), // Token Y program
+                self.vault_y.amount,                    // Vault Y balance before transfer
             ),
-
         };
 
-        let cpi_program = self.token_program.to_account_info();
-
-        let cpi_accounts = TransferChecked{
+        // Set up transfer instruction accounts
+        let cpi_accounts = TransferChecked {
             from,
             to,
-            authority: self.user.to_account_info(),
-            mint
+            mint,
+            authority: self.user.to_account_info(),  // User signs the transfer
         };
 
         let cpi_context = CpiContext::new(cpi_program, cpi_accounts);
-        transfer_checked(cpi_context, amount, decimals)
-    }
 
+        // Execute the transfer with amount and decimal validation
+        transfer_checked(cpi_context, amount, decimals)?;
 
-    pub fn mint_lp_tokens(&mut self, amount: u64) -> Result<()> {
+        // Re-read the vault to see what arrived after any transfer fee was withheld
+        let vault = match is_x {
+            true => &mut self.vault_x,
+            false => &mut self.vault_y,
+        };
+        vault.reload()?;
+        vault
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(AmmError::Underflow.into())
+    }
+
+    /// Mints LP tokens to an LP token account
+    /// The config PDA is the mint authority and signs the instruction
+    ///
+    /// # Arguments
+    /// * `to` - LP token account receiving the tokens (the user's or the lock's)
+    /// * `amount` - Amount of LP tokens to mint
+    ///
+    /// # Returns
+    /// * `Result<()>` - Ok if successful, error otherwise
+    pub fn mint_lp_tokens(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
         let cpi_program = self.token_program.to_account_info();
 
-        let cpi_accounts = MintTo{
+        // Set up mint instruction accounts
+        let cpi_accounts = MintTo {
             mint: self.mint_lp.to_account_info(),
-            to: self.user_ata_lp.to_account_info(),
-            authority: self.config.to_account_info(,)
+            to,
+            authority: self.config.to_account_info(),  // Config PDA signs the mint
         };
 
-        let seeds: &[&[u8]; 3] = &[
-        &b"config"[..],
-        &self.config.seed.to_le_bytes(),
-        &[self.config.config_bump],
-        ];
+        // Create signer seeds for config PDA
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"config",
+            &self.config.seed.to_le_bytes(),
+            &[self.config.config_bump],
+        ]];
 
-        let signer_seeds: &[&[&[u8]]] = &[7seeds[..]];
-
-        let cpi_contet = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        // Create CPI context with PDA signer
+        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
 
+        // Execute the mint operation
         mint_to(cpi_context, amount)
     }
-}
\ No newline at end of file
+}
+
+/// Part of a single-sided deposit to swap so both halves match the post-swap reserve ratio
+///
+/// With `r` the input reserve, `a` the amount deposited and `g = 1 - fee / 10_000`, swapping
+/// `s` on a constant product curve leaves `(a - s) / (r + s)` equal to the output's share of
+/// the remaining reserve when `g * s^2 + r * (1 + g) * s - a * r = 0`, so
+/// `s = (sqrt(r^2 * (1 + g)^2 + 4 * g * a * r) - r * (1 + g)) / (2 * g)`.
+/// Everything is scaled by 10_000 and the root is taken over 256 bits.
+///
+/// # Arguments
+/// * `reserve_in` - Pool reserves of the token being deposited
+/// * `amount_in` - Amount of it the vault received
+/// * `fee` - Swap fee in basis points
+///
+/// # Returns
+/// * `Result<u64>` - Amount to swap, rounded down
+pub fn zap_swap_amount(reserve_in: u64, amount_in: u64, fee: u16) -> Result<u64> {
+    require!(fee < 10_000, AmmError::InvalidFee);
+    let (r, a) = (reserve_in as u128, amount_in as u128);
+    let g = 10_000 - fee as u128;
+
+    // r * (1 + g) and 4 * g * a, scaled by 10_000
+    let b = r * (10_000 + g);
+    let c = 4 * g * 10_000 * a;
+
+    // b^2 + c * r over 256 bits
+    let (high_b, low_b) = full_mul(b, b);
+    let (high_c, low_c) = full_mul(c, r);
+    let (low, carry) = low_b.overflowing_add(low_c);
+    let high = high_b
+        .checked_add(high_c)
+        .and_then(|high| high.checked_add(carry as u128))
+        .ok_or(AmmError::Overflow)?;
+
+    let swap = (sqrt_u256(high, low)? - b) / (2 * g);
+    Ok(u64::try_from(swap).map_err(|_| AmmError::Overflow)?.min(amount_in))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Output of selling `amount_in` on x * y = k with the fee taken from the input
+    fn constant_product_out(reserve_in: u64, reserve_out: u64, amount_in: u64, fee: u16) -> u128 {
+        let net_in = amount_in as u128 * (10_000 - fee) as u128 / 10_000;
+        reserve_out as u128 * net_in / (reserve_in as u128 + net_in)
+    }
+
+    #[test]
+    fn zap_swap_amount_matches_the_closed_form() {
+        // 1_000_000 X / 1_000_000 Y at 0.3%: 48_882 of 100_000 X are swapped
+        assert_eq!(zap_swap_amount(1_000_000, 100_000, 30).unwrap(), 48_882);
+        // Without a fee, s^2 + 2 * r * s - a * r = 0
+        assert_eq!(zap_swap_amount(1_000_000, 100_000, 0).unwrap(), 48_808);
+        assert_eq!(zap_swap_amount(1_000_000, 0, 30).unwrap(), 0);
+        assert!(zap_swap_amount(1_000_000, 100_000, 10_000).is_err());
+    }
+
+    #[test]
+    fn zap_swap_amount_leaves_both_halves_in_the_pool_ratio() {
+        for (reserve_in, reserve_out, amount_in) in [
+            (1_000_000u64, 1_000_000u64, 100_000u64),
+            (1_000_000, 4_000_000, 10_000_000),
+            (50_000_000_000, 7_000_000, 1_000_000_000),
+            (1 << 60, 1 << 60, 1 << 59),
+        ] {
+            for fee in [0u16, 30, 1_000] {
+                let swap = zap_swap_amount(reserve_in, amount_in, fee).unwrap();
+                assert!(swap < amount_in);
+                let out = constant_product_out(reserve_in, reserve_out, swap, fee);
+
+                // (amount_in - swap) / (reserve_in + swap) == out / (reserve_out - out), to 0.01%
+                let left = (amount_in - swap) as f64 * (reserve_out as u128 - out) as f64;
+                let right = out as f64 * (reserve_in as u128 + swap as u128) as f64;
+                assert!((left - right).abs() <= left.max(right) * 1e-4, "fee {fee}: {left} vs {right}");
+            }
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{MAX_FEE_BPS, MAX_FEE_TIERS};
+use crate::errors::AmmError;
+use crate::states::Factory;
+
+/// Accounts required for the factory authority to manage the pool factory
+/// Shared by `add_fee_tier`, `remove_fee_tier` and `set_factory_authority`
+#[derive(Accounts)]
+pub struct FactoryAdmin<'info> {
+    /// The current factory authority (must sign the transaction)
+    pub authority: Signer<'info>,
+
+    /// The pool factory being updated
+    #[account(
+        mut,
+        seeds = [b"factory"],
+        bump = factory.bump,
+        has_one = authority @ AmmError::InvalidAuthority,
+    )]
+    pub factory: Account<'info, Factory>,
+}
+
+impl<'info> FactoryAdmin<'info> {
+    /// Allows canonical pools to be created with a new swap fee
+    ///
+    /// # Arguments
+    /// * `fee` - Swap fee in basis points, at most `MAX_FEE_BPS`
+    pub fn add_fee_tier(&mut self, fee: u16) -> Result<()> {
+        require!(fee <= MAX_FEE_BPS, AmmError::InvalidFee);
+        require!(!self.factory.fee_tiers.contains(&fee), AmmError::FeeTierExists);
+        require!(
+            self.factory.fee_tiers.len() < MAX_FEE_TIERS,
+            AmmError::FeeTierLimitReached
+        );
+        self.factory.fee_tiers.push(fee);
+        Ok(())
+    }
+
+    /// Stops new canonical pools from using a swap fee
+    /// Pools already created with it stay registered
+    ///
+    /// # Arguments
+    /// * `fee` - Swap fee in basis points to remove
+    pub fn remove_fee_tier(&mut self, fee: u16) -> Result<()> {
+        let index = self
+            .factory
+            .fee_tiers
+            .iter()
+            .position(|tier| *tier == fee)
+            .ok_or(AmmError::FeeTierNotAllowed)?;
+        self.factory.fee_tiers.remove(index);
+        Ok(())
+    }
+
+    /// Transfers the factory to a new authority
+    /// Pools the factory created, old and new, are managed by the new authority from now on
+    ///
+    /// # Arguments
+    /// * `new_authority` - Wallet that will manage the factory from now on
+    pub fn set_factory_authority(&mut self, new_authority: Pubkey) -> Result<()> {
+        self.factory.authority = new_authority;
+        Ok(())
+    }
+}
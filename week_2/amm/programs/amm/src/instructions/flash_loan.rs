@@ -0,0 +1,313 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::{
+    self as instructions_sysvar, load_current_index_checked, load_instruction_at_checked,
+};
+use anchor_lang::Discriminator;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::errors::AmmError;
+use crate::states::{Config, CurveType};
+use crate::utils::amount_before_fee;
+
+/// Position of `config` in the accounts of `flash_borrow` and `flash_repay`
+const CONFIG_INDEX: usize = 3;
+
+/// Accounts required for borrowing from the vaults and paying the loan back
+/// Shared by `flash_borrow` and `flash_repay`, which must come in the same transaction
+#[derive(Accounts)]
+pub struct FlashLoan<'info> {
+    /// The borrower (must sign the transaction)
+    pub user: Signer<'info>,
+
+    /// The mint account for token X in the trading pair
+    #[account(mint::token_program = token_program_x)]
+    pub mint_x: InterfaceAccount<'info, Mint>,
+
+    /// The mint account for token Y in the trading pair
+    #[account(mint::token_program = token_program_y)]
+    pub mint_y: InterfaceAccount<'info, Mint>,
+
+    /// The AMM pool configuration account
+    /// Mutable because it records what the loan in progress owes
+    #[account(
+        mut,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+        has_one = mint_x,  // Ensures mint_x matches the one in config
+        has_one = mint_y,  // Ensures mint_y matches the one in config
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The vault that holds all token X of the pool
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_x,
+    )]
+    pub vault_x: InterfaceAccount<'info, TokenAccount>,
+
+    /// The vault that holds all token Y of the pool
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_y,
+    )]
+    pub vault_y: InterfaceAccount<'info, TokenAccount>,
+
+    /// Borrower's token account for token X
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = user,
+        associated_token::token_program = token_program_x,
+    )]
+    pub user_ata_x: InterfaceAccount<'info, TokenAccount>,
+
+    /// Borrower's token account for token Y
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = user,
+        associated_token::token_program = token_program_y,
+    )]
+    pub user_ata_y: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token program owning mint_x (SPL Token or Token-2022)
+    pub token_program_x: Interface<'info, TokenInterface>,
+    /// Token program owning mint_y (SPL Token or Token-2022)
+    pub token_program_y: Interface<'info, TokenInterface>,
+
+    /// Instructions sysvar, read to find the matching `flash_repay`
+    /// CHECK: address is checked against the sysvar id
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+impl<'info> FlashLoan<'info> {
+    /// Lends tokens from the vaults until `flash_repay` later in the same transaction
+    ///
+    /// The process follows this sequence:
+    /// 1. Check the pool lends, can lend the amounts and no other loan is in progress
+    /// 2. Check a `flash_repay` for this pool follows in the transaction
+    /// 3. Record what the loan owes, fee included, which blocks every instruction that
+    ///    prices against the vaults until it is repaid
+    /// 4. Send the amounts to the borrower
+    ///
+    /// The borrower is free to use the tokens in the instructions in between; if
+    /// `flash_repay` can't pull back what is owed, the whole transaction fails.
+    ///
+    /// # Arguments
+    /// * `amount_x` - Amount of token X to borrow
+    /// * `amount_y` - Amount of token Y to borrow
+    ///
+    /// # Returns
+    /// * `Result<()>` - Ok if successful, error otherwise
+    pub fn flash_borrow(&mut self, amount_x: u64, amount_y: u64) -> Result<()> {
+        // Ensure the pool is not locked
+        require!(!self.config.locked, AmmError::PoolLocked);
+        require!(self.config.flash_loans, AmmError::FlashLoansDisabled);
+        // Concentrated pools have no reserves to guard while lent out
+        require!(self.config.curve_type != CurveType::Concentrated, AmmError::InvalidCurve);
+        require!(amount_x != 0 || amount_y != 0, AmmError::InvalidAmount);
+
+        // Fails if a loan is already in progress; uncollected protocol fees can't be lent
+        let (reserve_x, reserve_y) = self
+            .config
+            .reserves(self.vault_x.amount, self.vault_y.amount)?;
+        require!(
+            amount_x <= reserve_x && amount_y <= reserve_y,
+            AmmError::InsufficientBalance
+        );
+
+        self.check_repay()?;
+
+        let owed_x = self.charge(true, amount_x)?;
+        let owed_y = self.charge(false, amount_y)?;
+        self.config.flash_loan_x = owed_x;
+        self.config.flash_loan_y = owed_y;
+
+        if amount_x != 0 {
+            self.withdraw_tokens(true, amount_x)?;
+        }
+        if amount_y != 0 {
+            self.withdraw_tokens(false, amount_y)?;
+        }
+
+        Ok(())
+    }
+
+    /// Pulls back what the flash loan in progress owes, fee included, and ends the loan
+    ///
+    /// # Returns
+    /// * `Result<()>` - Ok if successful, error otherwise
+    pub fn flash_repay(&mut self) -> Result<()> {
+        require!(self.config.flash_loan_active(), AmmError::NoFlashLoan);
+
+        let (owed_x, owed_y) = (self.config.flash_loan_x, self.config.flash_loan_y);
+        self.config.flash_loan_x = 0;
+        self.config.flash_loan_y = 0;
+
+        for (is_x, owed) in [(true, owed_x), (false, owed_y)] {
+            if owed == 0 {
+                continue;
+            }
+            // Transfer fees are withheld in transit, so send enough for the vault to receive it all
+            let mint = match is_x {
+                true => &self.mint_x,
+                false => &self.mint_y,
+            };
+            let send = amount_before_fee(mint, owed)?;
+            let received = self.deposit_tokens(is_x, send)?;
+            require!(received >= owed, AmmError::InsufficientBalance);
+        }
+
+        Ok(())
+    }
+
+    /// Checks this instruction was called directly by the transaction, and that a
+    /// `flash_repay` for the same pool comes after it
+    pub fn check_repay(&self) -> Result<()> {
+        let sysvar = self.instructions.to_account_info();
+        let current = load_current_index_checked(&sysvar)? as usize;
+
+        // Through CPI, the top-level instruction would belong to the calling program
+        let borrow = load_instruction_at_checked(current, &sysvar)?;
+        require_keys_eq!(borrow.program_id, crate::ID, AmmError::MissingFlashRepay);
+
+        let mut index = current + 1;
+        while let Ok(instruction) = load_instruction_at_checked(index, &sysvar) {
+            if instruction.program_id == crate::ID
+                && instruction.data.starts_with(crate::instruction::FlashRepay::DISCRIMINATOR)
+                && instruction.accounts.get(CONFIG_INDEX).map(|meta| meta.pubkey)
+                    == Some(self.config.key())
+            {
+                return Ok(());
+            }
+            index += 1;
+        }
+        err!(AmmError::MissingFlashRepay)
+    }
+
+    /// Adds the flash loan fee to a borrowed amount and accrues the protocol's share of it
+    ///
+    /// # Arguments
+    /// * `is_x` - true for token X, false for token Y
+    /// * `amount` - Amount borrowed
+    ///
+    /// # Returns
+    /// * `Result<u64>` - Amount the vault must get back
+    pub fn charge(&mut self, is_x: bool, amount: u64) -> Result<u64> {
+        // Rounded up so even tiny loans pay a fee when the rate is non-zero
+        let fee = (amount as u128 * self.config.flash_loan_fee as u128).div_ceil(10_000) as u64;
+
+        // protocol_fee <= 10_000 so the share always fits back into a u64
+        let share = (fee as u128 * self.config.protocol_fee as u128 / 10_000) as u64;
+        let accrued = match is_x {
+            true => &mut self.config.protocol_fees_x,
+            false => &mut self.config.protocol_fees_y,
+        };
+        *accrued = accrued.checked_add(share).ok_or(AmmError::Overflow)?;
+
+        amount.checked_add(fee).ok_or(AmmError::Overflow.into())
+    }
+
+    /// Transfers tokens from the borrower's account to the appropriate vault
+    ///
+    /// # Arguments
+    /// * `is_x` - true for token X, false for token Y
+    /// * `amount` - Amount of tokens to send
+    ///
+    /// # Returns
+    /// * `Result<u64>` - Amount the vault actually received after transfer fees
+    pub fn deposit_tokens(&mut self, is_x: bool, amount: u64) -> Result<u64> {
+        // Select appropriate accounts based on token type
+        let (from, to, mint, decimals, cpi_program, balance_before) = match is_x {
+            true => (
+                self.user_ata_x.to_account_info(),      // Transfer from borrower's X account
+                self.vault_x.to_account_info(),         // Transfer to vault X
+                self.mint_x.to_account_info(),          // Token X mint
+                self.mint_x.decimals,                   // Token X decimals
+                self.token_program_x.to_account_info(), // Token X program
+                self.vault_x.amount,                    // Vault X balance before transfer
+            ),
+            false => (
+                self.user_ata_y.to_account_info(),      // Transfer from borrower's Y account
+                self.vault_y.to_account_info(),         // Transfer to vault Y
+                self.mint_y.to_account_info(),          // Token Y mint
+                self.mint_y.decimals,                   // Token Y decimals
+                self.token_program_y.to_account_info(), // Token Y program
+                self.vault_y.amount,                    // Vault Y balance before transfer
+            ),
+        };
+
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            mint,
+            authority: self.user.to_account_info(),  // Borrower signs the transfer
+        };
+        let cpi_context = CpiContext::new(cpi_program, cpi_accounts);
+        transfer_checked(cpi_context, amount, decimals)?;
+
+        // Re-read the vault to see what arrived after any transfer fee was withheld
+        let vault = match is_x {
+            true => &mut self.vault_x,
+            false => &mut self.vault_y,
+        };
+        vault.reload()?;
+        vault
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(AmmError::Underflow.into())
+    }
+
+    /// Transfers tokens from the appropriate vault to the borrower's account
+    /// The config PDA is the vault authority and signs the transfer
+    ///
+    /// # Arguments
+    /// * `is_x` - true for token X, false for token Y
+    /// * `amount` - Amount of tokens to send
+    ///
+    /// # Returns
+    /// * `Result<()>` - Ok if successful, error otherwise
+    pub fn withdraw_tokens(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        // Select appropriate accounts based on token type
+        let (from, to, mint, decimals, cpi_program) = match is_x {
+            true => (
+                self.vault_x.to_account_info(),         // Transfer from vault X
+                self.user_ata_x.to_account_info(),      // Transfer to borrower's X account
+                self.mint_x.to_account_info(),          // Token X mint
+                self.mint_x.decimals,                   // Token X decimals
+                self.token_program_x.to_account_info(), // Token X program
+            ),
+            false => (
+                self.vault_y.to_account_info(),         // Transfer from vault Y
+                self.user_ata_y.to_account_info(),      // Transfer to borrower's Y account
+                self.mint_y.to_account_info(),          // Token Y mint
+                self.mint_y.decimals,                   // Token Y decimals
+                self.token_program_y.to_account_info(), // Token Y program
+            ),
+        };
+
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            mint,
+            authority: self.config.to_account_info(),  // Config PDA signs the transfer
+        };
+
+        // Create signer seeds for config PDA
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"config",
+            &self.config.seed.to_le_bytes(),
+            &[self.config.config_bump],
+        ]];
+
+        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        transfer_checked(cpi_context, amount, decimals)
+    }
+}
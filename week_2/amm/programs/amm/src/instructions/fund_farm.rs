@@ -0,0 +1,115 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::errors::AmmError;
+use crate::states::Farm;
+
+/// Accounts required for the farm authority to fund a reward period
+#[derive(Accounts)]
+pub struct FundFarm<'info> {
+    /// The farm authority (must sign the transaction)
+    pub authority: Signer<'info>,
+
+    /// The farm being funded
+    #[account(
+        mut,
+        seeds = [b"farm", farm.config.as_ref()],
+        bump = farm.bump,
+        has_one = authority @ AmmError::InvalidAuthority,
+        has_one = mint_reward,
+    )]
+    pub farm: Account<'info, Farm>,
+
+    /// The mint of the reward token
+    #[account(mint::token_program = token_program_reward)]
+    pub mint_reward: InterfaceAccount<'info, Mint>,
+
+    /// Holds the reward tokens not yet harvested
+    #[account(
+        mut,
+        associated_token::mint = mint_reward,
+        associated_token::authority = farm,
+        associated_token::token_program = token_program_reward,
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Authority's token account the rewards are taken from
+    #[account(
+        mut,
+        associated_token::mint = mint_reward,
+        associated_token::authority = authority,
+        associated_token::token_program = token_program_reward,
+    )]
+    pub authority_ata_reward: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token program owning mint_reward (SPL Token or Token-2022)
+    pub token_program_reward: Interface<'info, TokenInterface>,
+}
+
+impl<'info> FundFarm<'info> {
+    /// Starts a new reward period of `duration` seconds from now
+    ///
+    /// Rewards left over from a period in progress roll into the new one, so this both
+    /// tops up and extends a farm: with `amount` zero it only stretches what is left.
+    ///
+    /// # Arguments
+    /// * `amount` - Reward tokens to add
+    /// * `duration` - Length of the new period in seconds
+    ///
+    /// # Returns
+    /// * `Result<()>` - Ok if successful, error otherwise
+    pub fn fund_farm(&mut self, amount: u64, duration: i64) -> Result<()> {
+        require!(duration > 0, AmmError::InvalidRewardPeriod);
+        let now = Clock::get()?.unix_timestamp;
+
+        // Settle the old rate up to now before it changes
+        self.farm.update(now)?;
+
+        let received = match amount {
+            0 => 0,
+            _ => self.deposit_rewards(amount)?,
+        };
+        let total = received
+            .checked_add(self.farm.remaining_rewards(now))
+            .ok_or(AmmError::Overflow)?;
+
+        // Rounded down so the period never emits more than the vault holds
+        let reward_rate = total / duration as u64;
+        require!(reward_rate != 0, AmmError::InvalidRewardPeriod);
+
+        self.farm.reward_rate = reward_rate;
+        self.farm.last_update = now;
+        self.farm.period_end = now.checked_add(duration).ok_or(AmmError::Overflow)?;
+
+        Ok(())
+    }
+
+    /// Transfers reward tokens from the authority to the reward vault
+    ///
+    /// # Arguments
+    /// * `amount` - Amount of tokens to send
+    ///
+    /// # Returns
+    /// * `Result<u64>` - Amount the vault actually received after transfer fees
+    pub fn deposit_rewards(&mut self, amount: u64) -> Result<u64> {
+        let balance_before = self.reward_vault.amount;
+
+        let cpi_accounts = TransferChecked {
+            from: self.authority_ata_reward.to_account_info(),
+            to: self.reward_vault.to_account_info(),
+            mint: self.mint_reward.to_account_info(),
+            authority: self.authority.to_account_info(),  // Authority signs the transfer
+        };
+        let cpi_context = CpiContext::new(self.token_program_reward.to_account_info(), cpi_accounts);
+        transfer_checked(cpi_context, amount, self.mint_reward.decimals)?;
+
+        // Re-read the vault to see what arrived after any transfer fee was withheld
+        self.reward_vault.reload()?;
+        self.reward_vault
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(AmmError::Underflow.into())
+    }
+}
@@ -1,10 +1,15 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{Mint, Token, TokenAccount},
+    token::Token,
+    token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
-use crate::states::Config;
+use crate::constants::{MAX_AMP, MAX_FEE_BPS, MIN_AMP};
+use crate::errors::AmmError;
+use crate::events;
+use crate::states::{Config, CurveType, Oracle};
+use crate::utils::validate_mint;
 
 /// Accounts required for initializing a new AMM pool
 /// This struct defines all the accounts needed to create a new liquidity pool
@@ -19,11 +24,15 @@ pub struct Initialize<'info> {
 
     /// The mint account for the first token (X) in the trading pair
     /// This is immutable as we only need to read mint information for validation
-    pub mint_x: Account<'info, Mint>,
+    /// May be owned by either the SPL Token or the Token-2022 program
+    #[account(mint::token_program = token_program_x)]
+    pub mint_x: InterfaceAccount<'info, Mint>,
 
     /// The mint account for the second token (Y) in the trading pair
     /// This is immutable as we only need to read mint information for validation
-    pub mint_y: Account<'info, Mint>,
+    /// May be owned by either the SPL Token or the Token-2022 program
+    #[account(mint::token_program = token_program_y)]
+    pub mint_y: InterfaceAccount<'info, Mint>,
 
     /// The main configuration account for the AMM pool
     /// This PDA stores all pool settings, token references, and metadata
@@ -33,10 +42,21 @@ pub struct Initialize<'info> {
         payer = admin,                                          // Admin pays for creation
         seeds = [b"config", seed.to_le_bytes().as_ref()],      // PDA derivation
         bump,                                                   // Anchor finds canonical bump
-        space = Config::INIT_SPACE                              // Required space for Config struct
+        space = 8 + Config::INIT_SPACE                          // Discriminator + Config struct
     )]
     pub config: Account<'info, Config>,
 
+    /// The TWAP price oracle for this pool
+    /// Boxed because its observation ring buffer is too large for the stack
+    #[account(
+        init,                                     // Create new account
+        payer = admin,                            // Admin pays for creation
+        seeds = [b"oracle", config.key().as_ref()], // PDA derived from config
+        bump,                                     // Anchor finds canonical bump
+        space = 8 + Oracle::INIT_SPACE            // Discriminator + Oracle struct
+    )]
+    pub oracle: Box<Account<'info, Oracle>>,
+
     /// The LP (Liquidity Provider) token mint for this pool
     /// Users receive these tokens as receipts when providing liquidity
     /// The config PDA is set as the mint authority for security
@@ -45,10 +65,11 @@ pub struct Initialize<'info> {
         payer = admin,                         // Admin pays for creation
         mint::decimals = 6,                    // Standard 6 decimal places for LP tokens
         mint::authority = config.key(),        // Config PDA controls minting
+        mint::token_program = token_program,   // LP tokens always use SPL Token
         seeds = [b"lp", config.key().as_ref()], // PDA derived from config
         bump,                                  // Anchor finds canonical bump
     )]
-    pub mint_lp: Account<'info, Mint>,
+    pub mint_lp: InterfaceAccount<'info, Mint>,
 
     /// The vault that will hold all deposited token X
     /// This is an associated token account owned by the config PDA
@@ -58,9 +79,9 @@ pub struct Initialize<'info> {
         payer = admin,                                 // Admin pays for creation
         associated_token::mint = mint_x,               // Associated with mint_x
         associated_token::authority = config,          // Owned by config PDA
-        associated_token::token_program = token_program, // Uses SPL Token program
+        associated_token::token_program = token_program_x, // Uses mint_x's token program
     )]
-    pub vault_x: Account<'info, TokenAccount>,
+    pub vault_x: InterfaceAccount<'info, TokenAccount>,
 
     /// The vault that will hold all deposited token Y
     /// This is an associated token account owned by the config PDA
@@ -70,12 +91,16 @@ pub struct Initialize<'info> {
         payer = admin,                                 // Admin pays for creation
         associated_token::mint = mint_y,               // Associated with mint_y
         associated_token::authority = config,          // Owned by config PDA
-        associated_token::token_program = token_program, // Uses SPL Token program
+        associated_token::token_program = token_program_y, // Uses mint_y's token program
     )]
-    pub vault_y: Account<'info, TokenAccount>,
+    pub vault_y: InterfaceAccount<'info, TokenAccount>,
 
-    /// SPL Token program for token operations
+    /// SPL Token program for the LP mint
     pub token_program: Program<'info, Token>,
+    /// Token program owning mint_x (SPL Token or Token-2022)
+    pub token_program_x: Interface<'info, TokenInterface>,
+    /// Token program owning mint_y (SPL Token or Token-2022)
+    pub token_program_y: Interface<'info, TokenInterface>,
     /// Associated Token program for ATA operations
     pub associated_token_program: Program<'info, AssociatedToken>,
     /// System program for account creation
@@ -95,16 +120,39 @@ impl<'info> Initialize<'info> {
     /// 
     /// # Arguments
     /// * `seed` - Unique identifier for this pool, allows multiple pools with same token pairs
-    /// * `fee` - Trading fee in basis points (e.g., 30 = 0.30%)
+    /// * `fee` - Trading fee in basis points (e.g., 30 = 0.30%), at most `MAX_FEE_BPS`
     /// * `authority` - Optional authority that can update pool settings (None = immutable)
+    /// * `curve_type` - Invariant used to price swaps
+    /// * `amp` - StableSwap amplification coefficient, ignored for constant product pools
     /// * `bumps` - Canonical bump values for PDAs (provided by Anchor)
     pub fn initialize(
         &mut self, 
         seed: u64, 
         fee: u16, 
         authority: Option<Pubkey>, 
+        curve_type: CurveType,
+        amp: u64,
         bumps: &InitializeBumps
     ) -> Result<()> {
+        // Reject fees the pool could never be updated away from if it has no authority
+        require!(fee <= MAX_FEE_BPS, AmmError::InvalidFee);
+
+        // Only StableSwap pools use an amplification coefficient
+        let amp = match curve_type {
+            CurveType::ConstantProduct => 0,
+            CurveType::StableSwap => {
+                require!((MIN_AMP..=MAX_AMP).contains(&amp), AmmError::InvalidAmp);
+                amp
+            }
+            // Concentrated pools need the extra state created by initialize_concentrated
+            CurveType::Concentrated => return err!(AmmError::InvalidCurve),
+        };
+        let now = Clock::get()?.unix_timestamp;
+
+        // Reject Token-2022 mints whose extensions would trap or drain the vaults
+        validate_mint(&self.mint_x)?;
+        validate_mint(&self.mint_y)?;
+
         // Initialize the config account with all pool parameters
         self.config.set_inner(Config { 
             seed,                              // Unique pool identifier
@@ -112,11 +160,42 @@ impl<'info> Initialize<'info> {
             mint_x: self.mint_x.key(),        // First token in the pair
             mint_y: self.mint_y.key(),        // Second token in the pair
             fee,                              // Trading fee in basis points
+            dynamic_fee: false,               // Static fee until the authority enables the dynamic one
+            min_fee: fee,
+            max_fee: fee,
+            volatility: 0,
+            volatility_updated: now,
+            protocol_fee: 0,                  // LPs keep the whole fee until the authority sets a share
+            treasury: None,                   // No protocol treasury yet
+            protocol_fees_x: 0,               // Nothing accrued yet
+            protocol_fees_y: 0,
+            flash_loans: false,               // No flash loans until the authority enables them
+            flash_loan_fee: fee,              // Flash loans start at the swap fee
+            flash_loan_x: 0,                  // No flash loan in progress
+            flash_loan_y: 0,
+            curve_type,                       // Invariant used to price swaps
+            amp_initial: amp,                 // No amplification ramp in progress
+            amp_target: amp,
+            amp_ramp_start: now,
+            amp_ramp_end: now,
             locked: false,                    // Pool starts unlocked (active)
             config_bump: bumps.config,        // PDA bump for config account
             lp_bump: bumps.mint_lp            // PDA bump for LP mint
         });
 
+        // Start the price history at pool creation, with empty accumulators
+        self.oracle.set_inner(Oracle::new(self.config.key(), now, bumps.oracle));
+
+        emit!(events::PoolInitialized {
+            config: self.config.key(),
+            seed,
+            mint_x: self.mint_x.key(),
+            mint_y: self.mint_y.key(),
+            fee,
+            curve_type,
+            authority,
+        });
+
         // Pool is now ready for liquidity deposits and trading
         Ok(())
     }
@@ -0,0 +1,188 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::concentrated_liquidity::{sqrt_price_at_tick, tick_at_sqrt_price};
+use crate::constants::{MAX_FEE_BPS, MAX_TICK, MAX_TICK_SPACING, MIN_TICK};
+use crate::errors::AmmError;
+use crate::events;
+use crate::states::{ConcentratedPool, Config, CurveType, Oracle};
+use crate::utils::validate_mint;
+
+/// Accounts required for initializing a new concentrated-liquidity pool
+/// Same pool configuration, oracle and vaults as `initialize`, but liquidity is
+/// tracked per position in a `ConcentratedPool` account instead of with an LP mint
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct InitializeConcentrated<'info> {
+    /// The admin/deployer who is creating the pool (must sign the transaction)
+    /// Mutable because they will pay for account creation costs
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// The mint account for the first token (X) in the trading pair
+    /// May be owned by either the SPL Token or the Token-2022 program
+    #[account(mint::token_program = token_program_x)]
+    pub mint_x: InterfaceAccount<'info, Mint>,
+
+    /// The mint account for the second token (Y) in the trading pair
+    /// May be owned by either the SPL Token or the Token-2022 program
+    #[account(mint::token_program = token_program_y)]
+    pub mint_y: InterfaceAccount<'info, Mint>,
+
+    /// The main configuration account for the pool
+    /// Uses a custom seed provided by the admin to allow multiple pools
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"config", seed.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + Config::INIT_SPACE
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The TWAP price oracle for this pool
+    /// Boxed because its observation ring buffer is too large for the stack
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"oracle", config.key().as_ref()],
+        bump,
+        space = 8 + Oracle::INIT_SPACE
+    )]
+    pub oracle: Box<Account<'info, Oracle>>,
+
+    /// Price, liquidity and tick state of the pool
+    /// Boxed because its tick list is too large for the stack
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"pool", config.key().as_ref()],
+        bump,
+        space = 8 + ConcentratedPool::INIT_SPACE
+    )]
+    pub pool: Box<Account<'info, ConcentratedPool>>,
+
+    /// The vault that will hold all token X provided by positions
+    #[account(
+        init,
+        payer = admin,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_x,
+    )]
+    pub vault_x: InterfaceAccount<'info, TokenAccount>,
+
+    /// The vault that will hold all token Y provided by positions
+    #[account(
+        init,
+        payer = admin,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_y,
+    )]
+    pub vault_y: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token program owning mint_x (SPL Token or Token-2022)
+    pub token_program_x: Interface<'info, TokenInterface>,
+    /// Token program owning mint_y (SPL Token or Token-2022)
+    pub token_program_y: Interface<'info, TokenInterface>,
+    /// Associated Token program for ATA operations
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeConcentrated<'info> {
+    /// Initializes a new concentrated-liquidity pool at a starting price
+    ///
+    /// Liquidity providers open positions over tick ranges with `open_position`
+    /// and `increase_liquidity`, and traders use `swap_concentrated`.
+    ///
+    /// # Arguments
+    /// * `seed` - Unique identifier for this pool, allows multiple pools with same token pairs
+    /// * `fee` - Trading fee in basis points (e.g., 30 = 0.30%), at most `MAX_FEE_BPS`
+    /// * `authority` - Optional authority that can update pool settings (None = immutable)
+    /// * `tick_spacing` - Position ticks must be multiples of this, at most `MAX_TICK_SPACING`
+    /// * `sqrt_price` - Square root of the starting price of X in Y, in Q64.64
+    /// * `bumps` - Canonical bump values for PDAs (provided by Anchor)
+    pub fn initialize_concentrated(
+        &mut self,
+        seed: u64,
+        fee: u16,
+        authority: Option<Pubkey>,
+        tick_spacing: u16,
+        sqrt_price: u128,
+        bumps: &InitializeConcentratedBumps,
+    ) -> Result<()> {
+        // Reject fees the pool could never be updated away from if it has no authority
+        require!(fee <= MAX_FEE_BPS, AmmError::InvalidFee);
+        require!(
+            (1..=MAX_TICK_SPACING).contains(&tick_spacing),
+            AmmError::InvalidTickSpacing
+        );
+        // The price must leave room to move in both directions
+        require!(
+            sqrt_price >= sqrt_price_at_tick(MIN_TICK)? && sqrt_price < sqrt_price_at_tick(MAX_TICK)?,
+            AmmError::InvalidPrice
+        );
+        let now = Clock::get()?.unix_timestamp;
+
+        // Reject Token-2022 mints whose extensions would trap or drain the vaults
+        validate_mint(&self.mint_x)?;
+        validate_mint(&self.mint_y)?;
+
+        self.config.set_inner(Config {
+            seed,
+            authority,
+            mint_x: self.mint_x.key(),
+            mint_y: self.mint_y.key(),
+            fee,
+            dynamic_fee: false,               // Concentrated pools always charge the static fee
+            min_fee: fee,
+            max_fee: fee,
+            volatility: 0,
+            volatility_updated: now,
+            protocol_fee: 0,
+            treasury: None,
+            protocol_fees_x: 0,
+            protocol_fees_y: 0,
+            flash_loans: false,               // Flash loans only lend from full-range pools
+            flash_loan_fee: 0,
+            flash_loan_x: 0,
+            flash_loan_y: 0,
+            curve_type: CurveType::Concentrated,
+            amp_initial: 0,
+            amp_target: 0,
+            amp_ramp_start: now,
+            amp_ramp_end: now,
+            locked: false,
+            config_bump: bumps.config,
+            lp_bump: 0,                       // No LP mint, liquidity lives in positions
+        });
+
+        self.oracle.set_inner(Oracle::new(self.config.key(), now, bumps.oracle));
+
+        // Set fields one by one: building the whole account would overflow the stack
+        let pool = &mut self.pool;
+        pool.config = self.config.key();
+        pool.tick_spacing = tick_spacing;
+        pool.sqrt_price = sqrt_price;
+        pool.tick_current = tick_at_sqrt_price(sqrt_price)?;
+        pool.bump = bumps.pool;
+
+        emit!(events::PoolInitialized {
+            config: self.config.key(),
+            seed,
+            mint_x: self.mint_x.key(),
+            mint_y: self.mint_y.key(),
+            fee,
+            curve_type: CurveType::Concentrated,
+            authority,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{MAX_FEE_BPS, MAX_FEE_TIERS};
+use crate::errors::AmmError;
+use crate::states::Factory;
+
+/// Accounts required for creating the program's pool factory
+/// There is a single factory, so this only ever succeeds once
+#[derive(Accounts)]
+pub struct InitializeFactory<'info> {
+    /// The factory authority (must sign the transaction)
+    /// Mutable because they pay for the factory account
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// The pool factory
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"factory"],
+        bump,
+        space = 8 + Factory::INIT_SPACE
+    )]
+    pub factory: Account<'info, Factory>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeFactory<'info> {
+    /// Creates the pool factory with its first fee tiers
+    /// The signer becomes the factory authority, so deployments should call this right away
+    ///
+    /// # Arguments
+    /// * `fee_tiers` - Swap fees in basis points canonical pools may use, each at most `MAX_FEE_BPS`
+    /// * `bumps` - Canonical bump values for PDAs (provided by Anchor)
+    ///
+    /// # Returns
+    /// * `Result<()>` - Ok if successful, error otherwise
+    pub fn initialize_factory(
+        &mut self,
+        fee_tiers: Vec<u16>,
+        bumps: &InitializeFactoryBumps,
+    ) -> Result<()> {
+        require!(fee_tiers.len() <= MAX_FEE_TIERS, AmmError::FeeTierLimitReached);
+        for (i, fee) in fee_tiers.iter().enumerate() {
+            require!(*fee <= MAX_FEE_BPS, AmmError::InvalidFee);
+            require!(!fee_tiers[..i].contains(fee), AmmError::FeeTierExists);
+        }
+
+        self.factory.set_inner(Factory {
+            authority: self.authority.key(),
+            fee_tiers,
+            bump: bumps.factory,
+        });
+
+        Ok(())
+    }
+}
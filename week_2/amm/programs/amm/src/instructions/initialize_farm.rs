@@ -0,0 +1,115 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::Token,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::errors::AmmError;
+use crate::states::{Config, Factory, Farm};
+use crate::utils::validate_mint;
+
+/// Accounts required for the pool authority to create a farm for the pool's LP tokens
+#[derive(Accounts)]
+pub struct InitializeFarm<'info> {
+    /// The pool authority, or the factory authority for factory pools (must sign the transaction)
+    /// Mutable because they pay for the farm accounts
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// The AMM pool configuration account
+    /// Pools created without an authority can't have a farm
+    #[account(
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+        constraint = config.authority.is_some() @ AmmError::NoAuthoritySet,
+        constraint = config.is_authority(authority.key(), factory.as_ref()) @ AmmError::InvalidAuthority,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The pool factory, required for pools created with `create_pool`
+    /// The only `Factory` account is the one at `[b"factory"]`, so its type is enough
+    pub factory: Option<Account<'info, Factory>>,
+
+    /// The LP token mint of the pool
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump,
+        mint::token_program = token_program,
+    )]
+    pub mint_lp: InterfaceAccount<'info, Mint>,
+
+    /// The mint of the reward token
+    /// May be owned by either the SPL Token or the Token-2022 program
+    #[account(mint::token_program = token_program_reward)]
+    pub mint_reward: InterfaceAccount<'info, Mint>,
+
+    /// The new farm
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"farm", config.key().as_ref()],
+        bump,
+        space = 8 + Farm::INIT_SPACE
+    )]
+    pub farm: Account<'info, Farm>,
+
+    /// Holds the staked LP tokens, owned by the farm PDA
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = mint_lp,
+        associated_token::authority = farm,
+        associated_token::token_program = token_program,
+    )]
+    pub lp_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Holds the reward tokens not yet harvested, owned by the farm PDA
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = mint_reward,
+        associated_token::authority = farm,
+        associated_token::token_program = token_program_reward,
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// SPL Token program for the LP mint
+    pub token_program: Program<'info, Token>,
+    /// Token program owning mint_reward (SPL Token or Token-2022)
+    pub token_program_reward: Interface<'info, TokenInterface>,
+    /// Associated Token program for ATA operations
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeFarm<'info> {
+    /// Creates an empty farm; rewards start once the authority calls `fund_farm`
+    /// The pool authority stays the farm authority even if the pool's authority changes later
+    ///
+    /// # Arguments
+    /// * `bumps` - Canonical bump values for PDAs (provided by Anchor)
+    ///
+    /// # Returns
+    /// * `Result<()>` - Ok if successful, error otherwise
+    pub fn initialize_farm(&mut self, bumps: &InitializeFarmBumps) -> Result<()> {
+        // Reject Token-2022 mints whose extensions would trap or drain the reward vault
+        validate_mint(&self.mint_reward)?;
+        let now = Clock::get()?.unix_timestamp;
+
+        self.farm.set_inner(Farm {
+            config: self.config.key(),
+            authority: self.authority.key(),
+            mint_reward: self.mint_reward.key(),
+            total_staked: 0,
+            reward_rate: 0,
+            period_end: now,                  // No reward period yet
+            last_update: now,
+            reward_per_share: 0,
+            bump: bumps.farm,
+        });
+
+        Ok(())
+    }
+}
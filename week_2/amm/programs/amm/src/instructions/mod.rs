@@ -8,4 +8,47 @@ pub mod withdraw;
 pub use withdraw::*;
 
 pub mod swap;
-pub use swap::*;
\ No newline at end of file
+pub use swap::*;
+
+pub mod admin;
+pub use admin::*;
+pub mod collect_protocol_fees;
+pub use collect_protocol_fees::*;
+
+pub mod initialize_concentrated;
+pub use initialize_concentrated::*;
+
+pub mod open_position;
+pub use open_position::*;
+
+pub mod modify_position;
+pub use modify_position::*;
+
+pub mod close_position;
+pub use close_position::*;
+
+pub mod swap_concentrated;
+pub use swap_concentrated::*;
+
+pub mod route_swap;
+pub use route_swap::*;
+
+pub mod quote_view;
+pub use quote_view::*;
+
+pub mod initialize_factory;
+pub use initialize_factory::*;
+pub mod factory_admin;
+pub use factory_admin::*;
+pub mod create_pool;
+pub use create_pool::*;
+
+pub mod flash_loan;
+pub use flash_loan::*;
+
+pub mod initialize_farm;
+pub use initialize_farm::*;
+pub mod fund_farm;
+pub use fund_farm::*;
+pub mod stake;
+pub use stake::*;
@@ -0,0 +1,356 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::concentrated_liquidity::{amounts_for_liquidity, sqrt_price_at_tick};
+use crate::errors::AmmError;
+use crate::events;
+use crate::states::{ConcentratedPool, Config, Position};
+use crate::utils::{amount_before_fee, check_expiration};
+
+/// Accounts required for changing a concentrated-liquidity position
+/// Shared by `increase_liquidity`, `decrease_liquidity` and `collect_fees`
+#[derive(Accounts)]
+pub struct ModifyPosition<'info> {
+    /// The position owner (must sign the transaction)
+    pub owner: Signer<'info>,
+
+    /// The mint account for token X in the trading pair
+    #[account(mint::token_program = token_program_x)]
+    pub mint_x: InterfaceAccount<'info, Mint>,
+
+    /// The mint account for token Y in the trading pair
+    #[account(mint::token_program = token_program_y)]
+    pub mint_y: InterfaceAccount<'info, Mint>,
+
+    /// The pool configuration account
+    #[account(
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+        has_one = mint_x,  // Ensures mint_x matches the one in config
+        has_one = mint_y,  // Ensures mint_y matches the one in config
+    )]
+    pub config: Account<'info, Config>,
+
+    /// Price, liquidity and tick state of the pool
+    /// Mutable because the position's ticks and the active liquidity change
+    #[account(
+        mut,
+        seeds = [b"pool", config.key().as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, ConcentratedPool>>,
+
+    /// The position being changed
+    #[account(
+        mut,
+        seeds = [
+            b"position",
+            config.key().as_ref(),
+            owner.key().as_ref(),
+            position.tick_lower.to_le_bytes().as_ref(),
+            position.tick_upper.to_le_bytes().as_ref(),
+        ],
+        bump = position.bump,
+        has_one = owner,
+        has_one = config,
+    )]
+    pub position: Account<'info, Position>,
+
+    /// The vault that holds all token X of the pool
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_x,
+    )]
+    pub vault_x: InterfaceAccount<'info, TokenAccount>,
+
+    /// The vault that holds all token Y of the pool
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_y,
+    )]
+    pub vault_y: InterfaceAccount<'info, TokenAccount>,
+
+    /// Owner's token account for token X
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_x,
+    )]
+    pub owner_ata_x: InterfaceAccount<'info, TokenAccount>,
+
+    /// Owner's token account for token Y
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_y,
+    )]
+    pub owner_ata_y: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token program owning mint_x (SPL Token or Token-2022)
+    pub token_program_x: Interface<'info, TokenInterface>,
+    /// Token program owning mint_y (SPL Token or Token-2022)
+    pub token_program_y: Interface<'info, TokenInterface>,
+}
+
+impl<'info> ModifyPosition<'info> {
+    /// Adds liquidity to the position
+    ///
+    /// The token amounts depend on where the price sits relative to the range:
+    /// only token X below it, only token Y above it, and both inside it.
+    ///
+    /// # Arguments
+    /// * `liquidity` - Liquidity to add
+    /// * `max_x` - Maximum amount of token X the owner is willing to send
+    /// * `max_y` - Maximum amount of token Y the owner is willing to send
+    /// * `expiration` - Optional unix timestamp after which the deposit is rejected
+    ///
+    /// # Returns
+    /// * `Result<()>` - Ok if successful, error otherwise
+    pub fn increase_liquidity(
+        &mut self,
+        liquidity: u128,
+        max_x: u64,
+        max_y: u64,
+        expiration: Option<i64>,
+    ) -> Result<()> {
+        check_expiration(expiration)?;
+        require!(!self.config.locked, AmmError::PoolLocked);
+        require!(liquidity != 0, AmmError::InvalidAmount);
+        let delta = i128::try_from(liquidity).map_err(|_| AmmError::Overflow)?;
+
+        // Amounts the vaults must receive, rounded up
+        let (need_x, need_y) = self.amounts(liquidity, true)?;
+        self.pool.modify_position(&mut self.position, delta)?;
+
+        // Transfer fees are withheld in transit, so send enough for the vaults to receive the amounts
+        let send_x = amount_before_fee(&self.mint_x, need_x)?;
+        let send_y = amount_before_fee(&self.mint_y, need_y)?;
+        require!(send_x <= max_x && send_y <= max_y, AmmError::SlippageExceeded);
+
+        let received_x = self.deposit_tokens(true, send_x)?;
+        let received_y = self.deposit_tokens(false, send_y)?;
+        require!(received_x >= need_x && received_y >= need_y, AmmError::SlippageExceeded);
+
+        emit!(events::PositionIncreased {
+            config: self.config.key(),
+            position: self.position.key(),
+            owner: self.owner.key(),
+            liquidity,
+            amount_x: received_x,
+            amount_y: received_y,
+            position_liquidity: self.position.liquidity,
+        });
+
+        Ok(())
+    }
+
+    /// Removes liquidity from the position and sends the tokens backing it to the owner
+    /// Fees earned so far stay in the position until `collect_fees`
+    ///
+    /// # Arguments
+    /// * `liquidity` - Liquidity to remove
+    /// * `min_x` - Minimum amount of token X the owner expects to receive
+    /// * `min_y` - Minimum amount of token Y the owner expects to receive
+    /// * `expiration` - Optional unix timestamp after which the withdrawal is rejected
+    ///
+    /// # Returns
+    /// * `Result<()>` - Ok if successful, error otherwise
+    pub fn decrease_liquidity(
+        &mut self,
+        liquidity: u128,
+        min_x: u64,
+        min_y: u64,
+        expiration: Option<i64>,
+    ) -> Result<()> {
+        check_expiration(expiration)?;
+        require!(!self.config.locked, AmmError::PoolLocked);
+        require!(
+            liquidity != 0 && liquidity <= self.position.liquidity,
+            AmmError::InvalidAmount
+        );
+        let delta = i128::try_from(liquidity).map_err(|_| AmmError::Overflow)?;
+
+        // Amounts the vaults pay out, rounded down
+        let (x, y) = self.amounts(liquidity, false)?;
+        self.pool.modify_position(&mut self.position, -delta)?;
+
+        let received_x = self.withdraw_tokens(true, x)?;
+        let received_y = self.withdraw_tokens(false, y)?;
+
+        // Slippage protection: the minimums apply to what the owner receives after transfer fees
+        require!(received_x >= min_x && received_y >= min_y, AmmError::SlippageExceeded);
+
+        emit!(events::PositionDecreased {
+            config: self.config.key(),
+            position: self.position.key(),
+            owner: self.owner.key(),
+            liquidity,
+            amount_x: x,
+            amount_y: y,
+            position_liquidity: self.position.liquidity,
+        });
+
+        Ok(())
+    }
+
+    /// Sends the swap fees the position has earned to the owner
+    ///
+    /// # Returns
+    /// * `Result<()>` - Ok if successful, error otherwise
+    pub fn collect_fees(&mut self) -> Result<()> {
+        // Credit the fees earned since the position was last updated
+        if self.position.liquidity != 0 {
+            self.pool.modify_position(&mut self.position, 0)?;
+        }
+
+        let (fees_x, fees_y) = (self.position.fees_owed_x, self.position.fees_owed_y);
+        require!(fees_x != 0 || fees_y != 0, AmmError::ZeroBalance);
+        self.position.fees_owed_x = 0;
+        self.position.fees_owed_y = 0;
+
+        self.withdraw_tokens(true, fees_x)?;
+        self.withdraw_tokens(false, fees_y)?;
+
+        Ok(())
+    }
+
+    /// Token amounts backing `liquidity` over the position's range at the current price
+    ///
+    /// # Arguments
+    /// * `liquidity` - Liquidity being added or removed
+    /// * `round_up` - true when the vaults receive the amounts, false when they pay them out
+    pub fn amounts(&self, liquidity: u128, round_up: bool) -> Result<(u64, u64)> {
+        amounts_for_liquidity(
+            self.pool.sqrt_price,
+            sqrt_price_at_tick(self.position.tick_lower)?,
+            sqrt_price_at_tick(self.position.tick_upper)?,
+            liquidity,
+            round_up,
+        )
+    }
+
+    /// Transfers tokens from the owner's account to the appropriate vault
+    ///
+    /// # Arguments
+    /// * `is_x` - true for token X, false for token Y
+    /// * `amount` - Amount of tokens to send
+    ///
+    /// # Returns
+    /// * `Result<u64>` - Amount the vault actually received after transfer fees
+    pub fn deposit_tokens(&mut self, is_x: bool, amount: u64) -> Result<u64> {
+        if amount == 0 {
+            return Ok(0);
+        }
+
+        // Select appropriate accounts based on token type
+        let (from, to, mint, decimals, cpi_program, balance_before) = match is_x {
+            true => (
+                self.owner_ata_x.to_account_info(),     // Transfer from owner's X account
+                self.vault_x.to_account_info(),         // Transfer to vault X
+                self.mint_x.to_account_info(),          // Token X mint
+                self.mint_x.decimals,                   // Token X decimals
+                self.token_program_x.to_account_info(), // Token X program
+                self.vault_x.amount,                    // Vault X balance before transfer
+            ),
+            false => (
+                self.owner_ata_y.to_account_info(),     // Transfer from owner's Y account
+                self.vault_y.to_account_info(),         // Transfer to vault Y
+                self.mint_y.to_account_info(),          // Token Y mint
+                self.mint_y.decimals,                   // Token Y decimals
+                self.token_program_y.to_account_info(), // Token Y program
+                self.vault_y.amount,                    // Vault Y balance before transfer
+            ),
+        };
+
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            mint,
+            authority: self.owner.to_account_info(),  // Owner signs the transfer
+        };
+        let cpi_context = CpiContext::new(cpi_program, cpi_accounts);
+        transfer_checked(cpi_context, amount, decimals)?;
+
+        // Re-read the vault to see what arrived after any transfer fee was withheld
+        let vault = match is_x {
+            true => &mut self.vault_x,
+            false => &mut self.vault_y,
+        };
+        vault.reload()?;
+        vault
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(AmmError::Underflow.into())
+    }
+
+    /// Transfers tokens from the appropriate vault to the owner's account
+    /// The config PDA is the vault authority and signs the transfer
+    ///
+    /// # Arguments
+    /// * `is_x` - true for token X, false for token Y
+    /// * `amount` - Amount of tokens to send
+    ///
+    /// # Returns
+    /// * `Result<u64>` - Amount the owner actually received after transfer fees
+    pub fn withdraw_tokens(&mut self, is_x: bool, amount: u64) -> Result<u64> {
+        if amount == 0 {
+            return Ok(0);
+        }
+
+        // Select appropriate accounts based on token type
+        let (from, to, mint, decimals, cpi_program, balance_before) = match is_x {
+            true => (
+                self.vault_x.to_account_info(),         // Transfer from vault X
+                self.owner_ata_x.to_account_info(),     // Transfer to owner's X account
+                self.mint_x.to_account_info(),          // Token X mint
+                self.mint_x.decimals,                   // Token X decimals
+                self.token_program_x.to_account_info(), // Token X program
+                self.owner_ata_x.amount,                // Owner X balance before transfer
+            ),
+            false => (
+                self.vault_y.to_account_info(),         // Transfer from vault Y
+                self.owner_ata_y.to_account_info(),     // Transfer to owner's Y account
+                self.mint_y.to_account_info(),          // Token Y mint
+                self.mint_y.decimals,                   // Token Y decimals
+                self.token_program_y.to_account_info(), // Token Y program
+                self.owner_ata_y.amount,                // Owner Y balance before transfer
+            ),
+        };
+
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            mint,
+            authority: self.config.to_account_info(),  // Config PDA signs the transfer
+        };
+
+        // Create signer seeds for config PDA
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"config",
+            &self.config.seed.to_le_bytes(),
+            &[self.config.config_bump],
+        ]];
+
+        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        transfer_checked(cpi_context, amount, decimals)?;
+
+        // Re-read the owner's account to see what arrived after any transfer fee was withheld
+        let destination = match is_x {
+            true => &mut self.owner_ata_x,
+            false => &mut self.owner_ata_y,
+        };
+        destination.reload()?;
+        destination
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(AmmError::Underflow.into())
+    }
+}
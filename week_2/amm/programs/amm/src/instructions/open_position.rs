@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+use crate::states::{ConcentratedPool, Config, CurveType, Position};
+
+/// Accounts required for opening an empty position over a tick range
+/// One position per owner and range; liquidity is added with `increase_liquidity`
+#[derive(Accounts)]
+#[instruction(tick_lower: i32, tick_upper: i32)]
+pub struct OpenPosition<'info> {
+    /// The liquidity provider (must sign the transaction)
+    /// Mutable because they pay for the position account
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// The pool configuration account
+    #[account(
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+        constraint = config.curve_type == CurveType::Concentrated @ AmmError::InvalidCurve,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// Price and tick state of the pool, read for its tick spacing
+    #[account(
+        seeds = [b"pool", config.key().as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, ConcentratedPool>>,
+
+    /// The new position
+    #[account(
+        init,
+        payer = owner,
+        seeds = [
+            b"position",
+            config.key().as_ref(),
+            owner.key().as_ref(),
+            tick_lower.to_le_bytes().as_ref(),
+            tick_upper.to_le_bytes().as_ref(),
+        ],
+        bump,
+        space = 8 + Position::INIT_SPACE
+    )]
+    pub position: Account<'info, Position>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> OpenPosition<'info> {
+    /// Creates an empty position between two ticks
+    ///
+    /// # Arguments
+    /// * `tick_lower` - Lower tick of the range, a multiple of the pool's tick spacing
+    /// * `tick_upper` - Upper tick of the range, a multiple of the pool's tick spacing
+    /// * `bumps` - Canonical bump values for PDAs (provided by Anchor)
+    ///
+    /// # Returns
+    /// * `Result<()>` - Ok if successful, error otherwise
+    pub fn open_position(
+        &mut self,
+        tick_lower: i32,
+        tick_upper: i32,
+        bumps: &OpenPositionBumps,
+    ) -> Result<()> {
+        self.pool.validate_range(tick_lower, tick_upper)?;
+
+        self.position.set_inner(Position {
+            config: self.config.key(),
+            owner: self.owner.key(),
+            tick_lower,
+            tick_upper,
+            liquidity: 0,
+            fee_growth_inside_x_last: 0,      // Set when liquidity is first added
+            fee_growth_inside_y_last: 0,
+            fees_owed_x: 0,
+            fees_owed_y: 0,
+            bump: bumps.position,
+        });
+
+        Ok(())
+    }
+}
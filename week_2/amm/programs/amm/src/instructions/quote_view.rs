@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::quote::{self, LiquidityQuote, SwapQuote};
+use crate::states::Config;
+
+/// Accounts read by the quote instructions, which change nothing and are meant to be
+/// simulated: `quote_swap_exact_in`, `quote_swap_exact_out`, `quote_deposit` and `quote_withdraw`
+#[derive(Accounts)]
+pub struct QuoteView<'info> {
+    /// The AMM pool configuration account
+    #[account(
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The LP token mint, read for its supply
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump,
+    )]
+    pub mint_lp: InterfaceAccount<'info, Mint>,
+
+    /// The vault holding token X
+    /// Derived with whichever token program owns it, so Token-2022 pools need no extra accounts
+    #[account(
+        associated_token::mint = config.mint_x,
+        associated_token::authority = config,
+        associated_token::token_program = vault_x.to_account_info().owner,
+    )]
+    pub vault_x: InterfaceAccount<'info, TokenAccount>,
+
+    /// The vault holding token Y
+    #[account(
+        associated_token::mint = config.mint_y,
+        associated_token::authority = config,
+        associated_token::token_program = vault_y.to_account_info().owner,
+    )]
+    pub vault_y: InterfaceAccount<'info, TokenAccount>,
+}
+
+impl<'info> QuoteView<'info> {
+    /// Quotes selling `amount_in` into the pool, as `swap` would price it now
+    ///
+    /// # Arguments
+    /// * `is_x` - true if selling token X for Y, false if selling Y for X
+    /// * `amount_in` - Amount the vault receives
+    pub fn quote_swap_exact_in(&self, is_x: bool, amount_in: u64) -> Result<SwapQuote> {
+        quote::quote_swap_exact_in(
+            &self.config,
+            self.vault_x.amount,
+            self.vault_y.amount,
+            self.mint_lp.supply,
+            is_x,
+            amount_in,
+            Clock::get()?.unix_timestamp,
+        )
+    }
+
+    /// Quotes buying exactly `amount_out` from the pool, as `swap_exact_out` would price it now
+    ///
+    /// # Arguments
+    /// * `is_x` - true if selling token X for Y, false if selling Y for X
+    /// * `amount_out` - Amount the vault sends
+    pub fn quote_swap_exact_out(&self, is_x: bool, amount_out: u64) -> Result<SwapQuote> {
+        quote::quote_swap_exact_out(
+            &self.config,
+            self.vault_x.amount,
+            self.vault_y.amount,
+            is_x,
+            amount_out,
+            Clock::get()?.unix_timestamp,
+        )
+    }
+
+    /// Quotes the tokens `deposit` needs to mint `lp_amount` LP tokens
+    ///
+    /// # Arguments
+    /// * `lp_amount` - LP tokens to mint
+    pub fn quote_deposit(&self, lp_amount: u64) -> Result<LiquidityQuote> {
+        quote::quote_deposit(
+            &self.config,
+            self.vault_x.amount,
+            self.vault_y.amount,
+            self.mint_lp.supply,
+            lp_amount,
+        )
+    }
+
+    /// Quotes the tokens `withdraw` returns for burning `lp_amount` LP tokens
+    ///
+    /// # Arguments
+    /// * `lp_amount` - LP tokens to burn
+    pub fn quote_withdraw(&self, lp_amount: u64) -> Result<LiquidityQuote> {
+        quote::quote_withdraw(
+            &self.config,
+            self.vault_x.amount,
+            self.vault_y.amount,
+            self.mint_lp.supply,
+            lp_amount,
+        )
+    }
+}
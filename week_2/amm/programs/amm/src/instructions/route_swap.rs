@@ -0,0 +1,330 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::constants::MAX_ROUTE_HOPS;
+use crate::errors::AmmError;
+use crate::events;
+use crate::quote::{post_swap_reserves, quote_exact_in};
+use crate::states::{Config, CurveType, Oracle};
+use crate::utils::check_expiration;
+
+/// Number of `remaining_accounts` each hop of a route takes, in this order:
+/// 1. `config` (mut) - Configuration of the pool
+/// 2. `oracle` (mut) - TWAP oracle of the pool
+/// 3. `mint_lp` - LP mint of the pool
+/// 4. `vault_in` (mut) - Pool vault of the token sold in this hop
+/// 5. `vault_out` (mut) - Pool vault of the token bought in this hop
+/// 6. `mint_out` - Mint of the token bought in this hop
+/// 7. `token_program_out` - Token program owning `mint_out`
+pub const HOP_ACCOUNTS: usize = 7;
+
+/// Accounts required for a multi-hop swap through several pools
+/// The pools themselves are passed in `remaining_accounts`, `HOP_ACCOUNTS` per hop
+#[derive(Accounts)]
+pub struct RouteSwap<'info> {
+    /// The user who is performing the swap (must sign the transaction)
+    /// Mutable because they may need to pay for the output token account
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// Mint of the token the user sells into the first pool
+    #[account(mint::token_program = token_program_in)]
+    pub mint_in: InterfaceAccount<'info, Mint>,
+
+    /// Mint of the token the user receives from the last pool
+    #[account(mint::token_program = token_program_out)]
+    pub mint_out: InterfaceAccount<'info, Mint>,
+
+    /// User's token account for the input token
+    #[account(
+        mut,
+        associated_token::mint = mint_in,
+        associated_token::authority = user,
+        associated_token::token_program = token_program_in,
+    )]
+    pub user_ata_in: InterfaceAccount<'info, TokenAccount>,
+
+    /// User's token account for the output token
+    /// Will be created if it doesn't exist, user pays for creation
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_out,
+        associated_token::authority = user,
+        associated_token::token_program = token_program_out,
+    )]
+    pub user_ata_out: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token program owning mint_in (SPL Token or Token-2022)
+    pub token_program_in: Interface<'info, TokenInterface>,
+    /// Token program owning mint_out (SPL Token or Token-2022)
+    pub token_program_out: Interface<'info, TokenInterface>,
+    /// Associated Token program for ATA operations
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Pool vault holding the output of the previous hop, waiting to be sent on
+struct PendingOutput<'info> {
+    /// Vault the output leaves from
+    vault: AccountInfo<'info>,
+    /// Config PDA owning the vault, signs the transfer
+    config: AccountInfo<'info>,
+    /// Seed of the config PDA
+    seed: [u8; 8],
+    /// Bump of the config PDA
+    bump: u8,
+    /// Amount the pool owes
+    amount: u64,
+}
+
+impl<'info> RouteSwap<'info> {
+    /// Swaps through an ordered list of constant product or StableSwap pools
+    ///
+    /// The swap process follows these steps:
+    /// 1. Deposit the user's input into the first pool's vault
+    /// 2. For every hop, price the swap on the amount the pool actually received and
+    ///    send the output straight into the next pool's vault
+    /// 3. Send the last pool's output to the user
+    /// 4. Validate slippage protection once, on the amount the user received
+    ///
+    /// Each hop reads its reserves and updates its oracle before its input arrives, and
+    /// accrues the protocol's share of its fee, exactly like `swap`.
+    ///
+    /// # Arguments
+    /// * `hops` - `HOP_ACCOUNTS` accounts per pool, in route order
+    /// * `amount_in` - Amount of input tokens to swap
+    /// * `min_amount_out` - Minimum amount of output tokens expected (slippage protection)
+    /// * `expiration` - Optional unix timestamp after which the swap is rejected
+    ///
+    /// # Returns
+    /// * `Result<()>` - Ok if successful, error otherwise
+    pub fn route_swap(
+        &mut self,
+        hops: &'info [AccountInfo<'info>],
+        amount_in: u64,
+        min_amount_out: u64,
+        expiration: Option<i64>,
+    ) -> Result<()> {
+        // Ensure the transaction didn't land after the user's deadline
+        check_expiration(expiration)?;
+        // Ensure user is swapping a positive amount
+        require!(amount_in > 0, AmmError::InvalidAmount);
+        let route = hops.chunks_exact(HOP_ACCOUNTS);
+        require!(
+            route.remainder().is_empty() && (1..=MAX_ROUTE_HOPS).contains(&route.len()),
+            AmmError::InvalidRoute
+        );
+
+        // Token entering the current hop
+        let mut mint_in = self.mint_in.to_account_info();
+        let mut decimals_in = self.mint_in.decimals;
+        let mut token_program_in = self.token_program_in.to_account_info();
+        let mut pending: Option<PendingOutput<'info>> = None;
+        let mut visited: Vec<Pubkey> = Vec::with_capacity(MAX_ROUTE_HOPS);
+
+        for accounts in route {
+            let [config_info, oracle_info, mint_lp_info, vault_in_info, vault_out_info, mint_out_info, token_program_out_info] =
+                accounts
+            else {
+                return err!(AmmError::InvalidRoute);
+            };
+
+            // Account::try_from checks the owner and discriminator of the pool accounts
+            let mut config = Account::<Config>::try_from(config_info)?;
+            let mut oracle = Box::new(Account::<Oracle>::try_from(oracle_info)?);
+            // A pool visited twice would have its first update overwritten
+            require!(!visited.contains(&config.key()), AmmError::InvalidRoute);
+            visited.push(config.key());
+            require_keys_eq!(oracle.config, config.key(), AmmError::InvalidRoute);
+            require!(!config.locked, AmmError::PoolLocked);
+            require!(
+                config.curve_type != CurveType::Concentrated,
+                AmmError::InvalidCurve
+            );
+
+            // The hop sells mint_in for the pool's other token
+            let is_x = match mint_in.key() {
+                key if key == config.mint_x => true,
+                key if key == config.mint_y => false,
+                _ => return err!(AmmError::InvalidToken),
+            };
+            let expected_out = if is_x { config.mint_y } else { config.mint_x };
+            require_keys_eq!(mint_out_info.key(), expected_out, AmmError::InvalidToken);
+
+            // Only the pool's own LP mint and vaults are accepted
+            let mint_lp_key = Pubkey::create_program_address(
+                &[b"lp", config.key().as_ref(), &[config.lp_bump]],
+                &crate::ID,
+            )
+            .map_err(|_| AmmError::InvalidRoute)?;
+            require_keys_eq!(mint_lp_info.key(), mint_lp_key, AmmError::InvalidRoute);
+            let token_program_out = Interface::<TokenInterface>::try_from(token_program_out_info)?;
+            require_keys_eq!(*mint_out_info.owner, token_program_out.key(), AmmError::InvalidToken);
+            require_keys_eq!(
+                vault_in_info.key(),
+                get_associated_token_address_with_program_id(&config.key(), &mint_in.key(), &token_program_in.key()),
+                AmmError::InvalidRoute
+            );
+            require_keys_eq!(
+                vault_out_info.key(),
+                get_associated_token_address_with_program_id(&config.key(), &mint_out_info.key(), &token_program_out.key()),
+                AmmError::InvalidRoute
+            );
+
+            let lp_supply = InterfaceAccount::<Mint>::try_from(mint_lp_info)?.supply;
+            let mint_out = InterfaceAccount::<Mint>::try_from(mint_out_info)?;
+            let mut vault_in = InterfaceAccount::<TokenAccount>::try_from(vault_in_info)?;
+            let vault_out = InterfaceAccount::<TokenAccount>::try_from(vault_out_info)?;
+
+            // Uncollected protocol fees sit in the vaults but aren't tradable liquidity
+            let (vault_x, vault_y) = match is_x {
+                true => (vault_in.amount, vault_out.amount),
+                false => (vault_out.amount, vault_in.amount),
+            };
+            let (reserve_x, reserve_y) = config.reserves(vault_x, vault_y)?;
+
+            // Accrue the price from before this hop into the TWAP oracle
+            oracle.update(reserve_x, reserve_y)?;
+
+            // Move this hop's input into the vault, from the user or from the previous pool
+            let balance_before = vault_in.amount;
+            match pending.take() {
+                None => self.deposit_token(vault_in_info.clone(), amount_in)?,
+                Some(output) => send_output(
+                    &output,
+                    vault_in_info.clone(),
+                    mint_in.clone(),
+                    decimals_in,
+                    token_program_in.clone(),
+                )?,
+            }
+            vault_in.reload()?;
+            let received_in = vault_in
+                .amount
+                .checked_sub(balance_before)
+                .ok_or(AmmError::Underflow)?;
+
+            // Price the hop on what the vault actually received
+            let now = Clock::get()?.unix_timestamp;
+            let (amount_out, fee) =
+                quote_exact_in(&config, is_x, reserve_x, reserve_y, lp_supply, received_in, now)?;
+            require!(amount_out != 0, AmmError::InvalidAmount);
+
+            // Set aside the protocol's share of the fee, paid in the input token
+            // protocol_fee <= 10_000 so the share always fits back into a u64
+            let share = (fee as u128 * config.protocol_fee as u128 / 10_000) as u64;
+            let accrued = match is_x {
+                true => &mut config.protocol_fees_x,
+                false => &mut config.protocol_fees_y,
+            };
+            *accrued = accrued.checked_add(share).ok_or(AmmError::Overflow)?;
+
+            // Feed this hop's price move into the pool's dynamic fee
+            let fee_bps = config.swap_fee(now);
+            let pool_in = received_in - share;
+            let after = post_swap_reserves(is_x, (reserve_x, reserve_y), pool_in, amount_out)?;
+            config.record_price_move(now, (reserve_x, reserve_y), after)?;
+
+            emit!(events::Swap {
+                config: config.key(),
+                is_x,
+                amount_in: received_in,
+                amount_out,
+                fee,
+                protocol_fee: share,
+                fee_bps,
+                reserve_x: after.0,
+                reserve_y: after.1,
+            });
+
+            // Persist the pool accounts loaded from remaining_accounts
+            config.exit(&crate::ID)?;
+            oracle.exit(&crate::ID)?;
+
+            pending = Some(PendingOutput {
+                vault: vault_out_info.clone(),
+                config: config_info.clone(),
+                seed: config.seed.to_le_bytes(),
+                bump: config.config_bump,
+                amount: amount_out,
+            });
+            mint_in = mint_out_info.clone();
+            decimals_in = mint_out.decimals;
+            token_program_in = token_program_out_info.clone();
+        }
+
+        // The route must end in the token the user asked for
+        require_keys_eq!(mint_in.key(), self.mint_out.key(), AmmError::InvalidRoute);
+        let output = pending.ok_or(AmmError::InvalidRoute)?;
+
+        let balance_before = self.user_ata_out.amount;
+        send_output(
+            &output,
+            self.user_ata_out.to_account_info(),
+            mint_in,
+            decimals_in,
+            token_program_in,
+        )?;
+        self.user_ata_out.reload()?;
+        let received_out = self
+            .user_ata_out
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(AmmError::Underflow)?;
+
+        // Slippage protection: only the final output is checked
+        require!(received_out >= min_amount_out, AmmError::SlippageExceeded);
+
+        Ok(())
+    }
+
+    /// Transfers the user's input into the first pool's vault
+    ///
+    /// # Arguments
+    /// * `vault` - Input vault of the first pool
+    /// * `amount` - Amount of tokens to send
+    ///
+    /// # Returns
+    /// * `Result<()>` - Ok if successful, error otherwise
+    pub fn deposit_token(&self, vault: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let cpi_accounts = TransferChecked {
+            from: self.user_ata_in.to_account_info(),
+            to: vault,
+            mint: self.mint_in.to_account_info(),
+            authority: self.user.to_account_info(),  // User signs the transfer
+        };
+        let cpi_context = CpiContext::new(self.token_program_in.to_account_info(), cpi_accounts);
+        transfer_checked(cpi_context, amount, self.mint_in.decimals)
+    }
+}
+
+/// Transfers a hop's output out of its pool vault, signed by the pool's config PDA
+///
+/// # Arguments
+/// * `output` - Vault, signer and amount of the pending output
+/// * `to` - Next pool's input vault, or the user's output token account
+/// * `mint` - Mint of the token being transferred
+/// * `decimals` - Decimals of the mint
+/// * `token_program` - Token program owning the mint
+fn send_output<'info>(
+    output: &PendingOutput<'info>,
+    to: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    decimals: u8,
+    token_program: AccountInfo<'info>,
+) -> Result<()> {
+    let cpi_accounts = TransferChecked {
+        from: output.vault.clone(),
+        to,
+        mint,
+        authority: output.config.clone(),  // Config PDA signs the transfer
+    };
+    let signer_seeds: &[&[&[u8]]] = &[&[b"config", &output.seed, &[output.bump]]];
+    let cpi_context = CpiContext::new_with_signer(token_program, cpi_accounts, signer_seeds);
+    transfer_checked(cpi_context, output.amount, decimals)
+}
@@ -0,0 +1,224 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::Token,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::errors::AmmError;
+use crate::states::{Config, Farm, Stake};
+
+/// Accounts required for staking LP tokens in a farm and collecting the rewards
+/// Shared by `stake_lp`, `unstake_lp` and `harvest`
+#[derive(Accounts)]
+pub struct ManageStake<'info> {
+    /// The staker (must sign the transaction)
+    /// Mutable because they pay for their stake and reward token accounts on first use
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// The AMM pool configuration account, read for its LP mint
+    #[account(
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The LP token mint of the pool
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump,
+        mint::token_program = token_program,
+    )]
+    pub mint_lp: InterfaceAccount<'info, Mint>,
+
+    /// The mint of the reward token
+    #[account(mint::token_program = token_program_reward)]
+    pub mint_reward: InterfaceAccount<'info, Mint>,
+
+    /// The pool's farm
+    /// Mutable because the reward accumulator and the total stake change
+    #[account(
+        mut,
+        seeds = [b"farm", config.key().as_ref()],
+        bump = farm.bump,
+        has_one = mint_reward,
+    )]
+    pub farm: Account<'info, Farm>,
+
+    /// The user's stake in the farm
+    /// Will be created on the first use, user pays for creation
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [b"stake", farm.key().as_ref(), user.key().as_ref()],
+        bump,
+        space = 8 + Stake::INIT_SPACE
+    )]
+    pub stake: Account<'info, Stake>,
+
+    /// Holds the staked LP tokens, owned by the farm PDA
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = farm,
+        associated_token::token_program = token_program,
+    )]
+    pub lp_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Holds the reward tokens not yet harvested, owned by the farm PDA
+    #[account(
+        mut,
+        associated_token::mint = mint_reward,
+        associated_token::authority = farm,
+        associated_token::token_program = token_program_reward,
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// User's token account for LP tokens
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = user,
+        associated_token::token_program = token_program,
+    )]
+    pub user_ata_lp: InterfaceAccount<'info, TokenAccount>,
+
+    /// User's token account for the reward token
+    /// Will be created if it doesn't exist, user pays for creation
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_reward,
+        associated_token::authority = user,
+        associated_token::token_program = token_program_reward,
+    )]
+    pub user_ata_reward: InterfaceAccount<'info, TokenAccount>,
+
+    /// SPL Token program for the LP mint
+    pub token_program: Program<'info, Token>,
+    /// Token program owning mint_reward (SPL Token or Token-2022)
+    pub token_program_reward: Interface<'info, TokenInterface>,
+    /// Associated Token program for ATA operations
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ManageStake<'info> {
+    /// Stakes LP tokens in the farm
+    /// Rewards earned so far stay owed until `harvest`
+    ///
+    /// # Arguments
+    /// * `amount` - Amount of LP tokens to stake
+    /// * `bumps` - Canonical bump values for PDAs (provided by Anchor)
+    ///
+    /// # Returns
+    /// * `Result<()>` - Ok if successful, error otherwise
+    pub fn stake_lp(&mut self, amount: u64, bumps: &ManageStakeBumps) -> Result<()> {
+        require!(amount != 0, AmmError::InvalidAmount);
+
+        // A stake account created just now starts with an empty checkpoint
+        if self.stake.owner == Pubkey::default() {
+            self.stake.farm = self.farm.key();
+            self.stake.owner = self.user.key();
+            self.stake.bump = bumps.stake;
+        }
+        self.accrue()?;
+
+        self.stake.amount = self.stake.amount.checked_add(amount).ok_or(AmmError::Overflow)?;
+        self.farm.total_staked = self.farm.total_staked.checked_add(amount).ok_or(AmmError::Overflow)?;
+
+        // Set up transfer instruction accounts
+        let cpi_accounts = TransferChecked {
+            from: self.user_ata_lp.to_account_info(),
+            to: self.lp_vault.to_account_info(),
+            mint: self.mint_lp.to_account_info(),
+            authority: self.user.to_account_info(),  // User signs the transfer
+        };
+        let cpi_context = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+        transfer_checked(cpi_context, amount, self.mint_lp.decimals)
+    }
+
+    /// Returns staked LP tokens to the user
+    /// Rewards earned so far stay owed until `harvest`
+    ///
+    /// # Arguments
+    /// * `amount` - Amount of LP tokens to unstake
+    ///
+    /// # Returns
+    /// * `Result<()>` - Ok if successful, error otherwise
+    pub fn unstake_lp(&mut self, amount: u64) -> Result<()> {
+        require!(amount != 0, AmmError::InvalidAmount);
+        require!(amount <= self.stake.amount, AmmError::InsufficientBalance);
+        self.accrue()?;
+
+        self.stake.amount -= amount;
+        self.farm.total_staked -= amount;
+
+        let mint = self.mint_lp.to_account_info();
+        let decimals = self.mint_lp.decimals;
+        let (from, to) = (self.lp_vault.to_account_info(), self.user_ata_lp.to_account_info());
+        self.send_from_farm(self.token_program.to_account_info(), from, to, mint, decimals, amount)
+    }
+
+    /// Sends the rewards the stake has earned to the user
+    ///
+    /// # Returns
+    /// * `Result<()>` - Ok if successful, error otherwise
+    pub fn harvest(&mut self) -> Result<()> {
+        self.accrue()?;
+
+        let rewards = self.stake.rewards_owed;
+        require!(rewards != 0, AmmError::ZeroBalance);
+        self.stake.rewards_owed = 0;
+
+        let mint = self.mint_reward.to_account_info();
+        let decimals = self.mint_reward.decimals;
+        let (from, to) = (self.reward_vault.to_account_info(), self.user_ata_reward.to_account_info());
+        self.send_from_farm(self.token_program_reward.to_account_info(), from, to, mint, decimals, rewards)
+    }
+
+    /// Brings the farm's accumulator up to date and credits the stake's rewards
+    pub fn accrue(&mut self) -> Result<()> {
+        self.farm.update(Clock::get()?.unix_timestamp)?;
+        self.stake.accrue_rewards(self.farm.reward_per_share)
+    }
+
+    /// Transfers tokens out of one of the farm's token accounts
+    /// The farm PDA owns them and signs the transfer
+    ///
+    /// # Arguments
+    /// * `cpi_program` - Token program owning the mint
+    /// * `from` - Farm token account to send from
+    /// * `to` - User token account to send to
+    /// * `mint` - Mint of the tokens
+    /// * `decimals` - Decimals of the mint
+    /// * `amount` - Amount of tokens to send
+    ///
+    /// # Returns
+    /// * `Result<()>` - Ok if successful, error otherwise
+    pub fn send_from_farm(
+        &self,
+        cpi_program: AccountInfo<'info>,
+        from: AccountInfo<'info>,
+        to: AccountInfo<'info>,
+        mint: AccountInfo<'info>,
+        decimals: u8,
+        amount: u64,
+    ) -> Result<()> {
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            mint,
+            authority: self.farm.to_account_info(),  // Farm PDA signs the transfer
+        };
+
+        // Create signer seeds for farm PDA
+        let config = self.config.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"farm", config.as_ref(), &[self.farm.bump]]];
+
+        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        transfer_checked(cpi_context, amount, decimals)
+    }
+}
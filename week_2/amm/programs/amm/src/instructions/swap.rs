@@ -1,12 +1,15 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{transfer_checked, Mint, Token, TokenAccount, TransferChecked},
+    token::Token,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
-use constant_product_curve::{ConstantProduct, LiquidityPair};
 
 use crate::errors::AmmError;
-use crate::states::Config;
+use crate::events;
+use crate::quote::{post_swap_reserves, quote_swap_exact_in, quote_swap_exact_out};
+use crate::states::{Config, Oracle};
+use crate::utils::{amount_before_fee, check_expiration};
 
 /// Accounts required for performing token swaps in the AMM pool
 /// This struct defines all the accounts needed to execute a swap operation
@@ -20,18 +23,20 @@ pub struct Swap<'info> {
 
     /// The mint account for token X in the trading pair
     /// Immutable as we only need to read mint information for transfers
-    #[account(mint::token_program = token_program)]
-    pub mint_x: Account<'info, Mint>,
+    #[account(mint::token_program = token_program_x)]
+    pub mint_x: InterfaceAccount<'info, Mint>,
 
     /// The mint account for token Y in the trading pair
     /// Immutable as we only need to read mint information for transfers
-    #[account(mint::token_program = token_program)]
-    pub mint_y: Account<'info, Mint>,
+    #[account(mint::token_program = token_program_y)]
+    pub mint_y: InterfaceAccount<'info, Mint>,
 
     /// The AMM pool configuration account
     /// Contains pool settings, fees, and references to the token mints
+    /// Mutable because the protocol's share of the swap fee is accrued here
     /// Uses PDA derived from "config" seed and config.seed
     #[account(
+        mut,
         seeds = [b"config", config.seed.to_le_bytes().as_ref()],
         bump = config.config_bump,
         has_one = mint_x,  // Ensures mint_x matches the one in config
@@ -39,14 +44,24 @@ pub struct Swap<'info> {
     )]
     pub config: Account<'info, Config>,
 
+    /// The TWAP price oracle for this pool
+    /// Mutable because the price accumulators are updated before the reserves change
+    #[account(
+        mut,
+        seeds = [b"oracle", config.key().as_ref()],
+        bump = oracle.bump,
+    )]
+    pub oracle: Box<Account<'info, Oracle>>,
+
     /// The LP (Liquidity Provider) token mint
     /// Used for reading supply information in swap calculations
     /// Uses PDA derived from "lp" seed and config pubkey
     #[account(
         seeds = [b"lp", config.key().as_ref()],
-        bump = config.lp_bump
+        bump = config.lp_bump,
+        mint::token_program = token_program,
     )]
-    pub mint_lp: Account<'info, Mint>,
+    pub mint_lp: InterfaceAccount<'info, Mint>,
 
     /// The vault that holds all deposited token X
     /// Mutable because swap operations either deposit to or withdraw from this vault
@@ -55,9 +70,9 @@ pub struct Swap<'info> {
         mut,
         associated_token::mint = mint_x,
         associated_token::authority = config,
-        associated_token::token_program = token_program,
+        associated_token::token_program = token_program_x,
     )]
-    pub vault_x: Account<'info, TokenAccount>,
+    pub vault_x: InterfaceAccount<'info, TokenAccount>,
 
     /// The vault that holds all deposited token Y
     /// Mutable because swap operations either deposit to or withdraw from this vault
@@ -66,9 +81,9 @@ pub struct Swap<'info> {
         mut,
         associated_token::mint = mint_y,
         associated_token::authority = config,
-        associated_token::token_program = token_program,
+        associated_token::token_program = token_program_y,
     )]
-    pub vault_y: Account<'info, TokenAccount>,
+    pub vault_y: InterfaceAccount<'info, TokenAccount>,
 
     /// User's token account for token X
     /// Will be created if it doesn't exist, user pays for creation
@@ -78,9 +93,9 @@ pub struct Swap<'info> {
         payer = user,
         associated_token::mint = mint_x,
         associated_token::authority = user,
-        associated_token::token_program = token_program
+        associated_token::token_program = token_program_x
     )]
-    pub user_ata_x: Account<'info, TokenAccount>,
+    pub user_ata_x: InterfaceAccount<'info, TokenAccount>,
 
     /// User's token account for token Y
     /// Will be created if it doesn't exist, user pays for creation
@@ -90,12 +105,16 @@ pub struct Swap<'info> {
         payer = user,
         associated_token::mint = mint_y,
         associated_token::authority = user,
-        associated_token::token_program = token_program
+        associated_token::token_program = token_program_y
     )]
-    pub user_ata_y: Account<'info, TokenAccount>,
+    pub user_ata_y: InterfaceAccount<'info, TokenAccount>,
 
-    /// SPL Token program for token operations
+    /// SPL Token program for the LP mint
     pub token_program: Program<'info, Token>,
+    /// Token program owning mint_x (SPL Token or Token-2022)
+    pub token_program_x: Interface<'info, TokenInterface>,
+    /// Token program owning mint_y (SPL Token or Token-2022)
+    pub token_program_y: Interface<'info, TokenInterface>,
     /// Associated Token program for ATA operations
     pub associated_token_program: Program<'info, AssociatedToken>,
     /// System program for account creation
@@ -103,61 +122,231 @@ pub struct Swap<'info> {
 }
 
 impl<'info> Swap<'info> {
-    /// Main swap function that handles token exchanges using constant product formula
+    /// Main swap function that handles token exchanges using the pool's curve
     /// 
     /// The swap process follows these steps:
     /// 1. Validate pool is not locked and amounts are valid
-    /// 2. Initialize constant product curve with current pool state
-    /// 3. Calculate swap amounts using the curve (accounting for fees)
-    /// 4. Validate slippage protection (output meets minimum requirement)
-    /// 5. Deposit input tokens to appropriate vault
-    /// 6. Withdraw output tokens from appropriate vault to user
+    /// 2. Read the pool reserves, excluding uncollected protocol fees
+    /// 3. Deposit input tokens to appropriate vault
+    /// 4. Calculate swap amounts with the pool's curve from the amount the vault received
+    /// 5. Withdraw output tokens from appropriate vault to user
+    /// 6. Validate slippage protection on the amount the user received
+    /// 7. Record the price move behind the dynamic fee and emit a `Swap` event
+    ///
+    /// Token-2022 transfer fees are withheld on both legs, so the curve only ever
+    /// sees amounts that actually reached the vault.
     /// 
-    /// The constant product formula (x * y = k) ensures that the product of
-    /// token reserves remains constant after accounting for fees.
+    /// Constant product pools keep x * y = k and StableSwap pools keep their
+    /// amplified invariant D, both after accounting for fees.
     /// 
     /// # Arguments
     /// * `is_x` - true if swapping token X for Y, false if swapping Y for X
     /// * `amount_in` - Amount of input tokens to swap
     /// * `min_amount_out` - Minimum amount of output tokens expected (slippage protection)
+    /// * `expiration` - Optional unix timestamp after which the swap is rejected
     /// 
     /// # Returns
     /// * `Result<()>` - Ok if successful, error otherwise
-    pub fn swap(&mut self, is_x: bool, amount_in: u64, min_amount_out: u64) -> Result<()> {
+    pub fn swap(
+        &mut self,
+        is_x: bool,
+        amount_in: u64,
+        min_amount_out: u64,
+        expiration: Option<i64>,
+    ) -> Result<()> {
+        // Ensure the transaction didn't land after the user's deadline
+        check_expiration(expiration)?;
         // Ensure the pool is not locked for swaps
         require!(!self.config.locked, AmmError::PoolLocked);
         // Ensure user is swapping a positive amount
         require!(amount_in > 0, AmmError::InvalidAmount);
 
-        // Initialize constant product curve with current pool state
-        let mut curve = ConstantProduct::init(
-            self.vault_x.amount,    // Current token X reserves
-            self.vault_y.amount,    // Current token Y reserves
-            self.mint_lp.supply,    // Current LP token supply
-            self.config.fee,        // Trading fee in basis points
-            None,                   // No additional configuration
-        )
-        .map_err(AmmError::from)?;
+        // Uncollected protocol fees sit in the vaults but aren't tradable liquidity
+        let (vault_x, vault_y) = (self.vault_x.amount, self.vault_y.amount);
+        let (reserve_x, reserve_y) = self.config.reserves(vault_x, vault_y)?;
 
-        // Determine which token is being swapped in
-        let p = match is_x {
-            true => LiquidityPair::X,   // Swapping X for Y
-            false => LiquidityPair::Y,  // Swapping Y for X
-        };
+        // Accrue the price from before this instruction into the TWAP oracle
+        self.oracle.update(reserve_x, reserve_y)?;
+
+        // Deposit input tokens first to learn how much reached the vault
+        let received_in = self.deposit_token(is_x, amount_in)?;
 
-        // Calculate swap amounts using constant product formula
+        // Calculate swap amounts with the pool's invariant, on the balances before the deposit
         // This accounts for fees and maintains the invariant
-        let swap_result = curve
-            .swap(p, amount_in, min_amount_out)
-            .map_err(AmmError::from)?;
+        // Slippage is checked below, against what the user actually receives
+        let quote = quote_swap_exact_in(
+            &self.config,
+            vault_x,
+            vault_y,
+            self.mint_lp.supply,
+            is_x,
+            received_in,
+            Clock::get()?.unix_timestamp,
+        )?;
 
         // Validate that the calculated amounts are valid
-        require!(swap_result.deposit != 0, AmmError::InvalidAmount);
-        require!(swap_result.withdraw != 0, AmmError::InvalidAmount);
+        require!(quote.amount_out != 0, AmmError::InvalidAmount);
+
+        // Set aside the protocol's share of the fee, paid in the input token
+        self.accrue_protocol_fee(is_x, quote.protocol_fee)?;
+
+        // Withdraw output tokens to the user
+        let received_out = self.withdraw_token(!is_x, quote.amount_out)?;
+
+        // Slippage protection: output after transfer fees must meet the user's minimum
+        require!(received_out >= min_amount_out, AmmError::SlippageExceeded);
+
+        self.finish_swap(
+            is_x,
+            (reserve_x, reserve_y),
+            received_in,
+            quote.amount_out,
+            (quote.fee, quote.protocol_fee),
+        )
+    }
+
+    /// Exact-output swap: the user receives exactly `amount_out` of the output token
+    ///
+    /// The swap process follows these steps:
+    /// 1. Validate pool is not locked and amounts are valid
+    /// 2. Add any Token-2022 transfer fee the vault will pay on the output
+    /// 3. Solve the pool's invariant for the input the vault must receive
+    /// 4. Add any Token-2022 transfer fee on the input and validate slippage protection
+    /// 5. Deposit input tokens to appropriate vault
+    /// 6. Withdraw output tokens from appropriate vault to user
+    /// 7. Record the price move behind the dynamic fee and emit a `Swap` event
+    ///
+    /// Every rounding step goes in the pool's favor, so k never decreases.
+    ///
+    /// # Arguments
+    /// * `is_x` - true if swapping token X for Y, false if swapping Y for X
+    /// * `amount_out` - Exact amount of output tokens the user wants to receive
+    /// * `max_amount_in` - Maximum amount of input tokens the user is willing to send
+    /// * `expiration` - Optional unix timestamp after which the swap is rejected
+    ///
+    /// # Returns
+    /// * `Result<()>` - Ok if successful, error otherwise
+    pub fn swap_exact_out(
+        &mut self,
+        is_x: bool,
+        amount_out: u64,
+        max_amount_in: u64,
+        expiration: Option<i64>,
+    ) -> Result<()> {
+        // Ensure the transaction didn't land after the user's deadline
+        check_expiration(expiration)?;
+        // Ensure the pool is not locked for swaps
+        require!(!self.config.locked, AmmError::PoolLocked);
+        // Ensure user is asking for a positive amount
+        require!(amount_out > 0, AmmError::InvalidAmount);
+
+        // Uncollected protocol fees sit in the vaults but aren't tradable liquidity
+        let (vault_x, vault_y) = (self.vault_x.amount, self.vault_y.amount);
+        let (reserve_x, reserve_y) = self.config.reserves(vault_x, vault_y)?;
+
+        // Accrue the price from before this instruction into the TWAP oracle
+        self.oracle.update(reserve_x, reserve_y)?;
+        let (mint_in, mint_out) = match is_x {
+            true => (&self.mint_x, &self.mint_y),   // Swapping X for Y
+            false => (&self.mint_y, &self.mint_x),  // Swapping Y for X
+        };
+
+        // The vault sends enough for the user to receive amount_out after transfer fees
+        let vault_out = amount_before_fee(mint_out, amount_out)?;
+
+        // Input the vault must receive, and the swap fee included in it
+        let quote = quote_swap_exact_out(
+            &self.config,
+            vault_x,
+            vault_y,
+            is_x,
+            vault_out,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        // The user sends enough for the vault to receive the quoted input after transfer fees
+        let amount_in = amount_before_fee(mint_in, quote.amount_in)?;
+
+        // Slippage protection: the input must stay within the user's limit
+        require!(amount_in <= max_amount_in, AmmError::SlippageExceeded);
 
         // Execute the swap by depositing input tokens and withdrawing output tokens
-        self.deposit_token(is_x, swap_result.deposit)?;      // Deposit input tokens
-        self.withdraw_token(!is_x, swap_result.withdraw)?;   // Withdraw output tokens
+        let received_in = self.deposit_token(is_x, amount_in)?;
+        require!(received_in >= quote.amount_in, AmmError::SlippageExceeded);
+
+        // Set aside the protocol's share of the fee, paid in the input token
+        self.accrue_protocol_fee(is_x, quote.protocol_fee)?;
+
+        let received_out = self.withdraw_token(!is_x, vault_out)?;
+        require!(received_out >= amount_out, AmmError::SlippageExceeded);
+
+        self.finish_swap(
+            is_x,
+            (reserve_x, reserve_y),
+            received_in,
+            vault_out,
+            (quote.fee, quote.protocol_fee),
+        )
+    }
+
+    /// Records the price move of a completed swap and emits a `Swap` event
+    ///
+    /// # Arguments
+    /// * `is_x` - true if token X was sold for Y, false if Y was sold for X
+    /// * `reserves` - Pool reserves (x, y) before the swap, excluding protocol fees
+    /// * `received_in` - Input the vault received, fee included
+    /// * `pool_out` - Output that left the pool
+    /// * `fees` - Total swap fee charged by the curve, and the protocol's share of it
+    ///
+    /// # Returns
+    /// * `Result<()>` - Ok if successful, error otherwise
+    fn finish_swap(
+        &mut self,
+        is_x: bool,
+        reserves: (u64, u64),
+        received_in: u64,
+        pool_out: u64,
+        (fee, protocol_fee): (u64, u64),
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        // The fee this swap paid, read before its own price move raises it
+        let fee_bps = self.config.swap_fee(now);
+
+        // The protocol's share sits in the vault but not in the reserves
+        let pool_in = received_in.checked_sub(protocol_fee).ok_or(AmmError::Underflow)?;
+        let after = post_swap_reserves(is_x, reserves, pool_in, pool_out)?;
+        self.config.record_price_move(now, reserves, after)?;
+
+        emit!(events::Swap {
+            config: self.config.key(),
+            is_x,
+            amount_in: received_in,
+            amount_out: pool_out,
+            fee,
+            protocol_fee,
+            fee_bps,
+            reserve_x: after.0,
+            reserve_y: after.1,
+        });
+
+        Ok(())
+    }
+
+    /// Records the protocol's share of a swap fee in the config
+    /// The tokens stay in the vault until `collect_protocol_fees` is called
+    ///
+    /// # Arguments
+    /// * `is_x` - true if the fee was paid in token X, false for token Y
+    /// * `share` - The protocol's share of the swap fee
+    ///
+    /// # Returns
+    /// * `Result<()>` - Ok if successful, error otherwise
+    pub fn accrue_protocol_fee(&mut self, is_x: bool, share: u64) -> Result<()> {
+        let accrued = match is_x {
+            true => &mut self.config.protocol_fees_x,
+            false => &mut self.config.protocol_fees_y,
+        };
+        *accrued = accrued.checked_add(share).ok_or(AmmError::Overflow)?;
 
         Ok(())
     }
@@ -170,26 +359,28 @@ impl<'info> Swap<'info> {
     /// * `amount` - Amount of tokens to deposit
     /// 
     /// # Returns
-    /// * `Result<()>` - Ok if successful, error otherwise
-    pub fn deposit_token(&mut self, is_x: bool, amount: u64) -> Result<()> {
+    /// * `Result<u64>` - Amount the vault actually received after transfer fees
+    pub fn deposit_token(&mut self, is_x: bool, amount: u64) -> Result<u64> {
         // Select appropriate accounts based on token type
-        let (from, to, mint, decimals) = match is_x {
+        let (from, to, mint, decimals, cpi_program, balance_before) = match is_x {
             true => (
-                self.user_ata_x.to_account_info(),    // Transfer from user's X account
-                self.vault_x.to_account_info(),       // Transfer to vault X
-                self.mint_x.to_account_info(),        // Token X mint
-                self.mint_x.decimals,                 // Token X decimals
+                self.user_ata_x.to_account_info(),      // Transfer from user's X account
+                self.vault_x.to_account_info(),         // Transfer to vault X
+                self.mint_x.to_account_info(),          // Token X mint
+                self.mint_x.decimals,                   // Token X decimals
+                self.token_program_x.to_account_info(), // Token X program
+                self.vault_x.amount,                    // Vault X balance before transfer
             ),
             false => (
-                self.user_ata_y.to_account_info(),    // Transfer from user's Y account
-                self.vault_y.to_account_info(),       // Transfer to vault Y
-                self.mint_y.to_account_info(),        // Token Y mint
-                self.mint_y.decimals,                 // Token Y decimals
+                self.user_ata_y.to_account_info(),      // Transfer from user's Y account
+                self.vault_y.to_account_info(),         // Transfer to vault Y
+                self.mint_y.to_account_info(),          // Token Y mint
+                self.mint_y.decimals,                   // Token Y decimals
+                self.token_program_y.to_account_info(), // Token Y program
+                self.vault_y.amount,                    // Vault Y balance before transfer
             ),
         };
 
-        let cpi_program = self.token_program.to_account_info();
-
         // Set up transfer instruction accounts
         let cpi_accounts = TransferChecked {
             from,
@@ -201,7 +392,18 @@ impl<'info> Swap<'info> {
         let cpi_context = CpiContext::new(cpi_program, cpi_accounts);
 
         // Execute the transfer with amount and decimal validation
-        transfer_checked(cpi_context, amount, decimals)
+        transfer_checked(cpi_context, amount, decimals)?;
+
+        // Re-read the vault to see what arrived after any transfer fee was withheld
+        let vault = match is_x {
+            true => &mut self.vault_x,
+            false => &mut self.vault_y,
+        };
+        vault.reload()?;
+        vault
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(AmmError::Underflow.into())
     }
 
     /// Withdraws tokens from vault to user's account
@@ -212,26 +414,28 @@ impl<'info> Swap<'info> {
     /// * `amount` - Amount of tokens to withdraw
     /// 
     /// # Returns
-    /// * `Result<()>` - Ok if successful, error otherwise
-    pub fn withdraw_token(&mut self, is_x: bool, amount: u64) -> Result<()> {
+    /// * `Result<u64>` - Amount the user actually received after transfer fees
+    pub fn withdraw_token(&mut self, is_x: bool, amount: u64) -> Result<u64> {
         // Select appropriate accounts based on token type
-        let (from, to, mint, decimals) = match is_x {
+        let (from, to, mint, decimals, cpi_program, balance_before) = match is_x {
             true => (
-                self.vault_x.to_account_info(),       // Transfer from vault X
-                self.user_ata_x.to_account_info(),    // Transfer to user's X account
-                self.mint_x.to_account_info(),        // Token X mint
-                self.mint_x.decimals,                 // Token X decimals
+                self.vault_x.to_account_info(),         // Transfer from vault X
+                self.user_ata_x.to_account_info(),      // Transfer to user's X account
+                self.mint_x.to_account_info(),          // Token X mint
+                self.mint_x.decimals,                   // Token X decimals
+                self.token_program_x.to_account_info(), // Token X program
+                self.user_ata_x.amount,                 // User X balance before transfer
             ),
             false => (
-                self.vault_y.to_account_info(),       // Transfer from vault Y
-                self.user_ata_y.to_account_info(),    // Transfer to user's Y account
-                self.mint_y.to_account_info(),        // Token Y mint
-                self.mint_y.decimals,                 // Token Y decimals
+                self.vault_y.to_account_info(),         // Transfer from vault Y
+                self.user_ata_y.to_account_info(),      // Transfer to user's Y account
+                self.mint_y.to_account_info(),          // Token Y mint
+                self.mint_y.decimals,                   // Token Y decimals
+                self.token_program_y.to_account_info(), // Token Y program
+                self.user_ata_y.amount,                 // User Y balance before transfer
             ),
         };
 
-        let cpi_program = self.token_program.to_account_info();
-
         // Set up transfer instruction accounts
         let cpi_accounts = TransferChecked {
             from,
@@ -251,6 +455,17 @@ impl<'info> Swap<'info> {
         let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
 
         // Execute the transfer with amount and decimal validation
-        transfer_checked(cpi_context, amount, decimals)
+        transfer_checked(cpi_context, amount, decimals)?;
+
+        // Re-read the user's account to see what arrived after any transfer fee was withheld
+        let user_ata = match is_x {
+            true => &mut self.user_ata_x,
+            false => &mut self.user_ata_y,
+        };
+        user_ata.reload()?;
+        user_ata
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(AmmError::Underflow.into())
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,334 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::errors::AmmError;
+use crate::events;
+use crate::states::{ConcentratedPool, Config, CurveType, Oracle};
+use crate::utils::check_expiration;
+
+/// Accounts required for swapping in a concentrated-liquidity pool
+/// Same as `Swap`, with the pool's tick state instead of the LP mint
+#[derive(Accounts)]
+pub struct SwapConcentrated<'info> {
+    /// The user who is performing the swap (must sign the transaction)
+    /// Mutable because they may need to pay for ATA creation if accounts don't exist
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// The mint account for token X in the trading pair
+    /// Immutable as we only need to read mint information for transfers
+    #[account(mint::token_program = token_program_x)]
+    pub mint_x: InterfaceAccount<'info, Mint>,
+
+    /// The mint account for token Y in the trading pair
+    /// Immutable as we only need to read mint information for transfers
+    #[account(mint::token_program = token_program_y)]
+    pub mint_y: InterfaceAccount<'info, Mint>,
+
+    /// The AMM pool configuration account
+    /// Contains pool settings, fees, and references to the token mints
+    /// Mutable because the protocol's share of the swap fee is accrued here
+    /// Uses PDA derived from "config" seed and config.seed
+    #[account(
+        mut,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+        has_one = mint_x,  // Ensures mint_x matches the one in config
+        has_one = mint_y,  // Ensures mint_y matches the one in config
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The TWAP price oracle for this pool
+    /// Mutable because the price accumulators are updated before the price moves
+    #[account(
+        mut,
+        seeds = [b"oracle", config.key().as_ref()],
+        bump = oracle.bump,
+    )]
+    pub oracle: Box<Account<'info, Oracle>>,
+
+    /// Price, liquidity and tick state of the pool
+    /// Mutable because the swap moves the price and crosses ticks
+    #[account(
+        mut,
+        seeds = [b"pool", config.key().as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, ConcentratedPool>>,
+
+    /// The vault that holds all deposited token X
+    /// Mutable because swap operations either deposit to or withdraw from this vault
+    /// Associated token account owned by the config PDA
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_x,
+    )]
+    pub vault_x: InterfaceAccount<'info, TokenAccount>,
+
+    /// The vault that holds all deposited token Y
+    /// Mutable because swap operations either deposit to or withdraw from this vault
+    /// Associated token account owned by the config PDA
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_y,
+    )]
+    pub vault_y: InterfaceAccount<'info, TokenAccount>,
+
+    /// User's token account for token X
+    /// Will be created if it doesn't exist, user pays for creation
+    /// Mutable because we may transfer tokens to/from this account
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_x,
+        associated_token::authority = user,
+        associated_token::token_program = token_program_x
+    )]
+    pub user_ata_x: InterfaceAccount<'info, TokenAccount>,
+
+    /// User's token account for token Y
+    /// Will be created if it doesn't exist, user pays for creation
+    /// Mutable because we may transfer tokens to/from this account
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_y,
+        associated_token::authority = user,
+        associated_token::token_program = token_program_y
+    )]
+    pub user_ata_y: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token program owning mint_x (SPL Token or Token-2022)
+    pub token_program_x: Interface<'info, TokenInterface>,
+    /// Token program owning mint_y (SPL Token or Token-2022)
+    pub token_program_y: Interface<'info, TokenInterface>,
+    /// Associated Token program for ATA operations
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SwapConcentrated<'info> {
+    /// Exact-input swap through the pool's liquidity ranges
+    ///
+    /// The swap process follows these steps:
+    /// 1. Validate pool is not locked and amounts are valid
+    /// 2. Deposit input tokens to appropriate vault
+    /// 3. Walk the price through each range, crossing initialized ticks, until
+    ///    the amount the vault received is used up
+    /// 4. Withdraw output tokens from appropriate vault to user
+    /// 5. Validate slippage protection on the amount the user received
+    /// 6. Emit a `SwapConcentrated` event
+    ///
+    /// Each range trades like x * y = k on the liquidity active in it, and its share
+    /// of the fee goes to the positions providing that liquidity.
+    ///
+    /// # Arguments
+    /// * `is_x` - true if swapping token X for Y, false if swapping Y for X
+    /// * `amount_in` - Amount of input tokens to swap
+    /// * `min_amount_out` - Minimum amount of output tokens expected (slippage protection)
+    /// * `expiration` - Optional unix timestamp after which the swap is rejected
+    ///
+    /// # Returns
+    /// * `Result<()>` - Ok if successful, error otherwise
+    pub fn swap_concentrated(
+        &mut self,
+        is_x: bool,
+        amount_in: u64,
+        min_amount_out: u64,
+        expiration: Option<i64>,
+    ) -> Result<()> {
+        // Ensure the transaction didn't land after the user's deadline
+        check_expiration(expiration)?;
+        require!(
+            self.config.curve_type == CurveType::Concentrated,
+            AmmError::InvalidCurve
+        );
+        // Ensure the pool is not locked for swaps
+        require!(!self.config.locked, AmmError::PoolLocked);
+        // Ensure user is swapping a positive amount
+        require!(amount_in > 0, AmmError::InvalidAmount);
+
+        // Accrue the price from before this instruction into the TWAP oracle
+        self.oracle.update_sqrt_price(self.pool.sqrt_price)?;
+
+        // Deposit input tokens first to learn how much reached the vault
+        let received_in = self.deposit_token(is_x, amount_in)?;
+
+        // Selling X pushes the price of X down, selling Y pushes it up
+        let (amount_out, fee, protocol_share) = self.pool.swap(
+            is_x,
+            received_in,
+            self.config.fee,
+            self.config.protocol_fee,
+        )?;
+        require!(amount_out != 0, AmmError::InvalidAmount);
+
+        // Set aside the protocol's share of the fee, paid in the input token
+        self.accrue_protocol_fee(is_x, protocol_share)?;
+
+        // Withdraw output tokens to the user
+        let received_out = self.withdraw_token(!is_x, amount_out)?;
+
+        // Slippage protection: output after transfer fees must meet the user's minimum
+        require!(received_out >= min_amount_out, AmmError::SlippageExceeded);
+
+        emit!(events::SwapConcentrated {
+            config: self.config.key(),
+            is_x,
+            amount_in: received_in,
+            amount_out,
+            fee,
+            protocol_fee: protocol_share,
+            sqrt_price: self.pool.sqrt_price,
+            tick_current: self.pool.tick_current,
+            liquidity: self.pool.liquidity,
+        });
+
+        Ok(())
+    }
+
+    /// Records the protocol's share of a swap fee in the config
+    /// The tokens stay in the vault until `collect_protocol_fees` is called
+    ///
+    /// # Arguments
+    /// * `is_x` - true if the fee was paid in token X, false for token Y
+    /// * `share` - Protocol share of the swap fee
+    ///
+    /// # Returns
+    /// * `Result<()>` - Ok if successful, error otherwise
+    pub fn accrue_protocol_fee(&mut self, is_x: bool, share: u64) -> Result<()> {
+        let accrued = match is_x {
+            true => &mut self.config.protocol_fees_x,
+            false => &mut self.config.protocol_fees_y,
+        };
+        *accrued = accrued.checked_add(share).ok_or(AmmError::Overflow)?;
+
+        Ok(())
+    }
+
+    /// Deposits tokens from user's account to the appropriate vault
+    /// This increases the vault's balance and decreases the user's balance
+    /// 
+    /// # Arguments
+    /// * `is_x` - true for token X, false for token Y
+    /// * `amount` - Amount of tokens to deposit
+    /// 
+    /// # Returns
+    /// * `Result<u64>` - Amount the vault actually received after transfer fees
+    pub fn deposit_token(&mut self, is_x: bool, amount: u64) -> Result<u64> {
+        // Select appropriate accounts based on token type
+        let (from, to, mint, decimals, cpi_program, balance_before) = match is_x {
+            true => (
+                self.user_ata_x.to_account_info(),      // Transfer from user's X account
+                self.vault_x.to_account_info(),         // Transfer to vault X
+                self.mint_x.to_account_info(),          // Token X mint
+                self.mint_x.decimals,                   // Token X decimals
+                self.token_program_x.to_account_info(), // Token X program
+                self.vault_x.amount,                    // Vault X balance before transfer
+            ),
+            false => (
+                self.user_ata_y.to_account_info(),      // Transfer from user's Y account
+                self.vault_y.to_account_info(),         // Transfer to vault Y
+                self.mint_y.to_account_info(),          // Token Y mint
+                self.mint_y.decimals,                   // Token Y decimals
+                self.token_program_y.to_account_info(), // Token Y program
+                self.vault_y.amount,                    // Vault Y balance before transfer
+            ),
+        };
+
+        // Set up transfer instruction accounts
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            authority: self.user.to_account_info(),  // User signs the transfer
+            mint,
+        };
+
+        let cpi_context = CpiContext::new(cpi_program, cpi_accounts);
+
+        // Execute the transfer with amount and decimal validation
+        transfer_checked(cpi_context, amount, decimals)?;
+
+        // Re-read the vault to see what arrived after any transfer fee was withheld
+        let vault = match is_x {
+            true => &mut self.vault_x,
+            false => &mut self.vault_y,
+        };
+        vault.reload()?;
+        vault
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(AmmError::Underflow.into())
+    }
+
+    /// Withdraws tokens from vault to user's account
+    /// This decreases the vault's balance and increases the user's balance
+    /// 
+    /// # Arguments
+    /// * `is_x` - true for token X, false for token Y
+    /// * `amount` - Amount of tokens to withdraw
+    /// 
+    /// # Returns
+    /// * `Result<u64>` - Amount the user actually received after transfer fees
+    pub fn withdraw_token(&mut self, is_x: bool, amount: u64) -> Result<u64> {
+        // Select appropriate accounts based on token type
+        let (from, to, mint, decimals, cpi_program, balance_before) = match is_x {
+            true => (
+                self.vault_x.to_account_info(),         // Transfer from vault X
+                self.user_ata_x.to_account_info(),      // Transfer to user's X account
+                self.mint_x.to_account_info(),          // Token X mint
+                self.mint_x.decimals,                   // Token X decimals
+                self.token_program_x.to_account_info(), // Token X program
+                self.user_ata_x.amount,                 // User X balance before transfer
+            ),
+            false => (
+                self.vault_y.to_account_info(),         // Transfer from vault Y
+                self.user_ata_y.to_account_info(),      // Transfer to user's Y account
+                self.mint_y.to_account_info(),          // Token Y mint
+                self.mint_y.decimals,                   // Token Y decimals
+                self.token_program_y.to_account_info(), // Token Y program
+                self.user_ata_y.amount,                 // User Y balance before transfer
+            ),
+        };
+
+        // Set up transfer instruction accounts
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            mint,
+            authority: self.config.to_account_info(),  // Config PDA signs the transfer
+        };
+
+        // Create signer seeds for config PDA
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"config",
+            &self.config.seed.to_le_bytes(),
+            &[self.config.config_bump],
+        ]];
+
+        // Create CPI context with PDA signer
+        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        // Execute the transfer with amount and decimal validation
+        transfer_checked(cpi_context, amount, decimals)?;
+
+        // Re-read the user's account to see what arrived after any transfer fee was withheld
+        let user_ata = match is_x {
+            true => &mut self.user_ata_x,
+            false => &mut self.user_ata_y,
+        };
+        user_ata.reload()?;
+        user_ata
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(AmmError::Underflow.into())
+    }
+}
@@ -1,12 +1,17 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{burn, transfer_checked, Burn, Mint, Token, TokenAccount, TransferChecked},
+    token::Token,
+    token_interface::{
+        burn, transfer_checked, Burn, Mint, TokenAccount, TokenInterface, TransferChecked,
+    },
 };
-use constant_product_curve::ConstantProduct;
 
 use crate::errors::AmmError;
-use crate::states::Config;
+use crate::events;
+use crate::quote::{post_swap_reserves, quote_exact_in, quote_withdraw};
+use crate::states::{Config, Oracle};
+use crate::utils::check_expiration;
 
 /// Accounts required for withdrawing liquidity from the AMM pool
 /// This struct defines all the accounts needed to perform a liquidity withdrawal operation
@@ -18,18 +23,20 @@ pub struct Withdraw<'info> {
 
     /// The mint account for token X in the trading pair
     /// Immutable as we only need to read mint information for transfers
-    #[account(mint::token_program = token_program)]
-    pub mint_x: Account<'info, Mint>,
+    #[account(mint::token_program = token_program_x)]
+    pub mint_x: InterfaceAccount<'info, Mint>,
 
     /// The mint account for token Y in the trading pair
     /// Immutable as we only need to read mint information for transfers
-    #[account(mint::token_program = token_program)]
-    pub mint_y: Account<'info, Mint>,
+    #[account(mint::token_program = token_program_y)]
+    pub mint_y: InterfaceAccount<'info, Mint>,
 
     /// The AMM pool configuration account
     /// Contains pool settings and references to the token mints
     /// Uses PDA derived from "config" seed and config.seed
+    /// Mutable because `withdraw_single` accrues the protocol's share of its internal swap fee
     #[account(
+        mut,
         seeds = [b"config", config.seed.to_le_bytes().as_ref()],
         bump = config.config_bump,
         has_one = mint_x,  // Ensures mint_x matches the one in config
@@ -37,15 +44,25 @@ pub struct Withdraw<'info> {
     )]
     pub config: Account<'info, Config>,
 
+    /// The TWAP price oracle for this pool
+    /// Mutable because the price accumulators are updated before the reserves change
+    #[account(
+        mut,
+        seeds = [b"oracle", config.key().as_ref()],
+        bump = oracle.bump,
+    )]
+    pub oracle: Box<Account<'info, Oracle>>,
+
     /// The LP (Liquidity Provider) token mint
     /// Mutable because we need to burn LP tokens from the user
     /// Uses PDA derived from "lp" seed and config pubkey
     #[account(
         mut,
         seeds = [b"lp", config.key().as_ref()],
-        bump = config.lp_bump
+        bump = config.lp_bump,
+        mint::token_program = token_program,
     )]
-    pub mint_lp: Account<'info, Mint>,
+    pub mint_lp: InterfaceAccount<'info, Mint>,
 
     /// The vault that holds all deposited token X
     /// Mutable because we're withdrawing tokens from it
@@ -54,9 +71,9 @@ pub struct Withdraw<'info> {
         mut,
         associated_token::mint = mint_x,
         associated_token::authority = config,
-        associated_token::token_program = token_program,
+        associated_token::token_program = token_program_x,
     )]
-    pub vault_x: Account<'info, TokenAccount>,
+    pub vault_x: InterfaceAccount<'info, TokenAccount>,
 
     /// The vault that holds all deposited token Y
     /// Mutable because we're withdrawing tokens from it
@@ -65,9 +82,9 @@ pub struct Withdraw<'info> {
         mut,
         associated_token::mint = mint_y,
         associated_token::authority = config,
-        associated_token::token_program = token_program,
+        associated_token::token_program = token_program_y,
     )]
-    pub vault_y: Account<'info, TokenAccount>,
+    pub vault_y: InterfaceAccount<'info, TokenAccount>,
 
     /// User's token account for token X
     /// Mutable because we're transferring tokens to it
@@ -75,9 +92,9 @@ pub struct Withdraw<'info> {
         mut,
         associated_token::mint = mint_x,
         associated_token::authority = user,
-        associated_token::token_program = token_program
+        associated_token::token_program = token_program_x
     )]
-    pub user_ata_x: Account<'info, TokenAccount>,
+    pub user_ata_x: InterfaceAccount<'info, TokenAccount>,
 
     /// User's token account for token Y
     /// Mutable because we're transferring tokens to it
@@ -85,9 +102,9 @@ pub struct Withdraw<'info> {
         mut,
         associated_token::mint = mint_y,
         associated_token::authority = user,
-        associated_token::token_program = token_program
+        associated_token::token_program = token_program_y
     )]
-    pub user_ata_y: Account<'info, TokenAccount>,
+    pub user_ata_y: InterfaceAccount<'info, TokenAccount>,
 
     /// User's token account for LP tokens
     /// Will be created if it doesn't exist, user pays for creation
@@ -99,10 +116,14 @@ pub struct Withdraw<'info> {
         associated_token::authority = user,
         associated_token::token_program = token_program
     )]
-    pub user_ata_lp: Account<'info, TokenAccount>,
+    pub user_ata_lp: InterfaceAccount<'info, TokenAccount>,
 
-    /// SPL Token program for token operations
+    /// SPL Token program for the LP mint
     pub token_program: Program<'info, Token>,
+    /// Token program owning mint_x (SPL Token or Token-2022)
+    pub token_program_x: Interface<'info, TokenInterface>,
+    /// Token program owning mint_y (SPL Token or Token-2022)
+    pub token_program_y: Interface<'info, TokenInterface>,
     /// Associated Token program for ATA operations
     pub associated_token_program: Program<'info, AssociatedToken>,
     /// System program for account creation
@@ -115,54 +136,198 @@ impl<'info> Withdraw<'info> {
     /// The process follows this sequence:
     /// 1. Read current LP supply and vault balances
     /// 2. Calculate proportional token amounts based on LP tokens being burned
-    /// 3. Validate slippage protection (amounts meet minimum requirements)
-    /// 4. Burn the LP tokens from user's account
-    /// 5. Transfer proportional amounts of both tokens to user
+    /// 3. Burn the LP tokens from user's account
+    /// 4. Transfer proportional amounts of both tokens to user
+    /// 5. Validate slippage protection on the amounts received after transfer fees
+    /// 6. Emit a `LiquidityRemoved` event
+    /// 
+    /// Withdrawals are always proportional to the reserves, which leaves the price unchanged
+    /// on both constant product and StableSwap curves, so they don't depend on the curve type.
     /// 
     /// # Arguments
     /// * `amount` - Amount of LP tokens to burn
     /// * `min_x` - Minimum amount of token X user expects to receive
     /// * `min_y` - Minimum amount of token Y user expects to receive
+    /// * `expiration` - Optional unix timestamp after which the withdrawal is rejected
     /// 
     /// # Returns
     /// * `Result<()>` - Ok if successful, error otherwise
-    pub fn withdraw(&mut self, amount: u64, min_x: u64, min_y: u64) -> Result<()> {
+    pub fn withdraw(
+        &mut self,
+        amount: u64,
+        min_x: u64,
+        min_y: u64,
+        expiration: Option<i64>,
+    ) -> Result<()> {
+        // Ensure the transaction didn't land after the user's deadline
+        check_expiration(expiration)?;
         // Ensure the pool is not locked for withdrawals
-        require!(self.config.locked == false, AmmError::PoolLocked);
+        require!(!self.config.locked, AmmError::PoolLocked);
         // Ensure user is requesting to burn some LP tokens
         require!(amount != 0, AmmError::InvalidAmount);
 
+        // Uncollected protocol fees sit in the vaults but don't belong to LPs
+        let (vault_x, vault_y) = (self.vault_x.amount, self.vault_y.amount);
+        let (reserve_x, reserve_y) = self.config.reserves(vault_x, vault_y)?;
+
+        // Accrue the price from before this instruction into the TWAP oracle
+        self.oracle.update(reserve_x, reserve_y)?;
+
         // Calculate token amounts to withdraw based on current pool state
         let (x, y) = match self.mint_lp.supply == 0
-            && self.vault_x.amount == 0
-            && self.vault_y.amount == 0
+            && reserve_x == 0
+            && reserve_y == 0
         {
             // Edge case: if pool is completely empty, use minimum amounts
             // This shouldn't happen in normal operation but provides safety
             true => (min_x, min_y),
             // Normal case: calculate proportional amounts based on LP token share
             false => {
-                let amounts = ConstantProduct::xy_withdraw_amounts_from_l(
-                    self.vault_x.amount,   // Current vault X balance
-                    self.vault_y.amount,   // Current vault Y balance
-                    self.mint_lp.supply,   // Current LP token supply
-                    amount,                // LP tokens being burned
-                    6,                     // Precision for calculations
-                )
-                .unwrap();
-                (amounts.x, amounts.y)
+                let amounts = quote_withdraw(&self.config, vault_x, vault_y, self.mint_lp.supply, amount)?;
+                (amounts.amount_x, amounts.amount_y)
             }
         };
 
-        // Slippage protection: ensure calculated amounts meet user's minimum requirements
-        require!(x >= min_x && y >= min_y, AmmError::SlippageExceeded);
-
         // Burn LP tokens from user's account first
         self.burn_lp_tokens(amount)?;
 
         // Transfer calculated amounts of both tokens to user
-        self.withdraw_tokens(x, true)?;   // Transfer token X
-        self.withdraw_tokens(y, false)    // Transfer token Y
+        let received_x = self.withdraw_tokens(x, true)?;    // Transfer token X
+        let received_y = self.withdraw_tokens(y, false)?;   // Transfer token Y
+
+        // Slippage protection: the minimums apply to what the user receives after transfer fees
+        require!(received_x >= min_x && received_y >= min_y, AmmError::SlippageExceeded);
+
+        emit!(events::LiquidityRemoved {
+            config: self.config.key(),
+            user: self.user.key(),
+            amount_x: x,
+            amount_y: y,
+            lp_amount: amount,
+            reserve_x: reserve_x.saturating_sub(x),
+            reserve_y: reserve_y.saturating_sub(y),
+        });
+
+        Ok(())
+    }
+
+    /// Single-sided ("zap") withdrawal: removes liquidity and receives only one of the two tokens
+    ///
+    /// The process follows this sequence:
+    /// 1. Calculate the proportional amounts of both tokens the LP tokens are worth
+    /// 2. Sell the unwanted token back into the remaining pool with the pool's curve
+    /// 3. Burn the LP tokens and send the wanted token plus the swap output to the user
+    /// 4. Emit a `LiquidityRemoved` event
+    ///
+    /// The unwanted token never leaves its vault. The pool must keep some liquidity
+    /// to swap against, which the locked `MINIMUM_LIQUIDITY` guarantees.
+    ///
+    /// # Arguments
+    /// * `is_x` - true to receive token X, false to receive token Y
+    /// * `amount` - Amount of LP tokens to burn
+    /// * `min_out` - Minimum amount of the wanted token the user expects to receive
+    /// * `expiration` - Optional unix timestamp after which the withdrawal is rejected
+    ///
+    /// # Returns
+    /// * `Result<()>` - Ok if successful, error otherwise
+    pub fn withdraw_single(
+        &mut self,
+        is_x: bool,
+        amount: u64,
+        min_out: u64,
+        expiration: Option<i64>,
+    ) -> Result<()> {
+        // Ensure the transaction didn't land after the user's deadline
+        check_expiration(expiration)?;
+        // Ensure the pool is not locked for withdrawals
+        require!(!self.config.locked, AmmError::PoolLocked);
+        // Ensure user is requesting to burn some LP tokens
+        require!(amount != 0, AmmError::InvalidAmount);
+
+        // Uncollected protocol fees sit in the vaults but don't belong to LPs
+        let (reserve_x, reserve_y) = self
+            .config
+            .reserves(self.vault_x.amount, self.vault_y.amount)?;
+        let supply = self.mint_lp.supply;
+
+        // Liquidity must remain after the withdrawal for the unwanted side to be sold into
+        require!(amount < supply, AmmError::NoLiquidityInPool);
+
+        // Accrue the price from before this instruction into the TWAP oracle
+        self.oracle.update(reserve_x, reserve_y)?;
+
+        // Proportional amounts based on the LP token share, priced like a balanced withdrawal
+        let amounts = quote_withdraw(
+            &self.config,
+            self.vault_x.amount,
+            self.vault_y.amount,
+            supply,
+            amount,
+        )?;
+        let (wanted, unwanted) = match is_x {
+            true => (amounts.amount_x, amounts.amount_y),
+            false => (amounts.amount_y, amounts.amount_x),
+        };
+
+        // Sell the unwanted side into the pool that remains after the withdrawal
+        let remaining = (reserve_x - amounts.amount_x, reserve_y - amounts.amount_y);
+        let now = Clock::get()?.unix_timestamp;
+        let (swap_out, after) = match unwanted {
+            0 => (0, remaining),
+            _ => {
+                let (swap_out, fee) = quote_exact_in(
+                    &self.config,
+                    !is_x,
+                    remaining.0,
+                    remaining.1,
+                    supply - amount,
+                    unwanted,
+                    now,
+                )?;
+
+                // Set aside the protocol's share of the fee, paid in the unwanted token
+                // protocol_fee <= 10_000 so the share always fits back into a u64
+                let share = (fee as u128 * self.config.protocol_fee as u128 / 10_000) as u64;
+                let accrued = match is_x {
+                    true => &mut self.config.protocol_fees_y,
+                    false => &mut self.config.protocol_fees_x,
+                };
+                *accrued = accrued.checked_add(share).ok_or(AmmError::Overflow)?;
+
+                // The internal swap moves the price like any other swap
+                let after = post_swap_reserves(!is_x, remaining, unwanted - share, swap_out)?;
+                self.config
+                    .record_price_move(now, remaining, after)?;
+                (swap_out, after)
+            }
+        };
+        let total = wanted.checked_add(swap_out).ok_or(AmmError::Overflow)?;
+        require!(total != 0, AmmError::InvalidAmount);
+
+        // Burn LP tokens from user's account first
+        self.burn_lp_tokens(amount)?;
+
+        // Transfer the wanted token to the user
+        let received = self.withdraw_tokens(total, is_x)?;
+
+        // Slippage protection: the minimum applies to what the user receives after transfer fees
+        require!(received >= min_out, AmmError::SlippageExceeded);
+
+        let (amount_x, amount_y) = match is_x {
+            true => (total, 0),
+            false => (0, total),
+        };
+        emit!(events::LiquidityRemoved {
+            config: self.config.key(),
+            user: self.user.key(),
+            amount_x,
+            amount_y,
+            lp_amount: amount,
+            reserve_x: after.0,
+            reserve_y: after.1,
+        });
+
+        Ok(())
     }
 
     /// Burns LP tokens from the user's account
@@ -204,26 +369,28 @@ impl<'info> Withdraw<'info> {
     /// * `is_x` - true for token X, false for token Y
     /// 
     /// # Returns
-    /// * `Result<()>` - Ok if successful, error otherwise
-    pub fn withdraw_tokens(&mut self, amount: u64, is_x: bool) -> Result<()> {
+    /// * `Result<u64>` - Amount the user actually received after transfer fees
+    pub fn withdraw_tokens(&mut self, amount: u64, is_x: bool) -> Result<u64> {
         // Select appropriate accounts based on token type
-        let (from, to, mint, decimals) = match is_x {
+        let (from, to, mint, decimals, cpi_program, balance_before) = match is_x {
             true => (
-                self.vault_x.to_account_info(),      // Transfer from vault X
-                self.user_ata_x.to_account_info(),   // Transfer to user's X account
-                self.mint_x.to_account_info(),       // Token X mint
-                self.mint_x.decimals,                // Token X decimals
+                self.vault_x.to_account_info(),         // Transfer from vault X
+                self.user_ata_x.to_account_info(),      // Transfer to user's X account
+                self.mint_x.to_account_info(),          // Token X mint
+                self.mint_x.decimals,                   // Token X decimals
+                self.token_program_x.to_account_info(), // Token X program
+                self.user_ata_x.amount,                 // User X balance before transfer
             ),
             false => (
-                self.vault_y.to_account_info(),      // Transfer from vault Y
-                self.user_ata_y.to_account_info(),   // Transfer to user's Y account
-                self.mint_y.to_account_info(),       // Token Y mint
-                self.mint_y.decimals,                // Token Y decimals
+                self.vault_y.to_account_info(),         // Transfer from vault Y
+                self.user_ata_y.to_account_info(),      // Transfer to user's Y account
+                self.mint_y.to_account_info(),          // Token Y mint
+                self.mint_y.decimals,                   // Token Y decimals
+                self.token_program_y.to_account_info(), // Token Y program
+                self.user_ata_y.amount,                 // User Y balance before transfer
             ),
         };
 
-        let cpi_program = self.token_program.to_account_info();
-
         // Set up transfer instruction accounts
         let cpi_accounts = TransferChecked {
             from,
@@ -243,6 +410,17 @@ impl<'info> Withdraw<'info> {
         let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
 
         // Execute the transfer with amount and decimal validation
-        transfer_checked(cpi_context, amount, decimals)
+        transfer_checked(cpi_context, amount, decimals)?;
+
+        // Re-read the user's account to see what arrived after any transfer fee was withheld
+        let user_ata = match is_x {
+            true => &mut self.user_ata_x,
+            false => &mut self.user_ata_y,
+        };
+        user_ata.reload()?;
+        user_ata
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(AmmError::Underflow.into())
     }
 }
\ No newline at end of file
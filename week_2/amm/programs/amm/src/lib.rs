@@ -1,13 +1,20 @@
+#![allow(deprecated, unexpected_cfgs)]
 use anchor_lang::prelude::*;
 
 declare_id!("J3Y5M9uwFXxBo2bKqqd58v44pz2v7gLi8tupjWQLf6bF");
 
+pub mod concentrated_liquidity;
 pub mod constants;
 pub mod errors;
+pub mod events;
 pub mod instructions;
+pub mod quote;
 pub mod states;
+pub mod utils;
 
 pub use instructions::*;
+pub use quote::{LiquidityQuote, SwapQuote};
+pub use states::CurveType;
 
 #[program]
 pub mod amm {
@@ -18,19 +25,263 @@ pub mod amm {
         seed: u64,
         fee: u16,
         authority: Option<Pubkey>,
+        curve_type: CurveType,
+        amp: u64,
     ) -> Result<()> {
-        ctx.accounts.initialize(seed, fee, authority, &ctx.bumps)
+        ctx.accounts.initialize(seed, fee, authority, curve_type, amp, &ctx.bumps)
     }
 
-    pub fn deposit(ctx: Context<Deposit>, amount: u64, max_x: u64, max_y: u64) -> Result<()> {
-        ctx.accounts.deposit(amount, max_x, max_y)
+    pub fn deposit(
+        ctx: Context<Deposit>,
+        amount: u64,
+        max_x: u64,
+        max_y: u64,
+        expiration: Option<i64>,
+    ) -> Result<()> {
+        ctx.accounts.deposit(amount, max_x, max_y, expiration)
+    }
+
+    pub fn withdraw(
+        ctx: Context<Withdraw>,
+        amount: u64,
+        min_x: u64,
+        min_y: u64,
+        expiration: Option<i64>,
+    ) -> Result<()> {
+        ctx.accounts.withdraw(amount, min_x, min_y, expiration)
+    }
+
+    pub fn deposit_single(
+        ctx: Context<Deposit>,
+        is_x: bool,
+        amount_in: u64,
+        min_lp_out: u64,
+        expiration: Option<i64>,
+    ) -> Result<()> {
+        ctx.accounts.deposit_single(is_x, amount_in, min_lp_out, expiration)
+    }
+
+    pub fn withdraw_single(
+        ctx: Context<Withdraw>,
+        is_x: bool,
+        amount: u64,
+        min_out: u64,
+        expiration: Option<i64>,
+    ) -> Result<()> {
+        ctx.accounts.withdraw_single(is_x, amount, min_out, expiration)
+    }
+
+    pub fn swap(
+        ctx: Context<Swap>,
+        is_x: bool,
+        amount_in: u64,
+        min_amount_out: u64,
+        expiration: Option<i64>,
+    ) -> Result<()> {
+        ctx.accounts.swap(is_x, amount_in, min_amount_out, expiration)
     }
 
-    pub fn withdraw(ctx: Context<Withdraw>, amount: u64, min_x: u64, min_y: u64) -> Result<()> {
-        ctx.accounts.withdraw(amount, min_x, min_y)
+    pub fn swap_exact_out(
+        ctx: Context<Swap>,
+        is_x: bool,
+        amount_out: u64,
+        max_amount_in: u64,
+        expiration: Option<i64>,
+    ) -> Result<()> {
+        ctx.accounts.swap_exact_out(is_x, amount_out, max_amount_in, expiration)
+    }
+
+    pub fn initialize_concentrated(
+        ctx: Context<InitializeConcentrated>,
+        seed: u64,
+        fee: u16,
+        authority: Option<Pubkey>,
+        tick_spacing: u16,
+        sqrt_price: u128,
+    ) -> Result<()> {
+        ctx.accounts
+            .initialize_concentrated(seed, fee, authority, tick_spacing, sqrt_price, &ctx.bumps)
     }
 
-    pub fn swap(ctx: Context<Swap>, is_x: bool, amount_in: u64, min_amount_out: u64) -> Result<()> {
-        ctx.accounts.swap(is_x, amount_in, min_amount_out)
+    pub fn open_position(ctx: Context<OpenPosition>, tick_lower: i32, tick_upper: i32) -> Result<()> {
+        ctx.accounts.open_position(tick_lower, tick_upper, &ctx.bumps)
+    }
+
+    pub fn increase_liquidity(
+        ctx: Context<ModifyPosition>,
+        liquidity: u128,
+        max_x: u64,
+        max_y: u64,
+        expiration: Option<i64>,
+    ) -> Result<()> {
+        ctx.accounts.increase_liquidity(liquidity, max_x, max_y, expiration)
+    }
+
+    pub fn decrease_liquidity(
+        ctx: Context<ModifyPosition>,
+        liquidity: u128,
+        min_x: u64,
+        min_y: u64,
+        expiration: Option<i64>,
+    ) -> Result<()> {
+        ctx.accounts.decrease_liquidity(liquidity, min_x, min_y, expiration)
+    }
+
+    pub fn collect_fees(ctx: Context<ModifyPosition>) -> Result<()> {
+        ctx.accounts.collect_fees()
+    }
+
+    pub fn close_position(ctx: Context<ClosePosition>) -> Result<()> {
+        ctx.accounts.close_position()
+    }
+
+    pub fn swap_concentrated(
+        ctx: Context<SwapConcentrated>,
+        is_x: bool,
+        amount_in: u64,
+        min_amount_out: u64,
+        expiration: Option<i64>,
+    ) -> Result<()> {
+        ctx.accounts.swap_concentrated(is_x, amount_in, min_amount_out, expiration)
+    }
+
+    pub fn route_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RouteSwap<'info>>,
+        amount_in: u64,
+        min_amount_out: u64,
+        expiration: Option<i64>,
+    ) -> Result<()> {
+        ctx.accounts
+            .route_swap(ctx.remaining_accounts, amount_in, min_amount_out, expiration)
+    }
+
+    pub fn quote_swap_exact_in(ctx: Context<QuoteView>, is_x: bool, amount_in: u64) -> Result<SwapQuote> {
+        ctx.accounts.quote_swap_exact_in(is_x, amount_in)
+    }
+
+    pub fn quote_swap_exact_out(ctx: Context<QuoteView>, is_x: bool, amount_out: u64) -> Result<SwapQuote> {
+        ctx.accounts.quote_swap_exact_out(is_x, amount_out)
+    }
+
+    pub fn quote_deposit(ctx: Context<QuoteView>, lp_amount: u64) -> Result<LiquidityQuote> {
+        ctx.accounts.quote_deposit(lp_amount)
+    }
+
+    pub fn quote_withdraw(ctx: Context<QuoteView>, lp_amount: u64) -> Result<LiquidityQuote> {
+        ctx.accounts.quote_withdraw(lp_amount)
+    }
+
+    pub fn initialize_farm(ctx: Context<InitializeFarm>) -> Result<()> {
+        ctx.accounts.initialize_farm(&ctx.bumps)
+    }
+
+    pub fn fund_farm(ctx: Context<FundFarm>, amount: u64, duration: i64) -> Result<()> {
+        ctx.accounts.fund_farm(amount, duration)
+    }
+
+    pub fn stake_lp(ctx: Context<ManageStake>, amount: u64) -> Result<()> {
+        ctx.accounts.stake_lp(amount, &ctx.bumps)
+    }
+
+    pub fn unstake_lp(ctx: Context<ManageStake>, amount: u64) -> Result<()> {
+        ctx.accounts.unstake_lp(amount)
+    }
+
+    pub fn harvest(ctx: Context<ManageStake>) -> Result<()> {
+        ctx.accounts.harvest()
+    }
+
+    pub fn flash_borrow(ctx: Context<FlashLoan>, amount_x: u64, amount_y: u64) -> Result<()> {
+        ctx.accounts.flash_borrow(amount_x, amount_y)
+    }
+
+    pub fn flash_repay(ctx: Context<FlashLoan>) -> Result<()> {
+        ctx.accounts.flash_repay()
+    }
+
+    pub fn lock(ctx: Context<Admin>) -> Result<()> {
+        ctx.accounts.lock()
+    }
+
+    pub fn unlock(ctx: Context<Admin>) -> Result<()> {
+        ctx.accounts.unlock()
+    }
+
+    pub fn update_fee(ctx: Context<Admin>, fee: u16) -> Result<()> {
+        ctx.accounts.update_fee(fee)
+    }
+
+    pub fn set_protocol_fee(ctx: Context<Admin>, protocol_fee: u16) -> Result<()> {
+        ctx.accounts.set_protocol_fee(protocol_fee)
+    }
+
+    pub fn enable_flash_loans(ctx: Context<Admin>) -> Result<()> {
+        ctx.accounts.enable_flash_loans()
+    }
+
+    pub fn disable_flash_loans(ctx: Context<Admin>) -> Result<()> {
+        ctx.accounts.disable_flash_loans()
+    }
+
+    pub fn set_flash_loan_fee(ctx: Context<Admin>, flash_loan_fee: u16) -> Result<()> {
+        ctx.accounts.set_flash_loan_fee(flash_loan_fee)
+    }
+
+    pub fn enable_dynamic_fee(ctx: Context<Admin>, min_fee: u16, max_fee: u16) -> Result<()> {
+        ctx.accounts.enable_dynamic_fee(min_fee, max_fee)
+    }
+
+    pub fn disable_dynamic_fee(ctx: Context<Admin>) -> Result<()> {
+        ctx.accounts.disable_dynamic_fee()
+    }
+
+    pub fn set_treasury(ctx: Context<Admin>, treasury: Pubkey) -> Result<()> {
+        ctx.accounts.set_treasury(treasury)
+    }
+
+    pub fn collect_protocol_fees(ctx: Context<CollectProtocolFees>) -> Result<()> {
+        ctx.accounts.collect_protocol_fees()
+    }
+
+    pub fn ramp_amp(ctx: Context<Admin>, target_amp: u64, ramp_end: i64) -> Result<()> {
+        ctx.accounts.ramp_amp(target_amp, ramp_end)
+    }
+
+    pub fn stop_ramp_amp(ctx: Context<Admin>) -> Result<()> {
+        ctx.accounts.stop_ramp_amp()
+    }
+
+    pub fn set_authority(ctx: Context<Admin>, new_authority: Pubkey) -> Result<()> {
+        ctx.accounts.set_authority(new_authority)
+    }
+
+    pub fn renounce_authority(ctx: Context<Admin>) -> Result<()> {
+        ctx.accounts.renounce_authority()
+    }
+
+    pub fn initialize_factory(ctx: Context<InitializeFactory>, fee_tiers: Vec<u16>) -> Result<()> {
+        ctx.accounts.initialize_factory(fee_tiers, &ctx.bumps)
+    }
+
+    pub fn add_fee_tier(ctx: Context<FactoryAdmin>, fee: u16) -> Result<()> {
+        ctx.accounts.add_fee_tier(fee)
+    }
+
+    pub fn remove_fee_tier(ctx: Context<FactoryAdmin>, fee: u16) -> Result<()> {
+        ctx.accounts.remove_fee_tier(fee)
+    }
+
+    pub fn set_factory_authority(ctx: Context<FactoryAdmin>, new_authority: Pubkey) -> Result<()> {
+        ctx.accounts.set_factory_authority(new_authority)
+    }
+
+    pub fn create_pool(
+        ctx: Context<CreatePool>,
+        seed: u64,
+        fee: u16,
+        curve_type: CurveType,
+        amp: u64,
+    ) -> Result<()> {
+        ctx.accounts.create_pool(seed, fee, curve_type, amp, &ctx.bumps)
     }
 }
\ No newline at end of file
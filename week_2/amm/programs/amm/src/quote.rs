@@ -0,0 +1,284 @@
+//! Quotes for swaps, deposits and withdrawals, computed from a pool's `Config` and vault balances
+//!
+//! The math lives in the `no_std` `amm_quote` crate, which clients can link without the
+//! program. These wrappers read the pool from its `Config` and map `QuoteError`s to
+//! `AmmError`s, and the quote structs here are the ones the `quote_*` instructions return.
+//!
+//! Amounts are what the vaults send and receive. Token-2022 transfer fees come on top of
+//! them and are left to the caller, as `amount_before_fee` does on-chain.
+
+use anchor_lang::prelude::*;
+use amm_quote::QuoteError;
+
+use crate::errors::AmmError;
+use crate::states::Config;
+
+/// Result of quoting a swap.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapQuote {
+    /// Input the vault receives, including the fee.
+    pub amount_in: u64,
+
+    /// Output the vault sends.
+    pub amount_out: u64,
+
+    /// Swap fee charged, in the input token.
+    pub fee: u64,
+
+    /// Part of `fee` set aside for the protocol.
+    pub protocol_fee: u64,
+
+    /// Swap fee rate applied (in basis points).
+    pub fee_bps: u16,
+
+    /// Fee actually paid as a share of the input (in basis points), after rounding.
+    pub effective_fee_bps: u64,
+
+    /// How much worse the execution price is than the price before the swap, ignoring
+    /// the fee (in basis points). The price is the reserve ratio, which for StableSwap
+    /// pools only approximates the marginal price.
+    pub price_impact_bps: u64,
+
+    /// Pool reserves of token X after the swap, excluding protocol fees.
+    pub reserve_x: u64,
+
+    /// Pool reserves of token Y after the swap, excluding protocol fees.
+    pub reserve_y: u64,
+}
+
+impl From<amm_quote::SwapQuote> for SwapQuote {
+    fn from(quote: amm_quote::SwapQuote) -> SwapQuote {
+        SwapQuote {
+            amount_in: quote.amount_in,
+            amount_out: quote.amount_out,
+            fee: quote.fee,
+            protocol_fee: quote.protocol_fee,
+            fee_bps: quote.fee_bps,
+            effective_fee_bps: quote.effective_fee_bps,
+            price_impact_bps: quote.price_impact_bps,
+            reserve_x: quote.reserve_x,
+            reserve_y: quote.reserve_y,
+        }
+    }
+}
+
+/// Result of quoting a deposit or withdrawal.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LiquidityQuote {
+    /// Token X the vault receives or sends.
+    pub amount_x: u64,
+
+    /// Token Y the vault receives or sends.
+    pub amount_y: u64,
+
+    /// LP tokens minted to or burned from the user.
+    pub lp_amount: u64,
+}
+
+impl From<amm_quote::LiquidityQuote> for LiquidityQuote {
+    fn from(quote: amm_quote::LiquidityQuote) -> LiquidityQuote {
+        LiquidityQuote {
+            amount_x: quote.amount_x,
+            amount_y: quote.amount_y,
+            lp_amount: quote.lp_amount,
+        }
+    }
+}
+
+/// Turns a quote failure into the program error of the same meaning
+fn quote_error(error: QuoteError) -> Error {
+    AmmError::from(error).into()
+}
+
+/// Quotes selling `amount_in` into a constant product or StableSwap pool
+/// See `amm_quote::quote_swap_exact_in`
+pub fn quote_swap_exact_in(
+    config: &Config,
+    vault_x: u64,
+    vault_y: u64,
+    lp_supply: u64,
+    is_x: bool,
+    amount_in: u64,
+    now: i64,
+) -> Result<SwapQuote> {
+    amm_quote::quote_swap_exact_in(
+        &config.pool_state(),
+        vault_x,
+        vault_y,
+        lp_supply,
+        is_x,
+        amount_in,
+        now,
+    )
+    .map(SwapQuote::from)
+    .map_err(quote_error)
+}
+
+/// Quotes buying exactly `amount_out` from a constant product or StableSwap pool
+/// See `amm_quote::quote_swap_exact_out`
+pub fn quote_swap_exact_out(
+    config: &Config,
+    vault_x: u64,
+    vault_y: u64,
+    is_x: bool,
+    amount_out: u64,
+    now: i64,
+) -> Result<SwapQuote> {
+    amm_quote::quote_swap_exact_out(&config.pool_state(), vault_x, vault_y, is_x, amount_out, now)
+        .map(SwapQuote::from)
+        .map_err(quote_error)
+}
+
+/// Quotes the tokens a deposit minting `lp_amount` LP tokens needs, rounded up
+/// See `amm_quote::quote_deposit`
+pub fn quote_deposit(
+    config: &Config,
+    vault_x: u64,
+    vault_y: u64,
+    lp_supply: u64,
+    lp_amount: u64,
+) -> Result<LiquidityQuote> {
+    amm_quote::quote_deposit(&config.pool_state(), vault_x, vault_y, lp_supply, lp_amount)
+        .map(LiquidityQuote::from)
+        .map_err(quote_error)
+}
+
+/// Quotes the LP tokens the first deposit into an empty pool mints to the depositor
+/// See `amm_quote::quote_initial_deposit`
+pub fn quote_initial_deposit(reserve_x: u64, reserve_y: u64) -> Result<u64> {
+    amm_quote::quote_initial_deposit(reserve_x, reserve_y).map_err(quote_error)
+}
+
+/// Quotes the tokens a withdrawal burning `lp_amount` LP tokens returns, rounded down
+/// See `amm_quote::quote_withdraw`
+pub fn quote_withdraw(
+    config: &Config,
+    vault_x: u64,
+    vault_y: u64,
+    lp_supply: u64,
+    lp_amount: u64,
+) -> Result<LiquidityQuote> {
+    amm_quote::quote_withdraw(&config.pool_state(), vault_x, vault_y, lp_supply, lp_amount)
+        .map(LiquidityQuote::from)
+        .map_err(quote_error)
+}
+
+/// Output of selling `amount_in` into a constant product or StableSwap pool, and the fee
+/// See `amm_quote::quote_exact_in`
+pub fn quote_exact_in(
+    config: &Config,
+    is_x: bool,
+    reserve_x: u64,
+    reserve_y: u64,
+    lp_supply: u64,
+    amount_in: u64,
+    now: i64,
+) -> Result<(u64, u64)> {
+    amm_quote::quote_exact_in(
+        &config.pool_state(),
+        is_x,
+        reserve_x,
+        reserve_y,
+        lp_supply,
+        amount_in,
+        now,
+    )
+    .map_err(quote_error)
+}
+
+/// Pool reserves (x, y) after a swap
+/// See `amm_quote::post_swap_reserves`
+pub fn post_swap_reserves(
+    is_x: bool,
+    reserves: (u64, u64),
+    pool_in: u64,
+    pool_out: u64,
+) -> Result<(u64, u64)> {
+    amm_quote::post_swap_reserves(is_x, reserves, pool_in, pool_out).map_err(quote_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::states::CurveType;
+
+    fn config(curve_type: CurveType) -> Config {
+        Config {
+            seed: 0,
+            authority: None,
+            mint_x: Pubkey::new_unique(),
+            mint_y: Pubkey::new_unique(),
+            fee: 30,
+            dynamic_fee: false,
+            min_fee: 30,
+            max_fee: 30,
+            volatility: 0,
+            volatility_updated: 0,
+            protocol_fee: 2_000,
+            treasury: None,
+            protocol_fees_x: 1_234,
+            protocol_fees_y: 567,
+            flash_loans: false,
+            flash_loan_fee: 0,
+            flash_loan_x: 0,
+            flash_loan_y: 0,
+            curve_type,
+            amp_initial: 200,
+            amp_target: 200,
+            amp_ramp_start: 0,
+            amp_ramp_end: 0,
+            locked: false,
+            config_bump: 0,
+            lp_bump: 0,
+        }
+    }
+
+    #[test]
+    fn quotes_match_the_core_crate_on_the_pool_state() {
+        for curve_type in [CurveType::ConstantProduct, CurveType::StableSwap] {
+            let config = config(curve_type);
+            let pool = config.pool_state();
+            let (vault_x, vault_y, supply) = (5_001_234, 4_000_567, 4_400_000);
+
+            assert_eq!(
+                quote_swap_exact_in(&config, vault_x, vault_y, supply, true, 250_000, 0).unwrap(),
+                amm_quote::quote_swap_exact_in(&pool, vault_x, vault_y, supply, true, 250_000, 0).unwrap().into()
+            );
+            assert_eq!(
+                quote_swap_exact_out(&config, vault_x, vault_y, false, 250_000, 0).unwrap(),
+                amm_quote::quote_swap_exact_out(&pool, vault_x, vault_y, false, 250_000, 0).unwrap().into()
+            );
+            assert_eq!(
+                quote_deposit(&config, vault_x, vault_y, supply, 10_000).unwrap(),
+                amm_quote::quote_deposit(&pool, vault_x, vault_y, supply, 10_000).unwrap().into()
+            );
+            assert_eq!(
+                quote_withdraw(&config, vault_x, vault_y, supply, 10_000).unwrap(),
+                amm_quote::quote_withdraw(&pool, vault_x, vault_y, supply, 10_000).unwrap().into()
+            );
+        }
+    }
+
+    #[test]
+    fn quote_errors_surface_as_amm_errors() {
+        let mut config = config(CurveType::ConstantProduct);
+        assert_eq!(
+            quote_withdraw(&config, 10_000, 10_000, 10, 11).unwrap_err(),
+            AmmError::InsufficientBalance.into()
+        );
+        assert_eq!(quote_initial_deposit(10, 10).unwrap_err(), AmmError::InsufficientInitialLiquidity.into());
+
+        config.flash_loan_x = 1;
+        assert_eq!(
+            quote_swap_exact_in(&config, 10_000, 10_000, 1_000, true, 10, 0).unwrap_err(),
+            AmmError::FlashLoanActive.into()
+        );
+
+        config.flash_loan_x = 0;
+        config.curve_type = CurveType::Concentrated;
+        assert_eq!(
+            quote_swap_exact_out(&config, 10_000, 10_000, true, 10, 0).unwrap_err(),
+            AmmError::InvalidCurve.into()
+        );
+    }
+}
@@ -0,0 +1,343 @@
+use anchor_lang::prelude::*;
+
+use crate::concentrated_liquidity::{
+    compute_swap_step, sqrt_price_at_tick, tick_at_sqrt_price, Q64,
+};
+use crate::constants::{MAX_POOL_TICKS, MAX_TICK, MIN_POSITION_LIQUIDITY, MIN_TICK};
+use crate::errors::AmmError;
+use crate::states::Position;
+use crate::utils::mul_div;
+
+/// Boundary of one or more positions' price ranges.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct Tick {
+    /// Tick index, a multiple of the pool's `tick_spacing`.
+    pub index: i32,
+
+    /// Liquidity added to the active liquidity when the price crosses this tick upwards.
+    /// Subtracted when the price crosses it downwards.
+    pub liquidity_net: i128,
+
+    /// Total liquidity of the positions using this tick as a boundary.
+    /// The tick is dropped from the pool once this reaches zero.
+    pub liquidity_gross: u128,
+
+    /// Token X fee growth (Q64.64 per unit of liquidity) on the side of this tick
+    /// away from the current price.
+    pub fee_growth_outside_x: u128,
+
+    /// Token Y fee growth (Q64.64 per unit of liquidity) on the side of this tick
+    /// away from the current price.
+    pub fee_growth_outside_y: u128,
+}
+
+/// Price and liquidity state of a concentrated-liquidity pool,
+/// stored next to its `Config` at `[b"pool", config]`.
+///
+/// Initialized ticks are kept sorted by index in `ticks[..tick_count]`, so a swap can
+/// walk them without extra accounts. The pool can track at most `MAX_POOL_TICKS` of them.
+#[account]
+#[derive(InitSpace)]
+pub struct ConcentratedPool {
+    /// Pool configuration this state belongs to.
+    pub config: Pubkey,
+
+    /// Position ticks must be multiples of this spacing.
+    pub tick_spacing: u16,
+
+    /// Square root of the current price of X in Y, in Q64.64.
+    pub sqrt_price: u128,
+
+    /// Highest tick at or below the current price.
+    pub tick_current: i32,
+
+    /// Liquidity of all positions whose range contains the current price.
+    pub liquidity: u128,
+
+    /// Token X fees earned per unit of liquidity over the life of the pool (Q64.64).
+    /// Allowed to wrap around: always subtract it with `wrapping_sub`.
+    pub fee_growth_global_x: u128,
+
+    /// Token Y fees earned per unit of liquidity over the life of the pool (Q64.64).
+    /// Allowed to wrap around: always subtract it with `wrapping_sub`.
+    pub fee_growth_global_y: u128,
+
+    /// Number of entries of `ticks` in use.
+    pub tick_count: u16,
+
+    /// Initialized ticks, sorted by index.
+    pub ticks: [Tick; MAX_POOL_TICKS],
+
+    /// Bump used to derive the PDA for this pool account.
+    pub bump: u8,
+}
+
+impl ConcentratedPool {
+    /// Slot of the initialized tick at `index` in `ticks`, if any
+    pub fn find_tick(&self, index: i32) -> Option<usize> {
+        self.ticks[..self.tick_count as usize]
+            .binary_search_by_key(&index, |tick| tick.index)
+            .ok()
+    }
+
+    /// Checks a position's range: ordered, within bounds and aligned to the tick spacing
+    ///
+    /// # Arguments
+    /// * `tick_lower` - Lower tick of the range
+    /// * `tick_upper` - Upper tick of the range
+    pub fn validate_range(&self, tick_lower: i32, tick_upper: i32) -> Result<()> {
+        let spacing = self.tick_spacing as i32;
+        require!(
+            tick_lower < tick_upper
+                && tick_lower >= MIN_TICK
+                && tick_upper <= MAX_TICK
+                && tick_lower % spacing == 0
+                && tick_upper % spacing == 0,
+            AmmError::InvalidTickRange
+        );
+        Ok(())
+    }
+
+    /// Adds `liquidity_delta` to a position and to the ticks bounding it
+    ///
+    /// The position is credited with the fees its range earned since its last update
+    /// before its liquidity changes. A delta of zero only credits the fees. The position
+    /// must end up empty or with at least `MIN_POSITION_LIQUIDITY`.
+    ///
+    /// # Arguments
+    /// * `position` - Position being updated
+    /// * `liquidity_delta` - Liquidity added (positive) or removed (negative)
+    pub fn modify_position(&mut self, position: &mut Position, liquidity_delta: i128) -> Result<()> {
+        let (lower, upper) = (position.tick_lower, position.tick_upper);
+        self.update_tick(lower, liquidity_delta, false)?;
+        self.update_tick(upper, liquidity_delta, true)?;
+
+        let (inside_x, inside_y) = self.fee_growth_inside(lower, upper)?;
+        position.accrue_fees(inside_x, inside_y)?;
+        position.liquidity = position
+            .liquidity
+            .checked_add_signed(liquidity_delta)
+            .ok_or(AmmError::InsufficientBalance)?;
+        require!(
+            liquidity_delta == 0
+                || position.liquidity == 0
+                || position.liquidity >= MIN_POSITION_LIQUIDITY,
+            AmmError::PositionTooSmall
+        );
+
+        // Only positions whose range contains the current price are active
+        if lower <= self.tick_current && self.tick_current < upper {
+            self.liquidity = self
+                .liquidity
+                .checked_add_signed(liquidity_delta)
+                .ok_or(AmmError::Overflow)?;
+        }
+
+        // Free the slots of ticks no position uses any more
+        self.remove_tick_if_unused(lower);
+        self.remove_tick_if_unused(upper);
+        Ok(())
+    }
+
+    /// Adds liquidity to a tick, initializing it first if needed
+    ///
+    /// # Arguments
+    /// * `index` - Tick index
+    /// * `liquidity_delta` - Liquidity added (positive) or removed (negative)
+    /// * `upper` - true if the tick is the upper bound of the position
+    fn update_tick(&mut self, index: i32, liquidity_delta: i128, upper: bool) -> Result<()> {
+        let (slot, initialized) = match self.find_tick(index) {
+            Some(slot) => (slot, false),
+            None => (self.insert_tick(index)?, true),
+        };
+        let (global_x, global_y) = (self.fee_growth_global_x, self.fee_growth_global_y);
+        let tick = &mut self.ticks[slot];
+
+        tick.liquidity_gross = tick
+            .liquidity_gross
+            .checked_add_signed(liquidity_delta)
+            .ok_or(AmmError::InsufficientBalance)?;
+        // Entering the range upwards adds the liquidity, leaving it upwards removes it
+        tick.liquidity_net = match upper {
+            true => tick.liquidity_net.checked_sub(liquidity_delta),
+            false => tick.liquidity_net.checked_add(liquidity_delta),
+        }
+        .ok_or(AmmError::Overflow)?;
+
+        // By convention all fees so far were earned below a new tick at or below the price
+        if initialized && index <= self.tick_current {
+            tick.fee_growth_outside_x = global_x;
+            tick.fee_growth_outside_y = global_y;
+        }
+        Ok(())
+    }
+
+    /// Inserts an empty tick at `index`, keeping `ticks` sorted
+    ///
+    /// # Returns
+    /// * `Result<usize>` - Position of the new tick in `ticks`
+    fn insert_tick(&mut self, index: i32) -> Result<usize> {
+        let count = self.tick_count as usize;
+        require!(count < MAX_POOL_TICKS, AmmError::TickLimitReached);
+
+        let slot = self.ticks[..count].partition_point(|tick| tick.index < index);
+        self.ticks.copy_within(slot..count, slot + 1);
+        self.ticks[slot] = Tick {
+            index,
+            ..Tick::default()
+        };
+        self.tick_count += 1;
+        Ok(slot)
+    }
+
+    /// Removes the tick at `index` if no position uses it any more
+    fn remove_tick_if_unused(&mut self, index: i32) {
+        let count = self.tick_count as usize;
+        if let Some(slot) = self.find_tick(index) {
+            if self.ticks[slot].liquidity_gross == 0 {
+                self.ticks.copy_within(slot + 1..count, slot);
+                self.ticks[count - 1] = Tick::default();
+                self.tick_count -= 1;
+            }
+        }
+    }
+
+    /// Fees earned per unit of liquidity between two initialized ticks over the life of the pool
+    ///
+    /// # Returns
+    /// * `Result<(u128, u128)>` - Fee growth of token X and token Y inside the range (Q64.64)
+    pub fn fee_growth_inside(&self, tick_lower: i32, tick_upper: i32) -> Result<(u128, u128)> {
+        let lower = self.ticks[self.find_tick(tick_lower).ok_or(AmmError::InvalidTickRange)?];
+        let upper = self.ticks[self.find_tick(tick_upper).ok_or(AmmError::InvalidTickRange)?];
+        let (global_x, global_y) = (self.fee_growth_global_x, self.fee_growth_global_y);
+
+        // Fees earned below the lower tick and above the upper tick
+        let (below_x, below_y) = match self.tick_current >= tick_lower {
+            true => (lower.fee_growth_outside_x, lower.fee_growth_outside_y),
+            false => (
+                global_x.wrapping_sub(lower.fee_growth_outside_x),
+                global_y.wrapping_sub(lower.fee_growth_outside_y),
+            ),
+        };
+        let (above_x, above_y) = match self.tick_current < tick_upper {
+            true => (upper.fee_growth_outside_x, upper.fee_growth_outside_y),
+            false => (
+                global_x.wrapping_sub(upper.fee_growth_outside_x),
+                global_y.wrapping_sub(upper.fee_growth_outside_y),
+            ),
+        };
+
+        Ok((
+            global_x.wrapping_sub(below_x).wrapping_sub(above_x),
+            global_y.wrapping_sub(below_y).wrapping_sub(above_y),
+        ))
+    }
+
+    /// Sells `amount_in` into the pool, crossing initialized ticks as the price moves
+    ///
+    /// Swap fees are split between the protocol and the liquidity active in each range
+    /// the trade passes through. Fails if the pool runs out of liquidity before the
+    /// whole input is used.
+    ///
+    /// # Arguments
+    /// * `x_to_y` - true if selling token X for Y (price moves down), false for Y to X
+    /// * `amount_in` - Input received by the pool, fees included
+    /// * `fee` - Swap fee in basis points
+    /// * `protocol_fee` - Protocol share of the swap fee in basis points of the fee
+    ///
+    /// # Returns
+    /// * `Result<(u64, u64, u64)>` - Amount the pool sends out, the swap fee charged
+    ///   and the protocol's share of it
+    pub fn swap(
+        &mut self,
+        x_to_y: bool,
+        amount_in: u64,
+        fee: u16,
+        protocol_fee: u16,
+    ) -> Result<(u64, u64, u64)> {
+        let price_bound = match x_to_y {
+            true => sqrt_price_at_tick(MIN_TICK)?,
+            false => sqrt_price_at_tick(MAX_TICK)?,
+        };
+        let mut remaining = amount_in;
+        let mut amount_out = 0u64;
+        let mut fee_total = 0u64;
+        let mut protocol_share = 0u64;
+
+        while remaining > 0 && self.sqrt_price != price_bound {
+            // Trade up to the next initialized tick, or to the edge of the price range
+            let next_tick = self.next_initialized_tick(x_to_y);
+            let target = match next_tick {
+                Some(slot) => sqrt_price_at_tick(self.ticks[slot].index)?,
+                None => price_bound,
+            };
+            let step = compute_swap_step(self.sqrt_price, target, self.liquidity, remaining, fee)?;
+
+            remaining -= step.amount_in + step.fee_amount;
+            amount_out = amount_out
+                .checked_add(step.amount_out)
+                .ok_or(AmmError::Overflow)?;
+
+            // Step fees add up to at most amount_in, so neither sum can overflow
+            fee_total += step.fee_amount;
+            // protocol_fee <= 10_000 so the share always fits back into a u64
+            let step_protocol = (step.fee_amount as u128 * protocol_fee as u128 / 10_000) as u64;
+            protocol_share += step_protocol;
+            // The rest of the fee goes to the liquidity that was active during the step
+            if self.liquidity != 0 {
+                let growth = mul_div((step.fee_amount - step_protocol) as u128, Q64, self.liquidity)?;
+                match x_to_y {
+                    true => self.fee_growth_global_x = self.fee_growth_global_x.wrapping_add(growth),
+                    false => self.fee_growth_global_y = self.fee_growth_global_y.wrapping_add(growth),
+                }
+            }
+
+            self.sqrt_price = step.sqrt_price_next;
+            match (step.sqrt_price_next == target, next_tick) {
+                // Reached an initialized tick: cross it into the next range
+                (true, Some(slot)) => {
+                    let index = self.ticks[slot].index;
+                    self.cross_tick(slot, x_to_y)?;
+                    self.tick_current = match x_to_y {
+                        true => index - 1,
+                        false => index,
+                    };
+                }
+                _ => self.tick_current = tick_at_sqrt_price(self.sqrt_price)?,
+            }
+        }
+
+        // Partial fills would leave the user's unused input stuck in the vault
+        require!(remaining == 0, AmmError::NoLiquidityInPool);
+        Ok((amount_out, fee_total, protocol_share))
+    }
+
+    /// Next initialized tick in the direction the price is moving
+    /// At or below the current tick when the price falls, above it when the price rises
+    fn next_initialized_tick(&self, x_to_y: bool) -> Option<usize> {
+        let count = self.tick_count as usize;
+        let above = self.ticks[..count].partition_point(|tick| tick.index <= self.tick_current);
+        match x_to_y {
+            true => above.checked_sub(1),
+            false => (above < count).then_some(above),
+        }
+    }
+
+    /// Moves the price across an initialized tick, updating the active liquidity
+    /// and flipping the tick's fee growth to the other side
+    fn cross_tick(&mut self, slot: usize, x_to_y: bool) -> Result<()> {
+        let tick = &mut self.ticks[slot];
+        tick.fee_growth_outside_x = self.fee_growth_global_x.wrapping_sub(tick.fee_growth_outside_x);
+        tick.fee_growth_outside_y = self.fee_growth_global_y.wrapping_sub(tick.fee_growth_outside_y);
+
+        let liquidity_net = match x_to_y {
+            true => tick.liquidity_net.checked_neg().ok_or(AmmError::Overflow)?,
+            false => tick.liquidity_net,
+        };
+        self.liquidity = self
+            .liquidity
+            .checked_add_signed(liquidity_net)
+            .ok_or(AmmError::Overflow)?;
+        Ok(())
+    }
+}
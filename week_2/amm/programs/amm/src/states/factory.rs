@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::MAX_FEE_TIERS;
+use crate::states::CurveType;
+
+/// Program-wide pool factory, stored at `[b"factory"]`.
+/// Creates at most one canonical pool per sorted mint pair and fee tier.
+#[account]
+#[derive(InitSpace)]
+pub struct Factory {
+    /// Wallet that manages the fee tiers.
+    /// Also manages every pool the factory creates, which name the factory as their authority.
+    pub authority: Pubkey,
+
+    /// Swap fees (in basis points) canonical pools can be created with.
+    #[max_len(MAX_FEE_TIERS)]
+    pub fee_tiers: Vec<u16>,
+
+    /// Bump used to derive the PDA for this factory account.
+    pub bump: u8,
+}
+
+/// Registry entry of the canonical pool for a mint pair, fee tier and curve,
+/// stored at `[b"pair", mint_x, mint_y, fee, curve_type]` with `mint_x < mint_y`.
+#[account]
+#[derive(InitSpace)]
+pub struct Pair {
+    /// Lower of the two mints (token X of the pool).
+    pub mint_x: Pubkey,
+
+    /// Higher of the two mints (token Y of the pool).
+    pub mint_y: Pubkey,
+
+    /// Fee tier of the pool (in basis points).
+    pub fee: u16,
+
+    /// Invariant the pool prices swaps with.
+    pub curve_type: CurveType,
+
+    /// Configuration account of the canonical pool.
+    pub config: Pubkey,
+
+    /// Bump used to derive the PDA for this registry entry.
+    pub bump: u8,
+}
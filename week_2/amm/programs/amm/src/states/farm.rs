@@ -0,0 +1,117 @@
+use anchor_lang::prelude::*;
+
+use crate::concentrated_liquidity::Q64;
+use crate::utils::mul_div;
+
+/// Liquidity mining rewards for a pool's LP tokens, stored at `[b"farm", config]`.
+///
+/// Stakers share `reward_rate` tokens per second in proportion to their staked LP,
+/// tracked with a reward-per-share accumulator so each update costs the same
+/// however many stakers there are.
+#[account]
+#[derive(InitSpace)]
+pub struct Farm {
+    /// Pool configuration whose LP tokens are staked.
+    pub config: Pubkey,
+
+    /// Wallet that funds and extends reward periods.
+    pub authority: Pubkey,
+
+    /// Mint of the reward token.
+    pub mint_reward: Pubkey,
+
+    /// LP tokens currently staked, held in the farm's LP token account.
+    pub total_staked: u64,
+
+    /// Reward tokens emitted per second until `period_end`.
+    pub reward_rate: u64,
+
+    /// Unix timestamp the current reward period ends at.
+    pub period_end: i64,
+
+    /// Unix timestamp rewards were last accrued into `reward_per_share`.
+    pub last_update: i64,
+
+    /// Rewards earned per staked LP token over the life of the farm (Q64.64).
+    /// Allowed to wrap around: always subtract it with `wrapping_sub`.
+    pub reward_per_share: u128,
+
+    /// Bump used to derive the PDA for this farm account.
+    pub bump: u8,
+}
+
+impl Farm {
+    /// Accrues the rewards emitted since the last update into `reward_per_share`
+    /// Nothing accrues while no LP is staked; those rewards stay in the reward vault
+    ///
+    /// # Arguments
+    /// * `now` - Current unix timestamp
+    pub fn update(&mut self, now: i64) -> Result<()> {
+        let until = now.min(self.period_end);
+        if until <= self.last_update {
+            return Ok(());
+        }
+
+        if self.total_staked != 0 {
+            let emitted = self.reward_rate as u128 * (until - self.last_update) as u128;
+            let per_share = mul_div(emitted, Q64, self.total_staked as u128)?;
+            self.reward_per_share = self.reward_per_share.wrapping_add(per_share);
+        }
+        self.last_update = until;
+
+        Ok(())
+    }
+
+    /// Rewards not yet emitted in the current period
+    ///
+    /// # Arguments
+    /// * `now` - Current unix timestamp
+    pub fn remaining_rewards(&self, now: i64) -> u64 {
+        let left = self.period_end.saturating_sub(now).max(0) as u128;
+        u64::try_from(left * self.reward_rate as u128).unwrap_or(u64::MAX)
+    }
+}
+
+/// LP tokens a wallet has staked in a farm, stored at `[b"stake", farm, owner]`.
+#[account]
+#[derive(InitSpace)]
+pub struct Stake {
+    /// Farm the LP tokens are staked in.
+    pub farm: Pubkey,
+
+    /// Wallet that staked them and receives the rewards.
+    pub owner: Pubkey,
+
+    /// LP tokens staked.
+    pub amount: u64,
+
+    /// Farm `reward_per_share` when the rewards were last credited.
+    pub reward_per_share_paid: u128,
+
+    /// Rewards earned and not yet harvested.
+    pub rewards_owed: u64,
+
+    /// Bump used to derive the PDA for this stake account.
+    pub bump: u8,
+}
+
+impl Stake {
+    /// Credits the rewards earned since the last update and moves the checkpoint forward
+    /// Rewards owed saturate at `u64::MAX` instead of failing, so a stake can always be updated
+    ///
+    /// # Arguments
+    /// * `reward_per_share` - Current `reward_per_share` of the farm, already updated
+    pub fn accrue_rewards(&mut self, reward_per_share: u128) -> Result<()> {
+        // The accumulator wraps around like fee growth, so subtract with wrapping_sub
+        let earned = mul_div(
+            reward_per_share.wrapping_sub(self.reward_per_share_paid),
+            self.amount as u128,
+            Q64,
+        )?;
+        self.rewards_owed = self
+            .rewards_owed
+            .saturating_add(u64::try_from(earned).unwrap_or(u64::MAX));
+        self.reward_per_share_paid = reward_per_share;
+        Ok(())
+    }
+}
@@ -1,4 +1,45 @@
 use anchor_lang::prelude::*;
+use amm_quote::{Curve, PoolState};
+
+use crate::constants::{VOLATILITY_FEE_DIVISOR, VOLATILITY_HALF_LIFE};
+use crate::errors::AmmError;
+use crate::utils::mul_div;
+
+pub mod oracle;
+pub use oracle::*;
+
+pub mod concentrated_pool;
+pub use concentrated_pool::*;
+
+pub mod position;
+pub use position::*;
+
+pub mod factory;
+pub use factory::*;
+
+pub mod farm;
+pub use farm::*;
+
+/// Invariant a pool prices swaps with, chosen at `initialize`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum CurveType {
+    /// x * y = k, for uncorrelated pairs.
+    ConstantProduct,
+    /// Curve-style StableSwap with amplification, for pegged pairs (stablecoins, LSTs).
+    StableSwap,
+    /// Uniswap v3 style liquidity over tick ranges, created with `initialize_concentrated`.
+    Concentrated,
+}
+
+impl From<CurveType> for Curve {
+    fn from(curve_type: CurveType) -> Curve {
+        match curve_type {
+            CurveType::ConstantProduct => Curve::ConstantProduct,
+            CurveType::StableSwap => Curve::StableSwap,
+            CurveType::Concentrated => Curve::Concentrated,
+        }
+    }
+}
 
 #[account]
 #[derive(InitSpace)]
@@ -10,6 +51,7 @@ pub struct Config {
     /// Optional authority/maintainer of the AMM.
     /// Can be used to control parameters like fees or to pause the AMM.
     /// If set to `None`, the AMM is fully decentralized.
+    /// Pools created by `create_pool` name the factory here and are managed by its authority.
     pub authority: Option<Pubkey>,
 
     /// SPL token mint address for Token X (first asset in the pair).
@@ -19,9 +61,75 @@ pub struct Config {
     pub mint_y: Pubkey,
 
     /// Swap fee taken on each trade (in basis points, e.g., 30 = 0.3%).
-    /// This fee typically goes to liquidity providers or protocol treasury.
+    /// Stays in the vaults for liquidity providers, less the `protocol_fee` share.
     pub fee: u16,
 
+    /// Whether swaps pay a fee between `min_fee` and `max_fee` that follows recent
+    /// volatility instead of the static `fee`.
+    pub dynamic_fee: bool,
+
+    /// Dynamic fee charged when the price has been still (in basis points).
+    pub min_fee: u16,
+
+    /// Highest dynamic fee, however volatile the price (in basis points).
+    pub max_fee: u16,
+
+    /// Recent price movement (in basis points), added up swap by swap.
+    /// Halves every `VOLATILITY_HALF_LIFE` seconds.
+    pub volatility: u64,
+
+    /// Unix timestamp `volatility` was last decayed to.
+    pub volatility_updated: i64,
+
+    /// Share of every swap fee set aside for the protocol (in basis points of `fee`).
+    /// Zero means liquidity providers keep the whole swap fee.
+    pub protocol_fee: u16,
+
+    /// Owner of the token accounts that `collect_protocol_fees` pays out to.
+    /// Protocol fees accrue but can't be collected while this is `None`.
+    pub treasury: Option<Pubkey>,
+
+    /// Token X protocol fees held in vault X but not yet collected.
+    /// Not part of the pool reserves used for pricing.
+    pub protocol_fees_x: u64,
+
+    /// Token Y protocol fees held in vault Y but not yet collected.
+    /// Not part of the pool reserves used for pricing.
+    pub protocol_fees_y: u64,
+
+    /// Whether `flash_borrow` may lend from the vaults.
+    /// Off until the authority enables flash loans.
+    pub flash_loans: bool,
+
+    /// Fee charged on flash loans (in basis points of the amount borrowed).
+    /// Stays in the vaults for liquidity providers, less the `protocol_fee` share.
+    pub flash_loan_fee: u16,
+
+    /// Token X a flash loan in progress owes vault X, fee included.
+    /// Only non-zero between `flash_borrow` and `flash_repay` in the same transaction.
+    pub flash_loan_x: u64,
+
+    /// Token Y a flash loan in progress owes vault Y, fee included.
+    /// Only non-zero between `flash_borrow` and `flash_repay` in the same transaction.
+    pub flash_loan_y: u64,
+
+    /// Invariant used to price swaps.
+    pub curve_type: CurveType,
+
+    /// StableSwap amplification coefficient at `amp_ramp_start`.
+    /// Unused (zero) for constant product pools.
+    pub amp_initial: u64,
+
+    /// StableSwap amplification coefficient reached at `amp_ramp_end`.
+    /// Equal to `amp_initial` when no ramp is in progress.
+    pub amp_target: u64,
+
+    /// Unix timestamp the current amplification ramp started at.
+    pub amp_ramp_start: i64,
+
+    /// Unix timestamp the current amplification ramp ends at.
+    pub amp_ramp_end: i64,
+
     /// Boolean flag to lock the AMM.
     /// When `true`, operations like swap or deposit can be disabled.
     pub locked: bool,
@@ -33,4 +141,131 @@ pub struct Config {
     /// Bump used to derive the PDA for the LP token mint account.
     /// LP tokens represent a user’s share of the liquidity pool.
     pub lp_bump: u8,
-}
\ No newline at end of file
+}
+
+impl Config {
+    /// The parts of the config its quotes depend on, as the `amm_quote` crate takes them
+    pub fn pool_state(&self) -> PoolState {
+        PoolState {
+            curve: self.curve_type.into(),
+            fee: self.fee,
+            dynamic_fee: self.dynamic_fee,
+            min_fee: self.min_fee,
+            max_fee: self.max_fee,
+            volatility: self.volatility,
+            volatility_updated: self.volatility_updated,
+            protocol_fee: self.protocol_fee,
+            protocol_fees_x: self.protocol_fees_x,
+            protocol_fees_y: self.protocol_fees_y,
+            flash_loan_x: self.flash_loan_x,
+            flash_loan_y: self.flash_loan_y,
+            amp_initial: self.amp_initial,
+            amp_target: self.amp_target,
+            amp_ramp_start: self.amp_ramp_start,
+            amp_ramp_end: self.amp_ramp_end,
+        }
+    }
+
+    /// Pool reserves backing the curve: vault balances minus uncollected protocol fees
+    /// Fails while a flash loan is in progress
+    ///
+    /// # Arguments
+    /// * `vault_x` - Current balance of vault X
+    /// * `vault_y` - Current balance of vault Y
+    pub fn reserves(&self, vault_x: u64, vault_y: u64) -> Result<(u64, u64)> {
+        self.pool_state()
+            .reserves(vault_x, vault_y)
+            .map_err(|error| AmmError::from(error).into())
+    }
+
+    /// Whether the pool was created by `factory` and is managed through it
+    ///
+    /// # Arguments
+    /// * `factory` - The factory account, if the instruction was given one
+    pub fn is_factory_pool(&self, factory: Option<&Account<Factory>>) -> bool {
+        factory.is_some_and(|factory| self.authority == Some(factory.key()))
+    }
+
+    /// Whether `signer` may manage the pool: its authority, or for factory pools
+    /// whoever is the factory authority at the time
+    ///
+    /// # Arguments
+    /// * `signer` - Key that signed the admin instruction
+    /// * `factory` - The factory account, required to manage factory pools
+    pub fn is_authority(&self, signer: Pubkey, factory: Option<&Account<Factory>>) -> bool {
+        match factory {
+            Some(factory) if self.is_factory_pool(Some(factory)) => factory.authority == signer,
+            _ => self.authority == Some(signer),
+        }
+    }
+
+    /// Swap fee in basis points charged at `now`: the static `fee`, or the dynamic fee
+    /// `min_fee + volatility / VOLATILITY_FEE_DIVISOR` capped at `max_fee`
+    ///
+    /// # Arguments
+    /// * `now` - Current unix timestamp
+    pub fn swap_fee(&self, now: i64) -> u16 {
+        self.pool_state().swap_fee(now)
+    }
+
+    /// Volatility decayed to `now`
+    ///
+    /// # Arguments
+    /// * `now` - Current unix timestamp
+    pub fn volatility_at(&self, now: i64) -> u64 {
+        self.pool_state().volatility_at(now)
+    }
+
+    /// Adds the price movement of a swap to the volatility behind the dynamic fee
+    ///
+    /// The price is the reserve ratio y / x, which for StableSwap pools only approximates
+    /// the marginal price. Volatility is capped where the fee reaches `max_fee`, so a burst
+    /// of trading keeps the fee at its highest for one half-life at most.
+    ///
+    /// # Arguments
+    /// * `now` - Current unix timestamp
+    /// * `before` - Reserves (x, y) before the swap
+    /// * `after` - Reserves (x, y) after the swap
+    pub fn record_price_move(&mut self, now: i64, before: (u64, u64), after: (u64, u64)) -> Result<()> {
+        if !self.dynamic_fee {
+            return Ok(());
+        }
+
+        // Decay in whole half-lives, keeping the remainder for the next swap
+        let halvings = now.saturating_sub(self.volatility_updated).max(0) / VOLATILITY_HALF_LIFE;
+        self.volatility = self.volatility_at(now);
+        self.volatility_updated = match halvings {
+            0..=63 => self.volatility_updated + halvings * VOLATILITY_HALF_LIFE,
+            _ => now,
+        };
+
+        // |y1 / x1 - y0 / x0| / (y0 / x0) = |y1 * x0 - y0 * x1| / (y0 * x1)
+        let new = after.1 as u128 * before.0 as u128;
+        let old = before.1 as u128 * after.0 as u128;
+        if old == 0 {
+            return Ok(());
+        }
+        let moved = mul_div(new.abs_diff(old), 10_000, old)?;
+
+        let cap = (self.max_fee - self.min_fee) as u64 * VOLATILITY_FEE_DIVISOR;
+        self.volatility = self
+            .volatility
+            .saturating_add(u64::try_from(moved).unwrap_or(u64::MAX))
+            .min(cap);
+        Ok(())
+    }
+
+    /// Whether a flash loan is waiting to be repaid
+    pub fn flash_loan_active(&self) -> bool {
+        self.pool_state().flash_loan_active()
+    }
+
+    /// StableSwap amplification coefficient at `now`
+    /// Moves linearly from `amp_initial` to `amp_target` over the ramp
+    ///
+    /// # Arguments
+    /// * `now` - Current unix timestamp
+    pub fn amp(&self, now: i64) -> u64 {
+        self.pool_state().amp(now)
+    }
+}
@@ -0,0 +1,194 @@
+use anchor_lang::prelude::*;
+
+use crate::concentrated_liquidity::Q64;
+use crate::constants::OBSERVATION_COUNT;
+use crate::utils::mul_div;
+
+/// Snapshot of the price accumulators at a point in time.
+/// The TWAP between two observations is the accumulator difference over the time difference.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct Observation {
+    /// Unix timestamp the accumulators were recorded at.
+    pub timestamp: i64,
+
+    /// `price_x_cumulative` of the oracle at `timestamp`.
+    pub price_x_cumulative: u128,
+
+    /// `price_y_cumulative` of the oracle at `timestamp`.
+    pub price_y_cumulative: u128,
+}
+
+/// Price history of a pool, stored next to its `Config` at `[b"oracle", config]`.
+///
+/// Prices are Q64.64 fixed-point numbers (the real price times 2^64):
+/// - price of X = reserve Y / reserve X (Y paid per X)
+/// - price of Y = reserve X / reserve Y (X paid per Y)
+///
+/// Concentrated-liquidity pools report the square of their square-root price instead.
+/// For StableSwap pools this reserve ratio is a conservative stand-in for the marginal
+/// price, which sits closer to 1:1 the larger the amplification coefficient.
+///
+/// Each accumulator is the sum of price * seconds over the life of the pool. It is
+/// updated with the reserves from *before* every swap, deposit and withdrawal, so
+/// a trade can only move the price the pool reports from the next second on.
+/// Accumulators are allowed to wrap around: always subtract them with `wrapping_sub`.
+#[account]
+#[derive(InitSpace)]
+pub struct Oracle {
+    /// Pool configuration this oracle belongs to.
+    pub config: Pubkey,
+
+    /// Unix timestamp of the last accumulator update.
+    pub last_update: i64,
+
+    /// Sum of (price of X in Q64.64) * seconds elapsed.
+    pub price_x_cumulative: u128,
+
+    /// Sum of (price of Y in Q64.64) * seconds elapsed.
+    pub price_y_cumulative: u128,
+
+    /// Slot in `observations` the next observation is written to.
+    pub observation_index: u16,
+
+    /// Number of slots in `observations` holding data (grows up to `OBSERVATION_COUNT`).
+    pub observation_count: u16,
+
+    /// Ring buffer of the most recent observations, at most one per second.
+    pub observations: [Observation; OBSERVATION_COUNT],
+
+    /// Bump used to derive the PDA for this oracle account.
+    pub bump: u8,
+}
+
+impl Oracle {
+    /// Empty price history for a pool created at `now`, with a first observation recorded
+    ///
+    /// # Arguments
+    /// * `config` - Pool configuration the oracle belongs to
+    /// * `now` - Unix timestamp the pool is created at
+    /// * `bump` - Bump of the oracle PDA
+    pub fn new(config: Pubkey, now: i64, bump: u8) -> Self {
+        let mut oracle = Oracle {
+            config,
+            last_update: now,
+            price_x_cumulative: 0,
+            price_y_cumulative: 0,
+            observation_index: 0,
+            observation_count: 0,
+            observations: [Observation::default(); OBSERVATION_COUNT],
+            bump,
+        };
+        oracle.record();
+        oracle
+    }
+
+    /// Accrues the prices implied by the current reserves since the last update
+    /// and records a new observation
+    /// Called at the start of every instruction that changes the reserves
+    ///
+    /// # Arguments
+    /// * `reserve_x` - Pool reserves of token X before the instruction
+    /// * `reserve_y` - Pool reserves of token Y before the instruction
+    pub fn update(&mut self, reserve_x: u64, reserve_y: u64) -> Result<()> {
+        // An empty pool has no price; time passes without accruing anything
+        let prices = (reserve_x != 0 && reserve_y != 0).then(|| {
+            (
+                ((reserve_y as u128) << 64) / reserve_x as u128,
+                ((reserve_x as u128) << 64) / reserve_y as u128,
+            )
+        });
+        self.accrue(prices)
+    }
+
+    /// Same as `update`, for concentrated-liquidity pools that track the price directly
+    ///
+    /// # Arguments
+    /// * `sqrt_price` - Square-root price of X in Y (Q64.64) before the instruction
+    pub fn update_sqrt_price(&mut self, sqrt_price: u128) -> Result<()> {
+        let price_x = mul_div(sqrt_price, sqrt_price, Q64)?;
+        let prices = match price_x {
+            0 => None,
+            // Near the lowest tick the price of Y no longer fits in Q64.64
+            _ => Some((price_x, mul_div(Q64, Q64, price_x).unwrap_or(u128::MAX))),
+        };
+        self.accrue(prices)
+    }
+
+    /// Adds `prices` times the seconds elapsed since the last update to the accumulators
+    /// and records a new observation
+    ///
+    /// # Arguments
+    /// * `prices` - Price of X and price of Y (Q64.64), `None` if the pool has no price
+    fn accrue(&mut self, prices: Option<(u128, u128)>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let elapsed = now.saturating_sub(self.last_update);
+        // Several updates in the same second add nothing to the accumulators
+        if elapsed <= 0 {
+            return Ok(());
+        }
+
+        if let Some((price_x, price_y)) = prices {
+            self.price_x_cumulative = self
+                .price_x_cumulative
+                .wrapping_add(price_x.wrapping_mul(elapsed as u128));
+            self.price_y_cumulative = self
+                .price_y_cumulative
+                .wrapping_add(price_y.wrapping_mul(elapsed as u128));
+        }
+
+        self.last_update = now;
+        self.record();
+        Ok(())
+    }
+
+    /// Writes the current accumulators into the ring buffer, overwriting the oldest entry when full
+    pub fn record(&mut self) {
+        self.observations[self.observation_index as usize] = Observation {
+            timestamp: self.last_update,
+            price_x_cumulative: self.price_x_cumulative,
+            price_y_cumulative: self.price_y_cumulative,
+        };
+        self.observation_index = ((self.observation_index as usize + 1) % OBSERVATION_COUNT) as u16;
+        if (self.observation_count as usize) < OBSERVATION_COUNT {
+            self.observation_count += 1;
+        }
+    }
+
+    /// Most recent observation, `None` before the first one is recorded
+    pub fn latest(&self) -> Option<Observation> {
+        if self.observation_count == 0 {
+            return None;
+        }
+        let index = (self.observation_index as usize + OBSERVATION_COUNT - 1) % OBSERVATION_COUNT;
+        Some(self.observations[index])
+    }
+
+    /// Time-weighted average prices (Q64.64) over at least the last `window` seconds
+    /// of recorded history
+    ///
+    /// Averages from the newest observation back to the newest one that is at least
+    /// `window` seconds older. Returns `None` if the ring buffer doesn't reach back that far.
+    ///
+    /// # Arguments
+    /// * `window` - Minimum number of seconds the average must cover
+    ///
+    /// # Returns
+    /// * `Option<(u128, u128)>` - Average price of X and average price of Y
+    pub fn twap(&self, window: i64) -> Option<(u128, u128)> {
+        let newest = self.latest()?;
+        let count = self.observation_count as usize;
+        // Walk backwards from the second newest observation
+        for back in 2..=count {
+            let index = (self.observation_index as usize + OBSERVATION_COUNT - back) % OBSERVATION_COUNT;
+            let older = self.observations[index];
+            let elapsed = newest.timestamp - older.timestamp;
+            if elapsed >= window && elapsed > 0 {
+                return Some((
+                    newest.price_x_cumulative.wrapping_sub(older.price_x_cumulative) / elapsed as u128,
+                    newest.price_y_cumulative.wrapping_sub(older.price_y_cumulative) / elapsed as u128,
+                ));
+            }
+        }
+        None
+    }
+}
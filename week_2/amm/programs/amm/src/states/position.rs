@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+
+use crate::concentrated_liquidity::Q64;
+use crate::utils::mul_div;
+
+/// Liquidity an owner provides over a tick range of a concentrated-liquidity pool,
+/// stored at `[b"position", config, owner, tick_lower, tick_upper]`.
+#[account]
+#[derive(InitSpace)]
+pub struct Position {
+    /// Pool configuration this position belongs to.
+    pub config: Pubkey,
+
+    /// Wallet that can add and remove liquidity and collect the fees.
+    pub owner: Pubkey,
+
+    /// Lower tick of the range (inclusive).
+    pub tick_lower: i32,
+
+    /// Upper tick of the range (exclusive).
+    pub tick_upper: i32,
+
+    /// Liquidity the position provides while the price is inside its range.
+    pub liquidity: u128,
+
+    /// Fee growth of token X inside the range (Q64.64 per unit of liquidity) at the last update.
+    pub fee_growth_inside_x_last: u128,
+
+    /// Fee growth of token Y inside the range (Q64.64 per unit of liquidity) at the last update.
+    pub fee_growth_inside_y_last: u128,
+
+    /// Token X fees earned and not yet collected.
+    pub fees_owed_x: u64,
+
+    /// Token Y fees earned and not yet collected.
+    pub fees_owed_y: u64,
+
+    /// Bump used to derive the PDA for this position account.
+    pub bump: u8,
+}
+
+impl Position {
+    /// Credits the fees earned since the last update and moves the checkpoints forward
+    /// Fees owed saturate at `u64::MAX` instead of failing, so a position can always be updated
+    ///
+    /// # Arguments
+    /// * `fee_growth_inside_x` - Current fee growth of token X inside the range
+    /// * `fee_growth_inside_y` - Current fee growth of token Y inside the range
+    pub fn accrue_fees(&mut self, fee_growth_inside_x: u128, fee_growth_inside_y: u128) -> Result<()> {
+        // Fee growth wraps around like the oracle accumulators, so subtract with wrapping_sub
+        let earned_x = mul_div(
+            fee_growth_inside_x.wrapping_sub(self.fee_growth_inside_x_last),
+            self.liquidity,
+            Q64,
+        )?;
+        let earned_y = mul_div(
+            fee_growth_inside_y.wrapping_sub(self.fee_growth_inside_y_last),
+            self.liquidity,
+            Q64,
+        )?;
+
+        self.fees_owed_x = self
+            .fees_owed_x
+            .saturating_add(u64::try_from(earned_x).unwrap_or(u64::MAX));
+        self.fees_owed_y = self
+            .fees_owed_y
+            .saturating_add(u64::try_from(earned_y).unwrap_or(u64::MAX));
+        self.fee_growth_inside_x_last = fee_growth_inside_x;
+        self.fee_growth_inside_y_last = fee_growth_inside_y;
+        Ok(())
+    }
+}
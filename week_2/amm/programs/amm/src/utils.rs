@@ -0,0 +1,142 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    extension::{
+        default_account_state::DefaultAccountState,
+        transfer_fee::TransferFeeConfig,
+        BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+    },
+    state::{AccountState, Mint as MintState},
+};
+use anchor_spl::token_interface::Mint;
+
+use crate::errors::AmmError;
+
+/// Token-2022 extensions a pool mint may not carry
+/// - `NonTransferable`: tokens could never leave the user's account
+/// - `PermanentDelegate`: the delegate could drain the vaults at any time
+/// - `TransferHook`: vault transfers don't pass the extra accounts a hook needs
+const UNSUPPORTED_EXTENSIONS: [ExtensionType; 3] = [
+    ExtensionType::NonTransferable,
+    ExtensionType::PermanentDelegate,
+    ExtensionType::TransferHook,
+];
+
+/// Rejects mints whose extensions would break the pool
+/// Legacy SPL mints have no extensions and are always accepted
+///
+/// # Arguments
+/// * `mint` - Mint of token X or token Y
+pub fn validate_mint(mint: &InterfaceAccount<Mint>) -> Result<()> {
+    let info = mint.to_account_info();
+    if *info.owner == anchor_spl::token::ID {
+        return Ok(());
+    }
+
+    let data = info.try_borrow_data()?;
+    let state = StateWithExtensions::<MintState>::unpack(&data)?;
+    for extension in state.get_extension_types()? {
+        require!(
+            !UNSUPPORTED_EXTENSIONS.contains(&extension),
+            AmmError::UnsupportedMintExtension
+        );
+    }
+
+    // Vaults created frozen could never receive or send tokens
+    if let Ok(default_state) = state.get_extension::<DefaultAccountState>() {
+        require!(
+            default_state.state != AccountState::Frozen as u8,
+            AmmError::UnsupportedMintExtension
+        );
+    }
+
+    Ok(())
+}
+
+/// Transfer fee withheld when `amount` tokens of `mint` are sent this epoch
+/// Zero for legacy SPL mints and Token-2022 mints without a transfer fee
+///
+/// # Arguments
+/// * `mint` - Mint of the token being transferred
+/// * `amount` - Amount passed to `transfer_checked`
+pub fn transfer_fee(mint: &InterfaceAccount<Mint>, amount: u64) -> Result<u64> {
+    let info = mint.to_account_info();
+    if *info.owner == anchor_spl::token::ID {
+        return Ok(0);
+    }
+
+    let data = info.try_borrow_data()?;
+    let state = StateWithExtensions::<MintState>::unpack(&data)?;
+    match state.get_extension::<TransferFeeConfig>() {
+        Ok(config) => config
+            .calculate_epoch_fee(Clock::get()?.epoch, amount)
+            .ok_or(AmmError::Overflow.into()),
+        Err(_) => Ok(0),
+    }
+}
+
+/// Amount that must be sent so that `received` tokens of `mint` arrive
+/// after the transfer fee is withheld (the inverse of `transfer_fee`)
+///
+/// # Arguments
+/// * `mint` - Mint of the token being transferred
+/// * `received` - Amount the destination account must be credited with
+pub fn amount_before_fee(mint: &InterfaceAccount<Mint>, received: u64) -> Result<u64> {
+    let info = mint.to_account_info();
+    if *info.owner == anchor_spl::token::ID {
+        return Ok(received);
+    }
+
+    let data = info.try_borrow_data()?;
+    let state = StateWithExtensions::<MintState>::unpack(&data)?;
+    let fee = match state.get_extension::<TransferFeeConfig>() {
+        Ok(config) => config
+            .calculate_inverse_epoch_fee(Clock::get()?.epoch, received)
+            .ok_or(AmmError::Overflow)?,
+        Err(_) => 0,
+    };
+    received.checked_add(fee).ok_or(AmmError::Overflow.into())
+}
+
+/// Fails with `OfferExpired` once the cluster clock has passed `expiration`
+/// Lets a signed transaction that lands late fail instead of trading at a stale price
+///
+/// # Arguments
+/// * `expiration` - Unix timestamp after which the instruction is rejected, `None` for no deadline
+pub fn check_expiration(expiration: Option<i64>) -> Result<()> {
+    if let Some(expiration) = expiration {
+        require!(
+            Clock::get()?.unix_timestamp <= expiration,
+            AmmError::OfferExpired
+        );
+    }
+    Ok(())
+}
+
+/// Full 256-bit product of `a` and `b` as (high, low) 128-bit halves
+pub use amm_quote::math::full_mul;
+
+/// Computes `a * b / c` rounded down, with a 256-bit intermediate product
+///
+/// # Arguments
+/// * `a`, `b` - Factors of the numerator
+/// * `c` - Denominator, must not be zero
+pub fn mul_div(a: u128, b: u128, c: u128) -> Result<u128> {
+    amm_quote::math::mul_div(a, b, c).map_err(|error| AmmError::from(error).into())
+}
+
+/// Computes `a * b / c` rounded up, with a 256-bit intermediate product
+///
+/// # Arguments
+/// * `a`, `b` - Factors of the numerator
+/// * `c` - Denominator, must not be zero
+pub fn mul_div_ceil(a: u128, b: u128, c: u128) -> Result<u128> {
+    amm_quote::math::mul_div_ceil(a, b, c).map_err(|error| AmmError::from(error).into())
+}
+
+/// Integer square root of the 256-bit number (`high`, `low`), rounded down
+///
+/// # Arguments
+/// * `high`, `low` - Upper and lower 128-bit halves of the number
+pub fn sqrt_u256(high: u128, low: u128) -> Result<u128> {
+    amm_quote::math::sqrt_u256(high, low).map_err(|error| AmmError::from(error).into())
+}
@@ -5,17 +5,158 @@ import {
   createMint,
   getOrCreateAssociatedTokenAccount,
   mintTo,
+  transfer,
   getAssociatedTokenAddress,
+  getAssociatedTokenAddressSync,
+  getAccount,
+  getMintLen,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  createInitializeNonTransferableMintInstruction,
+  ExtensionType,
+  TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
 } from "@solana/spl-token";
+import { assert } from "chai";
 
-describe("amm initialize", () => {
-  const provider = anchor.AnchorProvider.env();
-  anchor.setProvider(provider);
+const provider = anchor.AnchorProvider.env();
+anchor.setProvider(provider);
+
+const program = anchor.workspace.amm as Program<Amm>;
+const connection = provider.connection;
+const admin = provider.wallet;
+
+const tokenPrograms = {
+  tokenProgram: TOKEN_PROGRAM_ID,
+  tokenProgramX: TOKEN_PROGRAM_ID,
+  tokenProgramY: TOKEN_PROGRAM_ID,
+};
+
+type PublicKey = anchor.web3.PublicKey;
+type Curve = { constantProduct: {} } | { stableSwap: {} };
+
+const configFor = (seed: number | anchor.BN) =>
+  anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("config"), new anchor.BN(seed).toArrayLike(Buffer, "le", 8)],
+    program.programId
+  )[0];
+
+// PDAs seeded by a label and the pool config: "lp", "lock", "oracle", "pool", "farm"
+const poolPda = (label: string, config: PublicKey) =>
+  anchor.web3.PublicKey.findProgramAddressSync([Buffer.from(label), config.toBuffer()], program.programId)[0];
+
+const createTestMint = () => createMint(connection, admin.payer, admin.publicKey, null, 6);
+
+// Associated token account of `owner`, topped up with `amount` freshly minted tokens
+const fundedAta = async (mint: PublicKey, amount: number, owner = admin.publicKey) => {
+  const ata = await getOrCreateAssociatedTokenAccount(connection, admin.payer, mint, owner);
+  await mintTo(connection, admin.payer, mint, ata.address, admin.payer, amount);
+  return ata.address;
+};
+
+const balance = async (ata: PublicKey, tokenProgram = TOKEN_PROGRAM_ID) =>
+  Number((await getAccount(connection, ata, undefined, tokenProgram)).amount);
+
+const now = async () => new anchor.BN(await connection.getBlockTime(await connection.getSlot()));
+
+// Multi-instruction transactions only report the program error in the logs
+const expectError = async (promise: Promise<unknown>, code: string) => {
+  try {
+    await promise;
+  } catch (err) {
+    assert.include(`${err}\n${(err.logs ?? []).join("\n")}`, code);
+    return;
+  }
+  assert.fail(`expected ${code}`);
+};
+
+// Data of the named event emitted by a confirmed transaction
+const eventOf = async (signature: string, name: string) => {
+  const tx = await connection.getTransaction(signature, {
+    commitment: "confirmed",
+    maxSupportedTransactionVersion: 0,
+  });
+  const parser = new anchor.EventParser(program.programId, program.coder);
+  const event = [...parser.parseLogs(tx.meta.logMessages)].find((e) => e.name.toLowerCase() === name.toLowerCase());
+  assert.exists(event, `${name} event`);
+  return event.data;
+};
 
-  const program = anchor.workspace.amm as Program<Amm>;
-  const connection = provider.connection;
-  const admin = provider.wallet;
+type PoolOptions = {
+  seed: number;
+  fee?: number;
+  curve?: Curve;
+  amp?: number;
+  authority?: PublicKey | null;
+  // Reuse existing mints instead of creating a new pair
+  mints?: [PublicKey, PublicKey];
+  // Minted to the admin in each token before the pool is created
+  balance?: number;
+  // LP amount and token maximums of a first deposit by the admin, none if 0
+  deposit?: number;
+};
 
+type Pool = {
+  mintX: PublicKey;
+  mintY: PublicKey;
+  config: PublicKey;
+  mintLp: PublicKey;
+  vaultX: PublicKey;
+  vaultY: PublicKey;
+  userAtaX: PublicKey;
+  userAtaY: PublicKey;
+  userAtaLp: PublicKey;
+  // Admin as the user of deposit, withdraw and swaps
+  accounts: Record<string, PublicKey>;
+  // Admin as the authority of the pool's admin instructions
+  adminAccounts: Record<string, PublicKey | null>;
+};
+
+// Fresh SPL mints funded for the admin and a pool over them. Call `initializePool` to create
+// the pool later, e.g. to inspect the initialize transaction.
+const preparePool = async ({ seed, mints, balance: amount = 10_000_000 }: PoolOptions): Promise<Pool> => {
+  const [mintX, mintY] = mints ?? [await createTestMint(), await createTestMint()];
+  const config = configFor(seed);
+  const mintLp = poolPda("lp", config);
+  return {
+    mintX,
+    mintY,
+    config,
+    mintLp,
+    vaultX: getAssociatedTokenAddressSync(mintX, config, true),
+    vaultY: getAssociatedTokenAddressSync(mintY, config, true),
+    userAtaX: await fundedAta(mintX, amount),
+    userAtaY: await fundedAta(mintY, amount),
+    userAtaLp: getAssociatedTokenAddressSync(mintLp, admin.publicKey),
+    accounts: { user: admin.publicKey, mintX, mintY, config, ...tokenPrograms },
+    adminAccounts: { authority: admin.publicKey, config, factory: null },
+  };
+};
+
+const initializePool = (
+  pool: Pool,
+  { seed, fee = 30, curve = { constantProduct: {} }, amp = 0, authority = admin.publicKey }: PoolOptions
+) =>
+  program.methods
+    .initialize(new anchor.BN(seed), fee, authority, curve, new anchor.BN(amp))
+    .accountsPartial({ admin: admin.publicKey, mintX: pool.mintX, mintY: pool.mintY, config: pool.config, ...tokenPrograms });
+
+const deposit = (pool: Pool, lp: number, maxX = lp, maxY = lp) =>
+  program.methods
+    .deposit(new anchor.BN(lp), new anchor.BN(maxX), new anchor.BN(maxY), null)
+    .accountsPartial(pool.accounts);
+
+// Creates a pool with `options`, by default a 0.3% constant-product pool administered by the admin
+const setupPool = async (options: PoolOptions): Promise<Pool> => {
+  const pool = await preparePool(options);
+  await initializePool(pool, options).rpc();
+  if (options.deposit) {
+    await deposit(pool, options.deposit).rpc();
+  }
+  return pool;
+};
+
+describe("amm initialize", () => {
   let mintX: anchor.web3.PublicKey;
   let mintY: anchor.web3.PublicKey;
   let lpMint: anchor.web3.PublicKey;
@@ -49,8 +190,8 @@ describe("amm initialize", () => {
     vaultY = await getAssociatedTokenAddress(mintY, configPda, true);
 
     const tx = await program.methods
-      .initialize(seed, fee, null)
-      .accounts({
+      .initialize(seed, fee, null, { constantProduct: {} }, new anchor.BN(0))
+      .accountsPartial({
         admin: admin.publicKey,
         mintX,
         mintY,
@@ -58,9 +199,9 @@ describe("amm initialize", () => {
         mintLp: lpMint,
         vaultX,
         vaultY,
-        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
-        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
-        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenProgramX: TOKEN_PROGRAM_ID,
+        tokenProgramY: TOKEN_PROGRAM_ID,
       })
       .rpc();
     console.log(`https://explorer.solana.com/tx/${tx}?cluster=devnet`);
@@ -86,17 +227,17 @@ describe("amm initialize", () => {
     const maxY = new anchor.BN(500_000);
 
     const tx = await program.methods
-      .deposit(depositAmount, maxX, maxY)
-      .accounts({
+      .deposit(depositAmount, maxX, maxY, null)
+      .accountsPartial({
         user: admin.publicKey,
-        userTokenX: userAtaX,
-        userTokenY: userAtaY,
-        userLp: userLpAta,
+        mintX,
+        mintY,
         config: configPda,
-        vaultX,
-        vaultY,
-        lpMint,
-        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        userAtaX,
+        userAtaY,
+        userAtaLp: userLpAta,
+        tokenProgramX: TOKEN_PROGRAM_ID,
+        tokenProgramY: TOKEN_PROGRAM_ID,
       })
       .rpc();
 
@@ -105,24 +246,23 @@ describe("amm initialize", () => {
   });
 
   it("Withdraws liquidity from the pool", async () => {
-    const withdrawAmount = new anchor.BN(500_000);
+    const withdrawAmount = new anchor.BN(250_000);
     const minX = new anchor.BN(100_000);
     const minY = new anchor.BN(100_000);
 
     const tx = await program.methods
-      .withdraw(withdrawAmount, minX, minY)
-      .accounts({
+      .withdraw(withdrawAmount, minX, minY, null)
+      .accountsPartial({
         user: admin.publicKey,
-        userTokenX: userAtaX,
-        userTokenY: userAtaY,
-        userLp: userLpAta,
+        mintX,
+        mintY,
         config: configPda,
-        vaultX,
-        vaultY,
-        lpMint,
-        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        userAtaX,
+        userAtaY,
+        userAtaLp: userLpAta,
+        tokenProgramX: TOKEN_PROGRAM_ID,
+        tokenProgramY: TOKEN_PROGRAM_ID,
       })
-      .signers([admin.payer])
       .rpc();
 
     console.log(`https://explorer.solana.com/tx/${tx}?cluster=devnet`);
@@ -134,19 +274,1380 @@ describe("amm initialize", () => {
     const minOut = new anchor.BN(50_000);
 
     const tx = await program.methods
-      .swap(true, amountIn, minOut)
-      .accounts({
+      .swap(true, amountIn, minOut, null)
+      .accountsPartial({
         user: admin.publicKey,
-        userTokenIn: userAtaX,
-        userTokenOut: userAtaY,
+        mintX,
+        mintY,
         config: configPda,
-        vaultIn: vaultX,
-        vaultOut: vaultY,
-        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramX: TOKEN_PROGRAM_ID,
+        tokenProgramY: TOKEN_PROGRAM_ID,
       })
       .rpc();
 
     console.log(`https://explorer.solana.com/tx/${tx}?cluster=devnet`);
     console.log("✅ Swapped X for Y");
   });
-});
\ No newline at end of file
+});
+
+
+describe("amm admin", () => {
+  const newAuthority = anchor.web3.Keypair.generate();
+  const outsider = anchor.web3.Keypair.generate();
+  let pool: Pool;
+
+  before(async () => {
+    pool = await setupPool({ seed: 7 });
+  });
+
+  it("Rejects a pool created with a fee above the maximum", async () => {
+    const mints: [PublicKey, PublicKey] = [pool.mintX, pool.mintY];
+    const options = { seed: 8, fee: 1_001, mints };
+    await expectError(initializePool(await preparePool(options), options).rpc(), "InvalidFee");
+  });
+
+  it("Updates the fee within bounds", async () => {
+    await program.methods.updateFee(50).accounts(pool.adminAccounts).rpc();
+    assert.equal((await program.account.config.fetch(pool.config)).fee, 50);
+
+    await expectError(program.methods.updateFee(1_001).accounts(pool.adminAccounts).rpc(), "InvalidFee");
+  });
+
+  it("Locks the pool against swaps and unlocks it", async () => {
+    await program.methods.lock().accounts(pool.adminAccounts).rpc();
+    assert.isTrue((await program.account.config.fetch(pool.config)).locked);
+
+    await expectError(
+      program.methods.swap(true, new anchor.BN(1_000), new anchor.BN(1), null).accountsPartial(pool.accounts).rpc(),
+      "PoolLocked"
+    );
+
+    await program.methods.unlock().accounts(pool.adminAccounts).rpc();
+    assert.isFalse((await program.account.config.fetch(pool.config)).locked);
+  });
+
+  it("Rejects admin calls from anyone but the authority", async () => {
+    await expectError(
+      program.methods
+        .lock()
+        .accounts({ authority: outsider.publicKey, config: pool.config })
+        .signers([outsider])
+        .rpc(),
+      "InvalidAuthority"
+    );
+  });
+
+  it("Transfers and renounces the authority", async () => {
+    await program.methods.setAuthority(newAuthority.publicKey).accounts(pool.adminAccounts).rpc();
+    assert.equal(
+      (await program.account.config.fetch(pool.config)).authority.toBase58(),
+      newAuthority.publicKey.toBase58()
+    );
+
+    await expectError(program.methods.lock().accounts(pool.adminAccounts).rpc(), "InvalidAuthority");
+
+    await program.methods
+      .renounceAuthority()
+      .accounts({ authority: newAuthority.publicKey, config: pool.config })
+      .signers([newAuthority])
+      .rpc();
+    assert.isNull((await program.account.config.fetch(pool.config)).authority);
+
+    await expectError(
+      program.methods
+        .unlock()
+        .accounts({ authority: newAuthority.publicKey, config: pool.config })
+        .signers([newAuthority])
+        .rpc(),
+      "NoAuthoritySet"
+    );
+  });
+});
+
+describe("amm token-2022", () => {
+  const seed = new anchor.BN(2022);
+  const feeBps = 100; // 1% transfer fee on token X
+  let mintX: PublicKey;
+  let mintY: PublicKey;
+  let configPda: PublicKey;
+  let vaultX: PublicKey;
+  let userAtaX: PublicKey;
+  let userAtaY: PublicKey;
+
+  const token2022Programs = {
+    tokenProgram: TOKEN_PROGRAM_ID,
+    tokenProgramX: TOKEN_2022_PROGRAM_ID,
+    tokenProgramY: TOKEN_PROGRAM_ID,
+  };
+
+  // Creates a Token-2022 mint with either a transfer fee or the non-transferable extension
+  const createToken2022Mint = async (extension: ExtensionType) => {
+    const mint = anchor.web3.Keypair.generate();
+    const space = getMintLen([extension]);
+    const lamports = await connection.getMinimumBalanceForRentExemption(space);
+    const tx = new anchor.web3.Transaction().add(
+      anchor.web3.SystemProgram.createAccount({
+        fromPubkey: admin.publicKey,
+        newAccountPubkey: mint.publicKey,
+        space,
+        lamports,
+        programId: TOKEN_2022_PROGRAM_ID,
+      }),
+      extension === ExtensionType.TransferFeeConfig
+        ? createInitializeTransferFeeConfigInstruction(
+            mint.publicKey,
+            admin.publicKey,
+            admin.publicKey,
+            feeBps,
+            BigInt(1_000_000_000),
+            TOKEN_2022_PROGRAM_ID
+          )
+        : createInitializeNonTransferableMintInstruction(mint.publicKey, TOKEN_2022_PROGRAM_ID),
+      createInitializeMintInstruction(mint.publicKey, 6, admin.publicKey, null, TOKEN_2022_PROGRAM_ID)
+    );
+    await provider.sendAndConfirm(tx, [mint]);
+    return mint.publicKey;
+  };
+
+  before(async () => {
+    mintX = await createToken2022Mint(ExtensionType.TransferFeeConfig);
+    mintY = await createTestMint();
+    configPda = configFor(seed);
+    vaultX = await getAssociatedTokenAddress(mintX, configPda, true, TOKEN_2022_PROGRAM_ID);
+
+    userAtaX = (
+      await getOrCreateAssociatedTokenAccount(
+        connection, admin.payer, mintX, admin.publicKey, false, undefined, undefined, TOKEN_2022_PROGRAM_ID
+      )
+    ).address;
+    await mintTo(connection, admin.payer, mintX, userAtaX, admin.payer, 10_000_000, [], undefined, TOKEN_2022_PROGRAM_ID);
+    userAtaY = await fundedAta(mintY, 10_000_000);
+  });
+
+  it("Initializes a pool pairing a Token-2022 mint with an SPL mint", async () => {
+    await program.methods
+      .initialize(seed, 30, null, { constantProduct: {} }, new anchor.BN(0))
+      .accountsPartial({ admin: admin.publicKey, mintX, mintY, config: configPda, ...token2022Programs })
+      .rpc();
+
+    assert.equal(await balance(vaultX, TOKEN_2022_PROGRAM_ID), 0);
+  });
+
+  it("Rejects mints with unsupported extensions", async () => {
+    const nonTransferableMint = await createToken2022Mint(ExtensionType.NonTransferable);
+    const badSeed = new anchor.BN(2023);
+    await expectError(
+      program.methods
+        .initialize(badSeed, 30, null, { constantProduct: {} }, new anchor.BN(0))
+        .accountsPartial({
+          admin: admin.publicKey,
+          mintX: nonTransferableMint,
+          mintY,
+          config: configFor(badSeed),
+          ...token2022Programs,
+        })
+        .rpc(),
+      "UnsupportedMintExtension"
+    );
+  });
+
+  it("Deposits with the vault credited net of the transfer fee", async () => {
+    await program.methods
+      .deposit(new anchor.BN(1_000_000), new anchor.BN(1_000_000), new anchor.BN(1_000_000), null)
+      .accountsPartial({ user: admin.publicKey, mintX, mintY, config: configPda, userAtaX, userAtaY, ...token2022Programs })
+      .rpc();
+
+    // 1% of the 1_000_000 X sent is withheld in transit
+    assert.equal(await balance(vaultX, TOKEN_2022_PROGRAM_ID), 990_000);
+  });
+
+  it("Swaps using the amount that reached the vault", async () => {
+    const vaultBefore = await balance(vaultX, TOKEN_2022_PROGRAM_ID);
+    const userYBefore = await balance(userAtaY);
+
+    await program.methods
+      .swap(true, new anchor.BN(100_000), new anchor.BN(1), null)
+      .accountsPartial({ user: admin.publicKey, mintX, mintY, config: configPda, ...token2022Programs })
+      .rpc();
+
+    assert.equal((await balance(vaultX, TOKEN_2022_PROGRAM_ID)) - vaultBefore, 99_000);
+    // Output priced on 99_000 X in against 990_000 X / 1_000_000 Y, less the 0.3% pool fee
+    const out = (await balance(userAtaY)) - userYBefore;
+    assert.isAbove(out, 0);
+    assert.isBelow(out, 90_910);
+  });
+
+  it("Applies withdrawal minimums to the amounts received after fees", async () => {
+    const lp = new anchor.BN(100_000);
+    const withdraw = (minX: number) =>
+      program.methods
+        .withdraw(lp, new anchor.BN(minX), new anchor.BN(1), null)
+        .accountsPartial({ user: admin.publicKey, mintX, mintY, config: configPda, userAtaX, userAtaY, ...token2022Programs })
+        .rpc();
+
+    // The vault sends just over 10% of its X, but the user only receives 99% of that
+    const vaultShare = Math.floor((await balance(vaultX, TOKEN_2022_PROGRAM_ID)) / 10);
+    await expectError(withdraw(vaultShare), "SlippageExceeded");
+
+    const userXBefore = await balance(userAtaX, TOKEN_2022_PROGRAM_ID);
+    await withdraw(1);
+    assert.isAbove((await balance(userAtaX, TOKEN_2022_PROGRAM_ID)) - userXBefore, 0);
+  });
+});
+
+describe("amm protocol fees", () => {
+  const treasury = anchor.web3.Keypair.generate();
+  let pool: Pool;
+
+  before(async () => {
+    pool = await setupPool({ seed: 36, deposit: 1_000_000 });
+  });
+
+  it("Bounds the protocol share of the swap fee", async () => {
+    await expectError(program.methods.setProtocolFee(5_001).accounts(pool.adminAccounts).rpc(), "InvalidProtocolFee");
+
+    await program.methods.setProtocolFee(5_000).accounts(pool.adminAccounts).rpc();
+    assert.equal((await program.account.config.fetch(pool.config)).protocolFee, 5_000);
+  });
+
+  it("Accrues the protocol share outside the pool reserves", async () => {
+    const signature = await program.methods
+      .swap(true, new anchor.BN(100_000), new anchor.BN(1), null)
+      .accountsPartial(pool.accounts)
+      .rpc({ commitment: "confirmed" });
+
+    const config = await program.account.config.fetch(pool.config);
+    // Half of the 0.3% fee on 100_000 X
+    assert.equal(config.protocolFeesX.toNumber(), 150);
+    assert.equal(config.protocolFeesY.toNumber(), 0);
+
+    // The event reports the whole input, and the share kept out of the reserves
+    const event = await eventOf(signature, "Swap");
+    assert.equal(event.amountIn.toNumber(), 100_000);
+    assert.equal(event.fee.toNumber(), 300);
+    assert.equal(event.protocolFee.toNumber(), 150);
+    assert.equal(event.reserveX.toNumber(), 1_000_000 + 100_000 - 150);
+  });
+
+  it("Only pays out to the configured treasury", async () => {
+    const { mintX, mintY, config } = pool;
+    const collect = (to: PublicKey) =>
+      program.methods
+        .collectProtocolFees()
+        .accountsPartial({ collector: admin.publicKey, treasury: to, mintX, mintY, config, ...tokenPrograms })
+        .rpc();
+
+    await expectError(collect(treasury.publicKey), "NoTreasurySet");
+
+    await program.methods.setTreasury(treasury.publicKey).accounts(pool.adminAccounts).rpc();
+    await expectError(collect(admin.publicKey), "InvalidTreasury");
+
+    await collect(treasury.publicKey);
+    assert.equal(await balance(getAssociatedTokenAddressSync(mintX, treasury.publicKey)), 150);
+
+    assert.equal((await program.account.config.fetch(config)).protocolFeesX.toNumber(), 0);
+    await expectError(collect(treasury.publicKey), "ZeroBalance");
+  });
+});
+
+describe("amm exact-output swaps", () => {
+  let pool: Pool;
+
+  const swapExactOut = (amountOut: number, maxAmountIn: number) =>
+    program.methods
+      .swapExactOut(true, new anchor.BN(amountOut), new anchor.BN(maxAmountIn), null)
+      .accountsPartial(pool.accounts)
+      .rpc();
+
+  before(async () => {
+    pool = await setupPool({ seed: 37, deposit: 1_000_000 });
+  });
+
+  // 1_000_000 X / 1_000_000 Y at 0.3%: taking 50_000 Y needs
+  // ceil(1_000_000 * 50_000 / 950_000) = 52_632 X after the fee, ceil(52_632 / 0.997) = 52_791 X before it
+  it("Rejects an input cap below the required input", async () => {
+    await expectError(swapExactOut(50_000, 52_790), "SlippageExceeded");
+  });
+
+  it("Delivers exactly the requested output", async () => {
+    const xBefore = await balance(pool.userAtaX);
+    const yBefore = await balance(pool.userAtaY);
+
+    await swapExactOut(50_000, 52_791);
+
+    assert.equal(xBefore - (await balance(pool.userAtaX)), 52_791);
+    assert.equal((await balance(pool.userAtaY)) - yBefore, 50_000);
+  });
+
+  it("Never lets the pool be drained of the output token", async () => {
+    await expectError(swapExactOut(950_000, 1_000_000_000), "InsufficientBalance");
+  });
+});
+
+describe("amm deadlines", () => {
+  let accounts: Record<string, PublicKey>;
+
+  before(async () => {
+    ({ accounts } = await setupPool({ seed: 38 }));
+  });
+
+  it("Accepts liquidity changes and swaps before their deadline", async () => {
+    const deadline = (await now()).addn(60);
+    const max = new anchor.BN(1_000_000);
+
+    await program.methods.deposit(max, max, max, deadline).accountsPartial(accounts).rpc();
+    await program.methods
+      .swap(true, new anchor.BN(10_000), new anchor.BN(1), deadline)
+      .accountsPartial(accounts)
+      .rpc();
+    await program.methods
+      .withdraw(new anchor.BN(100_000), new anchor.BN(1), new anchor.BN(1), deadline)
+      .accountsPartial(accounts)
+      .rpc();
+  });
+
+  it("Rejects transactions that land after their deadline", async () => {
+    const expired = (await now()).subn(60);
+
+    await expectError(
+      program.methods
+        .deposit(new anchor.BN(1_000), new anchor.BN(1_000_000), new anchor.BN(1_000_000), expired)
+        .accountsPartial(accounts)
+        .rpc(),
+      "OfferExpired"
+    );
+    await expectError(
+      program.methods
+        .withdraw(new anchor.BN(1_000), new anchor.BN(1), new anchor.BN(1), expired)
+        .accountsPartial(accounts)
+        .rpc(),
+      "OfferExpired"
+    );
+    await expectError(
+      program.methods
+        .swap(true, new anchor.BN(10_000), new anchor.BN(1), expired)
+        .accountsPartial(accounts)
+        .rpc(),
+      "OfferExpired"
+    );
+    await expectError(
+      program.methods
+        .swapExactOut(true, new anchor.BN(1_000), new anchor.BN(1_000_000), expired)
+        .accountsPartial(accounts)
+        .rpc(),
+      "OfferExpired"
+    );
+  });
+});
+
+describe("amm oracle", () => {
+  const Q64 = new anchor.BN(1).shln(64);
+  let pool: Pool;
+  let oraclePda: PublicKey;
+
+  before(async () => {
+    pool = await setupPool({ seed: 39 });
+    oraclePda = poolPda("oracle", pool.config);
+  });
+
+  it("Records an observation when the pool is created", async () => {
+    const oracle = await program.account.oracle.fetch(oraclePda);
+    assert.equal(oracle.observationCount, 1);
+    assert.isTrue(oracle.priceXCumulative.isZero());
+  });
+
+  it("Accumulates the pre-trade price over time", async () => {
+    await deposit(pool, 1_000_000).rpc();
+
+    // Let the clock move so the swap accrues the 1:1 price
+    await new Promise((resolve) => setTimeout(resolve, 2_000));
+    await program.methods.swap(true, new anchor.BN(100_000), new anchor.BN(1), null).accountsPartial(pool.accounts).rpc();
+
+    const oracle = await program.account.oracle.fetch(oraclePda);
+    const newest = oracle.observations[(oracle.observationIndex + 31) % 32];
+    const previous = oracle.observations[(oracle.observationIndex + 30) % 32];
+    const elapsed = newest.timestamp.sub(previous.timestamp);
+    assert.isTrue(elapsed.gtn(0));
+
+    // Equal reserves until the swap: both TWAPs are exactly 1.0 in Q64.64
+    assert.isTrue(newest.priceXCumulative.sub(previous.priceXCumulative).div(elapsed).eq(Q64));
+    assert.isTrue(newest.priceYCumulative.sub(previous.priceYCumulative).div(elapsed).eq(Q64));
+  });
+});
+
+describe("amm stableswap", () => {
+  const options = { seed: 40, curve: { stableSwap: {} }, amp: 100 };
+  let pool: Pool;
+
+  before(async () => {
+    pool = await preparePool(options);
+  });
+
+  it("Rejects an amplification coefficient out of range", async () => {
+    const bad = { ...options, seed: 41, amp: 0, mints: [pool.mintX, pool.mintY] as [PublicKey, PublicKey] };
+    await expectError(initializePool(await preparePool(bad), bad).rpc(), "InvalidAmp");
+  });
+
+  it("Trades a balanced pegged pair with far less slippage than x * y = k", async () => {
+    await initializePool(pool, options).rpc();
+    await deposit(pool, 1_000_000).rpc();
+
+    const yBefore = await balance(pool.userAtaY);
+    await program.methods.swap(true, new anchor.BN(100_000), new anchor.BN(1), null).accountsPartial(pool.accounts).rpc();
+    const out = (await balance(pool.userAtaY)) - yBefore;
+
+    // x * y = k would pay 1_000_000 * 99_700 / 1_099_700 = 90_661 Y
+    assert.equal(out, 99_648);
+  });
+
+  it("Quotes exact-output swaps on the StableSwap invariant", async () => {
+    const xBefore = await balance(pool.userAtaX);
+    await program.methods
+      .swapExactOut(false, new anchor.BN(50_000), new anchor.BN(60_000), null)
+      .accountsPartial(pool.accounts)
+      .rpc();
+    assert.equal((await balance(pool.userAtaX)) - xBefore, 50_000);
+  });
+
+  it("Ramps the amplification coefficient within bounds", async () => {
+    const twoDays = (await now()).addn(2 * 86_400);
+    const ramp = (target: number, end: anchor.BN) =>
+      program.methods.rampAmp(new anchor.BN(target), end).accounts(pool.adminAccounts).rpc();
+
+    await expectError(ramp(1_001, twoDays), "InvalidRamp");
+    await expectError(ramp(500, (await now()).addn(100)), "InvalidRamp");
+
+    await ramp(500, twoDays);
+    let config = await program.account.config.fetch(pool.config);
+    assert.equal(config.ampTarget.toNumber(), 500);
+    assert.isTrue(config.ampRampEnd.eq(twoDays));
+
+    await program.methods.stopRampAmp().accounts(pool.adminAccounts).rpc();
+    config = await program.account.config.fetch(pool.config);
+    assert.isTrue(config.ampTarget.eq(config.ampInitial));
+    assert.isBelow(config.ampTarget.toNumber(), 500);
+  });
+});
+
+describe("amm concentrated liquidity", () => {
+  const seed = new anchor.BN(41);
+  // Price 1.0: sqrt(1) in Q64.64
+  const sqrtPrice = new anchor.BN(1).shln(64);
+  const liquidity = new anchor.BN(1_000_000_000);
+  const max = new anchor.BN(100_000_000);
+  let configPda: PublicKey;
+  let poolPdaKey: PublicKey;
+  let userAtaX: PublicKey;
+  let userAtaY: PublicKey;
+  let accounts: Record<string, PublicKey>;
+
+  const positionFor = (lower: number, upper: number) => {
+    const ticks = Buffer.alloc(8);
+    ticks.writeInt32LE(lower, 0);
+    ticks.writeInt32LE(upper, 4);
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("position"), configPda.toBuffer(), admin.publicKey.toBuffer(), ticks.subarray(0, 4), ticks.subarray(4)],
+      program.programId
+    )[0];
+  };
+
+  before(async () => {
+    const mintX = await createTestMint();
+    const mintY = await createTestMint();
+    configPda = configFor(seed);
+    poolPdaKey = poolPda("pool", configPda);
+    accounts = { mintX, mintY, config: configPda, tokenProgramX: TOKEN_PROGRAM_ID, tokenProgramY: TOKEN_PROGRAM_ID };
+
+    userAtaX = await fundedAta(mintX, 100_000_000);
+    userAtaY = await fundedAta(mintY, 100_000_000);
+
+    await program.methods
+      .initializeConcentrated(seed, 30, admin.publicKey, 10, sqrtPrice)
+      .accountsPartial({ admin: admin.publicKey, ...accounts })
+      .rpc();
+  });
+
+  it("Starts the pool at the given price", async () => {
+    const pool = await program.account.concentratedPool.fetch(poolPdaKey);
+    assert.isTrue(pool.sqrtPrice.eq(sqrtPrice));
+    assert.equal(pool.tickCurrent, 0);
+    assert.equal(pool.tickCount, 0);
+  });
+
+  it("Rejects ranges that are not aligned to the tick spacing", async () => {
+    await expectError(
+      program.methods
+        .openPosition(-105, 100)
+        .accountsPartial({ owner: admin.publicKey, config: configPda, position: positionFor(-105, 100) })
+        .rpc(),
+      "InvalidTickRange"
+    );
+  });
+
+  // Each position takes up to two of the pool's 64 tick slots
+  it("Rejects positions holding less than the minimum liquidity", async () => {
+    const position = positionFor(-20, 20);
+    await program.methods
+      .openPosition(-20, 20)
+      .accountsPartial({ owner: admin.publicKey, config: configPda, position })
+      .rpc();
+
+    await expectError(
+      program.methods
+        .increaseLiquidity(new anchor.BN(1), max, max, null)
+        .accountsPartial({ owner: admin.publicKey, ...accounts, position })
+        .rpc(),
+      "PositionTooSmall"
+    );
+    await program.methods.closePosition().accountsPartial({ owner: admin.publicKey, position }).rpc();
+  });
+
+  it("Takes both tokens for a range around the price and only X above it", async () => {
+    const inRange = positionFor(-100, 100);
+    const above = positionFor(200, 400);
+    await program.methods
+      .openPosition(-100, 100)
+      .accountsPartial({ owner: admin.publicKey, config: configPda, position: inRange })
+      .rpc();
+    await program.methods
+      .openPosition(200, 400)
+      .accountsPartial({ owner: admin.publicKey, config: configPda, position: above })
+      .rpc();
+
+    const [x0, y0] = [await balance(userAtaX), await balance(userAtaY)];
+    const signature = await program.methods
+      .increaseLiquidity(liquidity, max, max, null)
+      .accountsPartial({ owner: admin.publicKey, ...accounts, position: inRange })
+      .rpc({ commitment: "confirmed" });
+    assert.equal(x0 - (await balance(userAtaX)), 4_987_273);
+    assert.equal(y0 - (await balance(userAtaY)), 4_987_273);
+
+    const event = await eventOf(signature, "PositionIncreased");
+    assert.isTrue(event.position.equals(inRange) && event.owner.equals(admin.publicKey));
+    assert.isTrue(event.liquidity.eq(liquidity) && event.positionLiquidity.eq(liquidity));
+    assert.equal(event.amountX.toNumber(), 4_987_273);
+    assert.equal(event.amountY.toNumber(), 4_987_273);
+
+    await program.methods
+      .increaseLiquidity(liquidity, max, max, null)
+      .accountsPartial({ owner: admin.publicKey, ...accounts, position: above })
+      .rpc();
+    assert.equal(x0 - (await balance(userAtaX)), 4_987_273 + 9_850_676);
+    assert.equal(y0 - (await balance(userAtaY)), 4_987_273);
+
+    const pool = await program.account.concentratedPool.fetch(poolPdaKey);
+    assert.equal(pool.tickCount, 4);
+    assert.isTrue(pool.liquidity.eq(liquidity));
+  });
+
+  it("Trades against the concentrated liquidity and pays the fee to the position", async () => {
+    const y0 = await balance(userAtaY);
+    const signature = await program.methods
+      .swapConcentrated(true, new anchor.BN(1_000_000), new anchor.BN(1), null)
+      .accountsPartial({ user: admin.publicKey, ...accounts })
+      .rpc({ commitment: "confirmed" });
+    // A full-range pool holding the same 4_987_273 of each token would pay out ~829_000 Y
+    assert.equal((await balance(userAtaY)) - y0, 996_006);
+
+    const event = await eventOf(signature, "SwapConcentrated");
+    const pool = await program.account.concentratedPool.fetch(poolPdaKey);
+    assert.isTrue(event.isX);
+    assert.equal(event.amountIn.toNumber(), 1_000_000);
+    assert.equal(event.amountOut.toNumber(), 996_006);
+    assert.isAtLeast(event.fee.toNumber(), 3_000);
+    assert.isTrue(event.protocolFee.isZero());
+    assert.isTrue(event.sqrtPrice.eq(pool.sqrtPrice) && event.liquidity.eq(pool.liquidity));
+    assert.equal(event.tickCurrent, pool.tickCurrent);
+
+    const x0 = await balance(userAtaX);
+    await program.methods
+      .collectFees()
+      .accountsPartial({ owner: admin.publicKey, ...accounts, position: positionFor(-100, 100) })
+      .rpc();
+    assert.equal((await balance(userAtaX)) - x0, 2_999);
+  });
+
+  it("Crosses ticks into the next range as the price moves", async () => {
+    await program.methods
+      .swapConcentrated(false, new anchor.BN(7_000_000), new anchor.BN(1), null)
+      .accountsPartial({ user: admin.publicKey, ...accounts })
+      .rpc();
+
+    const pool = await program.account.concentratedPool.fetch(poolPdaKey);
+    assert.isAtLeast(pool.tickCurrent, 200);
+    assert.isBelow(pool.tickCurrent, 400);
+    assert.isTrue(pool.liquidity.eq(liquidity));
+  });
+
+  it("Fails instead of partially filling when liquidity runs out", async () => {
+    await expectError(
+      program.methods
+        .swapConcentrated(false, new anchor.BN(80_000_000), new anchor.BN(1), null)
+        .accountsPartial({ user: admin.publicKey, ...accounts })
+        .rpc(),
+      "NoLiquidityInPool"
+    );
+  });
+
+  it("Only closes a position once its liquidity and fees are gone", async () => {
+    const position = positionFor(-100, 100);
+    // Withdrawing all but a dust amount would leave the position below the minimum
+    await expectError(
+      program.methods
+        .decreaseLiquidity(liquidity.subn(1), new anchor.BN(0), new anchor.BN(0), null)
+        .accountsPartial({ owner: admin.publicKey, ...accounts, position })
+        .rpc(),
+      "PositionTooSmall"
+    );
+    const signature = await program.methods
+      .decreaseLiquidity(liquidity, new anchor.BN(0), new anchor.BN(0), null)
+      .accountsPartial({ owner: admin.publicKey, ...accounts, position })
+      .rpc({ commitment: "confirmed" });
+
+    const event = await eventOf(signature, "PositionDecreased");
+    assert.isTrue(event.position.equals(position));
+    assert.isTrue(event.liquidity.eq(liquidity) && event.positionLiquidity.isZero());
+    assert.isTrue(event.amountX.gtn(0) || event.amountY.gtn(0));
+
+    await expectError(
+      program.methods.closePosition().accountsPartial({ owner: admin.publicKey, position }).rpc(),
+      "PositionNotEmpty"
+    );
+
+    await program.methods
+      .collectFees()
+      .accountsPartial({ owner: admin.publicKey, ...accounts, position })
+      .rpc();
+    await program.methods.closePosition().accountsPartial({ owner: admin.publicKey, position }).rpc();
+    assert.isNull(await connection.getAccountInfo(position));
+
+    // Only the two ticks of the remaining position are left
+    const pool = await program.account.concentratedPool.fetch(poolPdaKey);
+    assert.equal(pool.tickCount, 2);
+  });
+});
+
+describe("amm routed swaps", () => {
+  const routePrograms = {
+    tokenProgramIn: TOKEN_PROGRAM_ID,
+    tokenProgramOut: TOKEN_PROGRAM_ID,
+  };
+  let mintA: PublicKey;
+  let mintB: PublicKey;
+  let mintC: PublicKey;
+  let ataB: PublicKey;
+  let ataC: PublicKey;
+  // Pools used by the route, and identical twins swapped one hop at a time
+  let routeAB: Pool;
+  let routeBC: Pool;
+  let twinAB: Pool;
+  let twinBC: Pool;
+
+  // The seven remaining accounts of one hop, in the order route_swap reads them
+  const hop = (pool: Pool, mintIn: PublicKey, mintOut: PublicKey) => [
+    { pubkey: pool.config, isSigner: false, isWritable: true },
+    { pubkey: poolPda("oracle", pool.config), isSigner: false, isWritable: true },
+    { pubkey: pool.mintLp, isSigner: false, isWritable: false },
+    { pubkey: getAssociatedTokenAddressSync(mintIn, pool.config, true), isSigner: false, isWritable: true },
+    { pubkey: getAssociatedTokenAddressSync(mintOut, pool.config, true), isSigner: false, isWritable: true },
+    { pubkey: mintOut, isSigner: false, isWritable: false },
+    { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+  ];
+
+  const routeSwap = (amountIn: number, minOut: number, mintIn: PublicKey, mintOut: PublicKey, hops: ReturnType<typeof hop>) =>
+    program.methods
+      .routeSwap(new anchor.BN(amountIn), new anchor.BN(minOut), null)
+      .accountsPartial({ user: admin.publicKey, mintIn, mintOut, ...routePrograms })
+      .remainingAccounts(hops)
+      .rpc();
+
+  before(async () => {
+    [mintA, mintB, mintC] = [await createTestMint(), await createTestMint(), await createTestMint()];
+    routeAB = await setupPool({ seed: 57, mints: [mintA, mintB], deposit: 1_000_000 });
+    routeBC = await setupPool({ seed: 58, mints: [mintB, mintC], deposit: 1_000_000 });
+    twinAB = await setupPool({ seed: 59, mints: [mintA, mintB], deposit: 1_000_000 });
+    twinBC = await setupPool({ seed: 60, mints: [mintB, mintC], deposit: 1_000_000 });
+    [ataB, ataC] = [routeAB.userAtaY, routeBC.userAtaY];
+  });
+
+  it("Pays the same as two separate swaps without touching the intermediate balance", async () => {
+    const amountIn = new anchor.BN(100_000);
+
+    // Reference: A -> B then B -> C on the twin pools
+    const b0 = await balance(ataB);
+    await program.methods.swap(true, amountIn, new anchor.BN(1), null).accountsPartial(twinAB.accounts).rpc();
+    const middle = new anchor.BN((await balance(ataB)) - b0);
+    const c0 = await balance(ataC);
+    await program.methods.swap(true, middle, new anchor.BN(1), null).accountsPartial(twinBC.accounts).rpc();
+    const expected = (await balance(ataC)) - c0;
+
+    const [b1, c1] = [await balance(ataB), await balance(ataC)];
+    await routeSwap(100_000, expected, mintA, mintC, [...hop(routeAB, mintA, mintB), ...hop(routeBC, mintB, mintC)]);
+
+    assert.equal((await balance(ataC)) - c1, expected);
+    assert.equal(await balance(ataB), b1);
+  });
+
+  it("Checks slippage once, on the final output", async () => {
+    await expectError(
+      routeSwap(100_000, 1_000_000, mintA, mintC, [...hop(routeAB, mintA, mintB), ...hop(routeBC, mintB, mintC)]),
+      "SlippageExceeded"
+    );
+  });
+
+  it("Rejects routes through the same pool twice", async () => {
+    await expectError(
+      routeSwap(100_000, 1, mintA, mintA, [...hop(routeAB, mintA, mintB), ...hop(routeAB, mintB, mintA)]),
+      "InvalidRoute"
+    );
+  });
+
+  it("Rejects vaults that don't belong to the hop's pool", async () => {
+    const accounts = hop(routeAB, mintA, mintB);
+    // Price hop one against the twin pool's output vault
+    accounts[4].pubkey = twinAB.vaultY;
+    await expectError(routeSwap(100_000, 1, mintA, mintB, accounts), "InvalidRoute");
+  });
+});
+
+describe("amm zaps", () => {
+  let pool: Pool;
+  let empty: Pool;
+
+  const depositSingle = (target: Pool, isX: boolean, amount: number, minLp: number) =>
+    program.methods
+      .depositSingle(isX, new anchor.BN(amount), new anchor.BN(minLp), null)
+      .accountsPartial(target.accounts)
+      .rpc();
+
+  const withdrawSingle = (isX: boolean, lp: number, minOut: number) =>
+    program.methods
+      .withdrawSingle(isX, new anchor.BN(lp), new anchor.BN(minOut), null)
+      .accountsPartial(pool.accounts)
+      .rpc();
+
+  before(async () => {
+    pool = await setupPool({ seed: 46, deposit: 1_000_000 });
+    empty = await setupPool({ seed: 47, mints: [pool.mintX, pool.mintY] });
+  });
+
+  it("Rejects zaps into a pool with no price yet", async () => {
+    await expectError(depositSingle(empty, true, 100_000, 0), "NoLiquidityInPool");
+  });
+
+  // 1_000_000 X / 1_000_000 Y / 1_000_000 LP at 0.3%: 48_882 of the 100_000 X are swapped
+  // for 46_471 Y, and the remaining 51_118 X and the 46_471 Y are worth about 48_735 LP
+  it("Rejects a zap deposit below the minimum LP amount", async () => {
+    await expectError(depositSingle(pool, true, 100_000, 48_800), "SlippageExceeded");
+  });
+
+  it("Deposits token X alone", async () => {
+    const xBefore = await balance(pool.userAtaX);
+    const yBefore = await balance(pool.userAtaY);
+    const lpBefore = await balance(pool.userAtaLp);
+
+    await depositSingle(pool, true, 100_000, 48_700);
+
+    assert.equal(xBefore - (await balance(pool.userAtaX)), 100_000);
+    assert.equal(await balance(pool.userAtaY), yBefore);
+    const minted = (await balance(pool.userAtaLp)) - lpBefore;
+    assert.isAtLeast(minted, 48_700);
+    assert.isAtMost(minted, 48_735);
+  });
+
+  // Withdrawing those LP tokens in X only costs the swap fee on the Y half a second time
+  it("Withdraws token X alone", async () => {
+    const xBefore = await balance(pool.userAtaX);
+    const yBefore = await balance(pool.userAtaY);
+
+    await withdrawSingle(true, 48_700, 99_000);
+
+    const received = (await balance(pool.userAtaX)) - xBefore;
+    assert.isAtLeast(received, 99_000);
+    assert.isBelow(received, 100_000);
+    assert.equal(await balance(pool.userAtaY), yBefore);
+  });
+
+  it("Rejects a zap withdrawal below the minimum output", async () => {
+    await expectError(withdrawSingle(false, 10_000, 20_000), "SlippageExceeded");
+  });
+
+  it("Zaps out every LP token against the locked liquidity", async () => {
+    await withdrawSingle(true, await balance(pool.userAtaLp), 1);
+
+    assert.equal(await balance(pool.userAtaLp), 0);
+    assert.equal((await connection.getTokenSupply(pool.mintLp)).value.amount, "1000");
+  });
+});
+
+describe("amm first deposit", () => {
+  const victim = anchor.web3.Keypair.generate();
+  let pool: Pool;
+  let victimAtaX: PublicKey;
+
+  const depositAs = (user: anchor.web3.Keypair, amount: number, maxX: number, maxY: number) =>
+    program.methods
+      .deposit(new anchor.BN(amount), new anchor.BN(maxX), new anchor.BN(maxY), null)
+      .accountsPartial({ ...pool.accounts, user: user.publicKey })
+      .signers([user])
+      .rpc();
+
+  before(async () => {
+    pool = await setupPool({ seed: 48 });
+
+    // The victim pays for their own LP token account
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: admin.publicKey,
+          toPubkey: victim.publicKey,
+          lamports: anchor.web3.LAMPORTS_PER_SOL,
+        })
+      )
+    );
+    victimAtaX = await fundedAta(pool.mintX, 10_000_000, victim.publicKey);
+    await fundedAta(pool.mintY, 10_000_000, victim.publicKey);
+  });
+
+  it("Rejects a first deposit worth no more than the locked minimum", async () => {
+    // sqrt(1_000 * 1_000) = 1_000 LP would all be locked
+    await expectError(deposit(pool, 1_000_000_000, 1_000, 1_000).rpc(), "InsufficientInitialLiquidity");
+  });
+
+  it("Mints sqrt(x * y) on the first deposit and locks the minimum", async () => {
+    // The requested amount is ignored: 1_001 X and 1_001 Y are worth 1_001 LP
+    await deposit(pool, 1_000_000_000, 1_001, 1_001).rpc();
+
+    const lockedLp = getAssociatedTokenAddressSync(pool.mintLp, poolPda("lock", pool.config), true);
+    assert.equal(await balance(pool.userAtaLp), 1);
+    assert.equal(await balance(lockedLp), 1_000);
+  });
+
+  it("Counts tokens donated before the first deposit in the initial reserves", async () => {
+    const empty = await setupPool({ seed: 61 });
+    await transfer(connection, admin.payer, empty.userAtaX, empty.vaultX, admin.payer, 5_000);
+
+    // sqrt(15_000 * 10_000) = 12_247 LP, of which 1_000 are locked
+    await deposit(empty, 1, 10_000, 10_000).rpc();
+    assert.equal(await balance(empty.userAtaLp), 11_247);
+    assert.equal((await connection.getTokenSupply(empty.mintLp)).value.amount, "12247");
+  });
+
+  // The attacker donates straight to the vaults to make their single LP token worth ~1_000 X
+  // and ~1_000 Y, hoping to round later deposits in their favor
+  it("Makes inflating the share price with a donation unprofitable", async () => {
+    const attackerXBefore = await balance(pool.userAtaX);
+    const victimXBefore = await balance(victimAtaX);
+
+    await transfer(connection, admin.payer, pool.userAtaX, pool.vaultX, admin.payer, 1_000_000);
+    await transfer(connection, admin.payer, pool.userAtaY, pool.vaultY, admin.payer, 1_000_000);
+
+    // The victim buys 100 LP at the inflated price and withdraws them, losing at most rounding
+    await depositAs(victim, 100, 110_000, 110_000);
+    await program.methods
+      .withdraw(new anchor.BN(100), new anchor.BN(1), new anchor.BN(1), null)
+      .accountsPartial({ ...pool.accounts, user: victim.publicKey })
+      .signers([victim])
+      .rpc();
+    assert.isAtMost(victimXBefore - (await balance(victimAtaX)), 100);
+
+    // The attacker withdraws their LP token, but 1_000 / 1_001 of the donation stays locked
+    await program.methods
+      .withdraw(new anchor.BN(1), new anchor.BN(1), new anchor.BN(1), null)
+      .accountsPartial(pool.accounts)
+      .rpc();
+    assert.isAbove(attackerXBefore - (await balance(pool.userAtaX)), 990_000);
+  });
+});
+
+describe("amm factory", () => {
+  const outsider = anchor.web3.Keypair.generate();
+  let mintX: PublicKey;
+  let mintY: PublicKey;
+
+  const [factoryPda] = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from("factory")], program.programId);
+
+  // Clients find the canonical pool from the sorted mints, the fee and the curve alone
+  const pairFor = (fee: number, curve = 0) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("pair"),
+        mintX.toBuffer(),
+        mintY.toBuffer(),
+        new anchor.BN(fee).toArrayLike(Buffer, "le", 2),
+        Buffer.from([curve]),
+      ],
+      program.programId
+    )[0];
+
+  const createPool = (seed: number, fee: number, x = mintX, y = mintY, curve: Curve = { constantProduct: {} }, amp = 0) =>
+    program.methods
+      .createPool(new anchor.BN(seed), fee, curve, new anchor.BN(amp))
+      .accountsPartial({ creator: admin.publicKey, mintX: x, mintY: y, config: configFor(seed), ...tokenPrograms })
+      .rpc();
+
+  before(async () => {
+    [mintX, mintY] = [await createTestMint(), await createTestMint()].sort((a, b) =>
+      Buffer.compare(a.toBuffer(), b.toBuffer())
+    );
+
+    await program.methods.initializeFactory([5, 30]).accounts({ authority: admin.publicKey }).rpc();
+  });
+
+  it("Manages fee tiers with the factory authority only", async () => {
+    await expectError(
+      program.methods.addFeeTier(100).accounts({ authority: outsider.publicKey }).signers([outsider]).rpc(),
+      "InvalidAuthority"
+    );
+    await expectError(program.methods.addFeeTier(30).accounts({ authority: admin.publicKey }).rpc(), "FeeTierExists");
+    await expectError(program.methods.addFeeTier(1_001).accounts({ authority: admin.publicKey }).rpc(), "InvalidFee");
+
+    await program.methods.addFeeTier(100).accounts({ authority: admin.publicKey }).rpc();
+    await program.methods.removeFeeTier(5).accounts({ authority: admin.publicKey }).rpc();
+
+    const factory = await program.account.factory.fetch(factoryPda);
+    assert.deepEqual(factory.feeTiers, [30, 100]);
+    assert.isTrue(factory.authority.equals(admin.publicKey));
+  });
+
+  it("Rejects unsorted mints and fees outside the tiers", async () => {
+    await expectError(createPool(49, 30, mintY, mintX), "MintsNotSorted");
+    await expectError(createPool(49, 5, mintX, mintY), "FeeTierNotAllowed");
+  });
+
+  it("Registers the canonical pool of a pair and fee tier", async () => {
+    await createPool(49, 30);
+
+    const pair = await program.account.pair.fetch(pairFor(30));
+    assert.isTrue(pair.config.equals(configFor(49)));
+    assert.equal(pair.fee, 30);
+
+    assert.deepEqual(pair.curveType, { constantProduct: {} });
+
+    // The factory, not the creator's choice, manages canonical pools
+    const config = await program.account.config.fetch(configFor(49));
+    assert.isTrue(config.authority.equals(factoryPda));
+    assert.equal(config.fee, 30);
+  });
+
+  it("Allows a single pool per pair, fee tier and curve", async () => {
+    // The system program refuses to create the registry entry a second time
+    await expectError(createPool(50, 30), "custom program error: 0x0");
+
+    // Another fee tier is a different canonical pool
+    await createPool(50, 100);
+    const pair = await program.account.pair.fetch(pairFor(100));
+    assert.isTrue(pair.config.equals(configFor(50)));
+
+    // So is another curve in the same fee tier
+    await createPool(62, 30, mintX, mintY, { stableSwap: {} }, 100);
+    const stablePair = await program.account.pair.fetch(pairFor(30, 1));
+    assert.isTrue(stablePair.config.equals(configFor(62)));
+    assert.deepEqual(stablePair.curveType, { stableSwap: {} });
+  });
+
+  it("Manages factory pools through the current factory authority", async () => {
+    const newAuthority = anchor.web3.Keypair.generate();
+    const accounts = (authority: PublicKey) => ({ authority, config: configFor(49), factory: factoryPda });
+
+    // The factory must be passed in, and only its authority gets through
+    await expectError(
+      program.methods.lock().accounts({ authority: admin.publicKey, config: configFor(49), factory: null }).rpc(),
+      "InvalidAuthority"
+    );
+    await expectError(
+      program.methods.lock().accounts(accounts(outsider.publicKey)).signers([outsider]).rpc(),
+      "InvalidAuthority"
+    );
+    await program.methods.lock().accounts(accounts(admin.publicKey)).rpc();
+    await program.methods.unlock().accounts(accounts(admin.publicKey)).rpc();
+
+    // The fee stays the fee of the tier, and the pool stays with the factory
+    await expectError(program.methods.updateFee(50).accounts(accounts(admin.publicKey)).rpc(), "FactoryPool");
+    await expectError(program.methods.enableDynamicFee(10, 100).accounts(accounts(admin.publicKey)).rpc(), "FactoryPool");
+    await expectError(
+      program.methods.setAuthority(outsider.publicKey).accounts(accounts(admin.publicKey)).rpc(),
+      "FactoryPool"
+    );
+
+    // Handing the factory over hands over the pools it already created
+    await program.methods.setFactoryAuthority(newAuthority.publicKey).accounts({ authority: admin.publicKey }).rpc();
+    await expectError(program.methods.lock().accounts(accounts(admin.publicKey)).rpc(), "InvalidAuthority");
+    await program.methods.lock().accounts(accounts(newAuthority.publicKey)).signers([newAuthority]).rpc();
+    await program.methods.unlock().accounts(accounts(newAuthority.publicKey)).signers([newAuthority]).rpc();
+
+    await program.methods
+      .setFactoryAuthority(admin.publicKey)
+      .accounts({ authority: newAuthority.publicKey })
+      .signers([newAuthority])
+      .rpc();
+  });
+});
+
+describe("amm flash loans", () => {
+  let pool: Pool;
+
+  const borrow = (amountX: number, amountY: number) =>
+    program.methods.flashBorrow(new anchor.BN(amountX), new anchor.BN(amountY)).accountsPartial(pool.accounts).instruction();
+  const repay = () => program.methods.flashRepay().accountsPartial(pool.accounts).instruction();
+  const send = async (...instructions: Promise<anchor.web3.TransactionInstruction>[]) =>
+    provider.sendAndConfirm(new anchor.web3.Transaction().add(...(await Promise.all(instructions))));
+
+  before(async () => {
+    pool = await setupPool({ seed: 51, deposit: 1_000_000 });
+  });
+
+  it("Only lends once the pool authority enables flash loans", async () => {
+    await expectError(send(borrow(100_000, 0), repay()), "FlashLoansDisabled");
+
+    await program.methods.enableFlashLoans().accounts(pool.adminAccounts).rpc();
+    await program.methods.disableFlashLoans().accounts(pool.adminAccounts).rpc();
+    await expectError(send(borrow(100_000, 0), repay()), "FlashLoansDisabled");
+
+    await program.methods.enableFlashLoans().accounts(pool.adminAccounts).rpc();
+    assert.isTrue((await program.account.config.fetch(pool.config)).flashLoans);
+  });
+
+  // The flash loan fee starts at the 0.3% swap fee: ceil(100_000 * 0.003) = 300 X
+  it("Lends and takes back the loan plus the fee in one transaction", async () => {
+    const userBefore = await balance(pool.userAtaX);
+    const vaultBefore = await balance(pool.vaultX);
+
+    await send(borrow(100_000, 0), repay());
+
+    assert.equal(userBefore - (await balance(pool.userAtaX)), 300);
+    assert.equal((await balance(pool.vaultX)) - vaultBefore, 300);
+    const config = await program.account.config.fetch(pool.config);
+    assert.isTrue(config.flashLoanX.isZero() && config.flashLoanY.isZero());
+  });
+
+  it("Refuses to lend without a repayment later in the transaction", async () => {
+    await expectError(send(borrow(100_000, 0)), "MissingFlashRepay");
+    await expectError(send(repay(), borrow(100_000, 0)), "NoFlashLoan");
+  });
+
+  it("Blocks the pool's other instructions until the loan is repaid", async () => {
+    const swap = program.methods
+      .swap(true, new anchor.BN(10_000), new anchor.BN(1), null)
+      .accountsPartial(pool.accounts)
+      .instruction();
+    await expectError(send(borrow(100_000, 100_000), swap, repay()), "FlashLoanActive");
+    await expectError(send(borrow(100_000, 0), borrow(1, 0), repay()), "FlashLoanActive");
+  });
+
+  it("Never lends more than the reserves", async () => {
+    await expectError(send(borrow(2_000_000, 0), repay()), "InsufficientBalance");
+  });
+});
+
+describe("amm farming", () => {
+  const outsider = anchor.web3.Keypair.generate();
+  let pool: Pool;
+  let mintReward: PublicKey;
+  let farmPda: PublicKey;
+  let userAtaReward: PublicKey;
+
+  const stakeAccounts = () => ({
+    user: admin.publicKey,
+    config: pool.config,
+    mintReward,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    tokenProgramReward: TOKEN_PROGRAM_ID,
+  });
+
+  const fund = (amount: number, duration: number, authority = admin.publicKey) =>
+    program.methods
+      .fundFarm(new anchor.BN(amount), new anchor.BN(duration))
+      .accountsPartial({ authority, farm: farmPda, mintReward, tokenProgramReward: TOKEN_PROGRAM_ID });
+
+  before(async () => {
+    pool = await setupPool({ seed: 52, deposit: 1_000_000 });
+    farmPda = poolPda("farm", pool.config);
+    mintReward = await createTestMint();
+    userAtaReward = await fundedAta(mintReward, 10_000_000);
+  });
+
+  it("Lets only the pool authority create and fund the farm", async () => {
+    const initializeFarm = (authority: PublicKey) =>
+      program.methods
+        .initializeFarm()
+        .accountsPartial({
+          authority,
+          config: pool.config,
+          factory: null,
+          mintReward,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          tokenProgramReward: TOKEN_PROGRAM_ID,
+        });
+
+    await expectError(initializeFarm(outsider.publicKey).signers([outsider]).rpc(), "InvalidAuthority");
+    await initializeFarm(admin.publicKey).rpc();
+
+    await expectError(fund(1_000_000, 100, outsider.publicKey).signers([outsider]).rpc(), "InvalidAuthority");
+    await expectError(fund(1_000_000, 0).rpc(), "InvalidRewardPeriod");
+
+    // 1_000_000 reward tokens over 100 seconds
+    await fund(1_000_000, 100).rpc();
+    const farm = await program.account.farm.fetch(farmPda);
+    assert.equal(farm.rewardRate.toNumber(), 10_000);
+    assert.equal(farm.periodEnd.sub(farm.lastUpdate).toNumber(), 100);
+  });
+
+  it("Pays the sole staker the whole emission rate", async () => {
+    await program.methods.stakeLp(new anchor.BN(500_000)).accountsPartial(stakeAccounts()).rpc();
+    const stakedAt = (await program.account.farm.fetch(farmPda)).lastUpdate;
+
+    await new Promise((resolve) => setTimeout(resolve, 2_000));
+    const before = await balance(userAtaReward);
+    await program.methods.harvest().accountsPartial(stakeAccounts()).rpc();
+    const harvestedAt = (await program.account.farm.fetch(farmPda)).lastUpdate;
+
+    const expected = 10_000 * harvestedAt.sub(stakedAt).toNumber();
+    const harvested = (await balance(userAtaReward)) - before;
+    assert.isAbove(expected, 0);
+    // Reward per share rounds down, losing at most one token
+    assert.isAtLeast(harvested, expected - 1);
+    assert.isAtMost(harvested, expected);
+  });
+
+  it("Returns staked LP tokens up to the amount staked", async () => {
+    await expectError(
+      program.methods.unstakeLp(new anchor.BN(500_001)).accountsPartial(stakeAccounts()).rpc(),
+      "InsufficientBalance"
+    );
+
+    const before = await balance(pool.userAtaLp);
+    await program.methods.unstakeLp(new anchor.BN(500_000)).accountsPartial(stakeAccounts()).rpc();
+    assert.equal((await balance(pool.userAtaLp)) - before, 500_000);
+    assert.equal((await program.account.farm.fetch(farmPda)).totalStaked.toNumber(), 0);
+  });
+
+  it("Stretches the remaining rewards over an extended period", async () => {
+    await fund(0, 1_000).rpc();
+
+    const farm = await program.account.farm.fetch(farmPda);
+    assert.equal(farm.periodEnd.sub(farm.lastUpdate).toNumber(), 1_000);
+    assert.isAbove(farm.rewardRate.toNumber(), 0);
+    assert.isBelow(farm.rewardRate.toNumber(), 1_000);
+  });
+});
+
+describe("amm dynamic fees", () => {
+  let pool: Pool;
+
+  // Swaps and returns the fee rate reported by the Swap event
+  const swap = async (isX: boolean, amountIn: number) => {
+    const signature = await program.methods
+      .swap(isX, new anchor.BN(amountIn), new anchor.BN(1), null)
+      .accountsPartial(pool.accounts)
+      .rpc({ commitment: "confirmed" });
+    return (await eventOf(signature, "Swap")).feeBps as number;
+  };
+
+  before(async () => {
+    pool = await setupPool({ seed: 53, deposit: 1_000_000 });
+  });
+
+  it("Charges the static fee until the dynamic fee is enabled", async () => {
+    assert.equal(await swap(true, 10_000), 30);
+  });
+
+  it("Rejects dynamic fee bounds out of order or above the maximum", async () => {
+    await expectError(program.methods.enableDynamicFee(50, 10).accounts(pool.adminAccounts).rpc(), "InvalidFee");
+    await expectError(program.methods.enableDynamicFee(10, 1_001).accounts(pool.adminAccounts).rpc(), "InvalidFee");
+    await program.methods.enableDynamicFee(10, 100).accounts(pool.adminAccounts).rpc();
+  });
+
+  // Volatility adds one basis point of fee per 10 basis points of price movement
+  it("Raises the fee with the price movement of previous swaps", async () => {
+    assert.equal(await swap(true, 10_000), 10);
+
+    const { volatility } = await program.account.config.fetch(pool.config);
+    assert.isTrue(volatility.gtn(0));
+    assert.equal(await swap(false, 10_000), 10 + Math.floor(volatility.toNumber() / 10));
+  });
+
+  it("Caps the fee at the maximum however large the move", async () => {
+    await swap(true, 1_000_000);
+
+    const { volatility } = await program.account.config.fetch(pool.config);
+    assert.equal(volatility.toNumber(), 900);
+    assert.equal(await swap(false, 10_000), 100);
+  });
+
+  it("Returns to the static fee once disabled", async () => {
+    await program.methods.disableDynamicFee().accounts(pool.adminAccounts).rpc();
+
+    const config = await program.account.config.fetch(pool.config);
+    assert.isFalse(config.dynamicFee);
+    assert.isTrue(config.volatility.isZero());
+    assert.equal(await swap(true, 10_000), 30);
+  });
+});
+
+describe("amm events", () => {
+  const options = { seed: 54, authority: null };
+  let pool: Pool;
+
+  before(async () => {
+    pool = await preparePool(options);
+  });
+
+  it("Emits PoolInitialized with the pool parameters", async () => {
+    const signature = await initializePool(pool, options).rpc({ commitment: "confirmed" });
+
+    const event = await eventOf(signature, "PoolInitialized");
+    assert.isTrue(event.config.equals(pool.config));
+    assert.isTrue(event.mintX.equals(pool.mintX) && event.mintY.equals(pool.mintY));
+    assert.equal(event.fee, 30);
+    assert.deepEqual(event.curveType, { constantProduct: {} });
+    assert.isNull(event.authority);
+  });
+
+  // sqrt(1_000_000 * 1_000_000) = 1_000_000 LP, of which 1_000 are locked
+  it("Emits LiquidityAdded with the LP amount minted", async () => {
+    const signature = await deposit(pool, 1_000_000).rpc({ commitment: "confirmed" });
+
+    const event = await eventOf(signature, "LiquidityAdded");
+    assert.isTrue(event.user.equals(admin.publicKey));
+    assert.equal(event.amountX.toNumber(), 1_000_000);
+    assert.equal(event.amountY.toNumber(), 1_000_000);
+    assert.equal(event.lpAmount.toNumber(), 999_000);
+    assert.equal(event.reserveX.toNumber(), 1_000_000);
+    assert.equal(event.reserveY.toNumber(), 1_000_000);
+  });
+
+  it("Emits Swap with the amounts, fee and reserves after the swap", async () => {
+    const signature = await program.methods
+      .swap(true, new anchor.BN(10_000), new anchor.BN(1), null)
+      .accountsPartial(pool.accounts)
+      .rpc({ commitment: "confirmed" });
+
+    const event = await eventOf(signature, "Swap");
+    assert.isTrue(event.isX);
+    assert.equal(event.amountIn.toNumber(), 10_000);
+    assert.equal(event.feeBps, 30);
+    assert.isTrue(event.fee.gtn(0));
+    assert.isTrue(event.protocolFee.isZero());
+    assert.equal(event.reserveX.toNumber(), 1_010_000);
+    assert.equal(event.reserveY.toNumber(), 1_000_000 - event.amountOut.toNumber());
+  });
+
+  it("Emits LiquidityRemoved with the LP amount burned", async () => {
+    const { reserveX, reserveY } = await eventOf(
+      await program.methods
+        .swap(false, new anchor.BN(1_000), new anchor.BN(1), null)
+        .accountsPartial(pool.accounts)
+        .rpc({ commitment: "confirmed" }),
+      "Swap"
+    );
+    const signature = await program.methods
+      .withdraw(new anchor.BN(100_000), new anchor.BN(0), new anchor.BN(0), null)
+      .accountsPartial(pool.accounts)
+      .rpc({ commitment: "confirmed" });
+
+    const event = await eventOf(signature, "LiquidityRemoved");
+    assert.equal(event.lpAmount.toNumber(), 100_000);
+    assert.equal(event.reserveX.toNumber(), reserveX.toNumber() - event.amountX.toNumber());
+    assert.equal(event.reserveY.toNumber(), reserveY.toNumber() - event.amountY.toNumber());
+    assert.isTrue(event.amountX.gtn(0) && event.amountY.gtn(0));
+  });
+});
+
+describe("amm quotes", () => {
+  // Each curve gets its own pool; every quote is checked against the instruction it predicts
+  const curves: { name: string; options: PoolOptions }[] = [
+    { name: "constant product", options: { seed: 55, balance: 100_000_000 } },
+    { name: "StableSwap", options: { seed: 56, curve: { stableSwap: {} }, amp: 100, balance: 100_000_000 } },
+  ];
+
+  for (const { name, options } of curves) {
+    describe(name, () => {
+      let pool: Pool;
+      let quoteAccounts: Record<string, PublicKey>;
+
+      // Token and LP balance changes of the user across an instruction
+      const changes = async (send: () => Promise<unknown>) => {
+        const atas = [pool.userAtaX, pool.userAtaY, pool.userAtaLp];
+        const before = await Promise.all(atas.map((ata) => balance(ata)));
+        await send();
+        const after = await Promise.all(atas.map((ata) => balance(ata)));
+        return { x: after[0] - before[0], y: after[1] - before[1], lp: after[2] - before[2] };
+      };
+
+      before(async () => {
+        pool = await setupPool(options);
+        quoteAccounts = { config: pool.config, mintLp: pool.mintLp, vaultX: pool.vaultX, vaultY: pool.vaultY };
+        await program.methods.setProtocolFee(2_000).accounts(pool.adminAccounts).rpc();
+        await deposit(pool, 10_000_000).rpc();
+      });
+
+      it("Quotes exact-input swaps to the unit", async () => {
+        for (const [isX, amountIn] of [[true, 1_000], [false, 250_000], [true, 3_000_000]] as const) {
+          const quote = await program.methods
+            .quoteSwapExactIn(isX, new anchor.BN(amountIn))
+            .accountsPartial(quoteAccounts)
+            .view();
+
+          const delta = await changes(() =>
+            program.methods.swap(isX, new anchor.BN(amountIn), new anchor.BN(1), null).accountsPartial(pool.accounts).rpc()
+          );
+          assert.equal(isX ? -delta.x : -delta.y, quote.amountIn.toNumber());
+          assert.equal(isX ? delta.y : delta.x, quote.amountOut.toNumber());
+        }
+      });
+
+      it("Quotes exact-output swaps to the unit", async () => {
+        for (const [isX, amountOut] of [[false, 1_000], [true, 400_000]] as const) {
+          const quote = await program.methods
+            .quoteSwapExactOut(isX, new anchor.BN(amountOut))
+            .accountsPartial(quoteAccounts)
+            .view();
+
+          // The quoted input is also the tightest limit the swap accepts
+          const delta = await changes(() =>
+            program.methods
+              .swapExactOut(isX, new anchor.BN(amountOut), quote.amountIn, null)
+              .accountsPartial(pool.accounts)
+              .rpc()
+          );
+          assert.equal(isX ? -delta.x : -delta.y, quote.amountIn.toNumber());
+          assert.equal(isX ? delta.y : delta.x, amountOut);
+        }
+      });
+
+      it("Reports the fee rate, effective fee and growing price impact", async () => {
+        const small = await program.methods
+          .quoteSwapExactIn(true, new anchor.BN(10_000))
+          .accountsPartial(quoteAccounts)
+          .view();
+        const large = await program.methods
+          .quoteSwapExactIn(true, new anchor.BN(5_000_000))
+          .accountsPartial(quoteAccounts)
+          .view();
+
+        assert.equal(small.feeBps, 30);
+        assert.equal(small.effectiveFeeBps.toNumber(), 30);
+        assert.equal(small.protocolFee.toNumber(), Math.floor(small.fee.toNumber() * 0.2));
+        assert.isTrue(large.priceImpactBps.gt(small.priceImpactBps));
+      });
+
+      it("Quotes deposits and withdrawals to the unit", async () => {
+        const lp = new anchor.BN(123_457);
+
+        const quoted = await program.methods.quoteDeposit(lp).accountsPartial(quoteAccounts).view();
+        // The quoted amounts are also the tightest limits the deposit accepts
+        const added = await changes(() =>
+          program.methods.deposit(lp, quoted.amountX, quoted.amountY, null).accountsPartial(pool.accounts).rpc()
+        );
+        assert.deepEqual(added, { x: -quoted.amountX.toNumber(), y: -quoted.amountY.toNumber(), lp: lp.toNumber() });
+
+        const withdraw = await program.methods.quoteWithdraw(lp).accountsPartial(quoteAccounts).view();
+        const removed = await changes(() =>
+          program.methods.withdraw(lp, withdraw.amountX, withdraw.amountY, null).accountsPartial(pool.accounts).rpc()
+        );
+        assert.deepEqual(removed, { x: withdraw.amountX.toNumber(), y: withdraw.amountY.toNumber(), lp: -lp.toNumber() });
+      });
+    });
+  }
+});